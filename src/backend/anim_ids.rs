@@ -0,0 +1,75 @@
+//! SV stores motions in the 20000 range (`pmXXXX_YY_ZZ_20030_foo.tranm`), while ZA's tracr
+//! references the same motion twice: once in the 00000 range and once in the 10000 range
+//!
+//! ```text
+//! ZA 00000..=09999 -> SV 20000 + id
+//! ZA 10000..=19999 -> SV 20000 + (id - 10000)
+//! ZA 20000..=29999 -> SV id (already SV-style)
+//! anything else    -> no SV equivalent
+//! ```
+
+/// Maps a ZA motion id to the SV motion id that provides its data
+pub(crate) fn sv_id_from_za_id(za_id: i32) -> Option<i32> {
+    if (0..=9999).contains(&za_id) {
+        return Some(20000 + za_id);
+    }
+    if (10000..=19999).contains(&za_id) {
+        return Some(20000 + (za_id - 10000));
+    }
+    if (20000..=29999).contains(&za_id) {
+        return Some(za_id);
+    }
+    None
+}
+
+/// Maps an SV motion id (20000 range) to the two ZA ids (00000 and 10000 ranges) it is mirrored to
+pub(crate) fn za_ids_from_sv_motion_id(sv_id: i32) -> Option<(i32, i32)> {
+    if !(20000..=29999).contains(&sv_id) {
+        return None;
+    }
+    let za0 = sv_id - 20000;
+    Some((za0, 10000 + za0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sv_id_from_za_id_boundaries() {
+        for (za, sv) in [
+            (-1, None),
+            (0, Some(20000)),
+            (9999, Some(29999)),
+            (10000, Some(20000)),
+            (19999, Some(29999)),
+            (20000, Some(20000)),
+            (29999, Some(29999)),
+            (30000, None),
+        ] {
+            assert_eq!(sv_id_from_za_id(za), sv, "za id {za}");
+        }
+    }
+
+    #[test]
+    fn za_ids_from_sv_motion_id_boundaries() {
+        for (sv, za) in [
+            (19999, None),
+            (20000, Some((0, 10000))),
+            (20030, Some((30, 10030))),
+            (29999, Some((9999, 19999))),
+            (30000, None),
+        ] {
+            assert_eq!(za_ids_from_sv_motion_id(sv), za, "sv id {sv}");
+        }
+    }
+
+    #[test]
+    fn za_ids_map_back_to_their_sv_id() {
+        for sv in [20000, 20030, 29999] {
+            let (za0, za1) = za_ids_from_sv_motion_id(sv).unwrap();
+            assert_eq!(sv_id_from_za_id(za0), Some(sv));
+            assert_eq!(sv_id_from_za_id(za1), Some(sv));
+        }
+    }
+}
@@ -1,4 +1,6 @@
+use crate::backend::anim_ids::sv_id_from_za_id;
 use crate::fb::raw::FbBuf;
+use crate::paths::pm_variant_dir_name;
 use crate::progress::ProgressSink;
 use serde::Serialize;
 use std::{fs, path::Path};
@@ -20,11 +22,7 @@ pub fn sync_tracr_resources_from_sv(
     sv_pm_dir: &Path,
     progress: &ProgressSink,
 ) -> anyhow::Result<AnimSyncStats> {
-    let pm_variant = target_pm_dir
-        .file_name()
-        .ok_or_else(|| anyhow::anyhow!("unexpected pm dir: {target_pm_dir:?}"))?
-        .to_string_lossy()
-        .to_string();
+    let pm_variant = pm_variant_dir_name(target_pm_dir)?;
 
     let mut stats = AnimSyncStats {
         pm_variant: pm_variant.clone(),
@@ -92,7 +90,7 @@ pub fn sync_tracr_resources_from_sv(
             let mut src = sv_pm_dir.join(&filename);
             if !src.is_file() {
                 if let Some(za_id) = za_id {
-                    if let Some(sv_id) = src_id_from_za_id(za_id) {
+                    if let Some(sv_id) = sv_id_from_za_id(za_id) {
                         let cand = format!("{pm_variant}_{sv_id:05}_{suffix}.{ext}");
                         let p = sv_pm_dir.join(&cand);
                         if p.is_file() {
@@ -146,10 +144,13 @@ pub fn sync_tracr_resources_from_sv(
     }
 
     if stats.filled > 0 || stats.missing_src > 0 || stats.missing_after > 0 {
-        progress.info(format!(
-            "[anim] {pm_variant}: tracks={} refs={} filled={} missing_src={} missing_after={}",
-            stats.tracks, stats.refs, stats.filled, stats.missing_src, stats.missing_after
-        ));
+        progress.detail(
+            1,
+            format!(
+                "[anim] {pm_variant}: tracks={} refs={} filled={} missing_src={} missing_after={}",
+                stats.tracks, stats.refs, stats.filled, stats.missing_src, stats.missing_after
+            ),
+        );
     }
 
     Ok(stats)
@@ -171,19 +172,6 @@ fn parse_track_name(track_name: &str) -> (Option<i32>, String) {
     (Some(id), rest.to_string())
 }
 
-fn src_id_from_za_id(za_id: i32) -> Option<i32> {
-    if (0..=9999).contains(&za_id) {
-        return Some(20000 + za_id);
-    }
-    if (10000..=19999).contains(&za_id) {
-        return Some(20000 + (za_id - 10000));
-    }
-    if (20000..=29999).contains(&za_id) {
-        return Some(za_id);
-    }
-    None
-}
-
 fn pick_fallback(sv_pm_dir: &Path, pm_variant: &str, ext: &str) -> Option<std::path::PathBuf> {
     let candidates = [
         format!("{pm_variant}_20000_defaultwait01_loop.{ext}"),
@@ -200,11 +188,11 @@ fn pick_fallback(sv_pm_dir: &Path, pm_variant: &str, ext: &str) -> Option<std::p
         }
     }
     let pat2 = format!("{pm_variant}_2????_*.{ext}");
-    for e in glob_in_dir(sv_pm_dir, &pat2) {
+    if let Some(e) = glob_in_dir(sv_pm_dir, &pat2).into_iter().next() {
         return Some(e);
     }
     let pat_any = format!("{pm_variant}_?????_*.{ext}");
-    for e in glob_in_dir(sv_pm_dir, &pat_any) {
+    if let Some(e) = glob_in_dir(sv_pm_dir, &pat_any).into_iter().next() {
         return Some(e);
     }
     None
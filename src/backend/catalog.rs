@@ -1,10 +1,14 @@
 use crate::{
-    fb::trpmcatalog::{self, CatalogEntryLite, SpeciesKey},
-    paths::find_under,
+    backend::names,
+    error::SvzaError,
+    fb::trpmcatalog::{self, CatalogDoc, CatalogEntryLite, SpeciesKey},
+    paths::{canonicalish, find_under, is_pm_dir, parse_model_path, parse_pm_variant_name},
     progress::ProgressSink,
+    template::{Key, KeyRange},
 };
+use serde::Serialize;
 use std::{
-    collections::HashSet,
+    collections::{BTreeSet, HashSet},
     fs,
     path::{Path, PathBuf},
 };
@@ -23,12 +27,156 @@ pub struct CatalogSelection {
     pub za_catalog: PathBuf,
     pub entries: Vec<SelectedMon>,
     pub unique_pm_variants: Vec<(String, String)>,
+    /// ZA catalog entries whose key has no SV counterpart
+    pub za_only: Vec<ZaOnlyEntry>,
+    /// SV catalog entries left out of `entries`, with the reason
+    pub skipped: Vec<SkippedEntry>,
+    /// Every `(species, form, gender)` in the SV catalog
+    pub sv_keys: BTreeSet<(u16, u16, u8)>,
+    /// Every `(species, form, gender)` in the ZA catalog
+    pub za_keys: BTreeSet<(u16, u16, u8)>,
+    /// Whether keys already in the ZA catalog were allowed into `entries`
+    pub includes_za_keys: bool,
+}
+
+impl CatalogSelection {
+    /// SV catalog pm_variants skipped because their assets aren't under the SV root,
+    /// limited to keys `filter` keeps
+    pub fn missing_asset_variants(&self, filter: &KeyFilter) -> Vec<(String, String)> {
+        let mut out = self
+            .skipped
+            .iter()
+            .filter(|s| matches!(s.reason, SkipReason::MissingAssets))
+            .filter(|s| {
+                filter.matches(SpeciesKey {
+                    species: s.species,
+                    form: s.form,
+                    gender: s.gender,
+                })
+            })
+            .filter_map(|s| parse_model_path(&s.model_path))
+            .collect::<Vec<_>>();
+        out.sort();
+        out.dedup();
+        out
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// model_path isn't `pmXXXX/pmXXXX_YY_ZZ/...`
+    UnparsableModelPath,
+    /// pm_variant folder is missing under the SV dump's `data/`
+    MissingAssets,
+    /// key already exists in the ZA catalog and already-in-ZA mons are skipped
+    AlreadyInZa,
+    /// key isn't among the template's selected targets
+    NotSelected,
+    /// dropped by `only_species` / `exclude_species`
+    Filtered,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedEntry {
+    pub species: u16,
+    pub form: u16,
+    pub gender: u8,
+    pub model_path: String,
+    pub reason: SkipReason,
+}
+
+impl SkippedEntry {
+    fn new(e: &CatalogEntryLite, reason: SkipReason) -> Self {
+        Self {
+            species: e.key.species,
+            form: e.key.form,
+            gender: e.key.gender,
+            model_path: e.model_path.clone(),
+            reason,
+        }
+    }
+}
+
+/// Include/exclude filter over selected keys, built from [`KeyRange`] tokens
+/// A bare species matches every form and gender; `species:form[:gender]` narrows it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyFilter {
+    only: Vec<KeyRange>,
+    exclude: Vec<KeyRange>,
+}
+
+impl KeyFilter {
+    pub fn parse(only: &[String], exclude: &[String]) -> anyhow::Result<Self> {
+        Ok(Self {
+            only: KeyRange::parse_list(only)?,
+            exclude: KeyRange::parse_list(exclude)?,
+        })
+    }
+
+    /// Filter that rejects exactly the given tokens, e.g. for keys to remove
+    pub fn excluding(tokens: &[String]) -> anyhow::Result<Self> {
+        Self::parse(&[], tokens)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.only.is_empty() && self.exclude.is_empty()
+    }
+
+    pub fn matches(&self, key: SpeciesKey) -> bool {
+        let key = Key::from(key);
+        let hit = |r: &KeyRange| r.matches(&key);
+        (self.only.is_empty() || self.only.iter().any(hit)) && !self.exclude.iter().any(hit)
+    }
+}
+
+impl CatalogSelection {
+    /// Moves entries the filter rejects into `skipped` and recomputes `unique_pm_variants`
+    pub fn apply_filter(&mut self, filter: &KeyFilter, progress: &ProgressSink) {
+        if filter.is_empty() {
+            return;
+        }
+        let before = self.entries.len();
+        let (keep, drop): (Vec<_>, Vec<_>) =
+            self.entries.drain(..).partition(|e| filter.matches(e.key));
+        self.skipped.extend(drop.into_iter().map(|e| SkippedEntry {
+            species: e.key.species,
+            form: e.key.form,
+            gender: e.key.gender,
+            model_path: e.model_path,
+            reason: SkipReason::Filtered,
+        }));
+        self.entries = keep;
+
+        let uniq: HashSet<_> = self
+            .entries
+            .iter()
+            .map(|e| (e.pm.clone(), e.pm_variant.clone()))
+            .collect();
+        self.unique_pm_variants = uniq.into_iter().collect();
+        self.unique_pm_variants.sort();
+        progress.info(format!(
+            "species filter kept {} of {} entries (unique pm_variants={})",
+            self.entries.len(),
+            before,
+            self.unique_pm_variants.len()
+        ));
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ZaOnlyEntry {
+    pub species: u16,
+    pub form: u16,
+    pub gender: u8,
+    pub model_path: String,
 }
 
 pub fn select_missing_in_za(
     poke_root: &Path,
     za_dump: &Path,
     skip_already_in_za: bool,
+    follow_links: bool,
     progress: &ProgressSink,
 ) -> anyhow::Result<CatalogSelection> {
     progress.phase_start("Catalog & selection");
@@ -37,18 +185,24 @@ pub fn select_missing_in_za(
         poke_root,
         "catalog/catalog/poke_resource_table.trpmcatalog",
         "poke_resource_table.trpmcatalog",
+        follow_links,
     )?;
     let za_catalog = find_under(
         za_dump,
         "ik_pokemon/catalog/catalog/poke_resource_table.trpmcatalog",
         "poke_resource_table.trpmcatalog",
+        follow_links,
     )?;
+    ensure_distinct_catalogs(&sv_catalog, &za_catalog)?;
 
     progress.info(format!("SV catalog: {:?}", sv_catalog));
     progress.info(format!("ZA catalog: {:?}", za_catalog));
 
     let sv_entries = read_catalog(&sv_catalog)?;
     let za_entries = read_catalog(&za_catalog)?;
+    let za_only = za_only_entries(&sv_entries, &za_entries);
+    let sv_keys = key_set(&sv_entries);
+    let za_key_set = key_set(&za_entries);
 
     let existing_pm_variants = scan_existing_pm_variants(poke_root);
     if existing_pm_variants.is_empty() {
@@ -60,6 +214,7 @@ pub fn select_missing_in_za(
 
     let za_keys: HashSet<SpeciesKey> = za_entries.iter().map(|e| e.key).collect();
     let mut filtered = Vec::new();
+    let mut skipped = Vec::new();
     let mut missing_assets = Vec::new();
 
     if skip_already_in_za {
@@ -69,14 +224,17 @@ pub fn select_missing_in_za(
     }
 
     for e in sv_entries {
-        let Some((pm, pm_variant)) = parse_pm_from_model_path(&e.model_path) else {
+        let Some((pm, pm_variant)) = parse_model_path(&e.model_path) else {
+            skipped.push(SkippedEntry::new(&e, SkipReason::UnparsableModelPath));
             continue;
         };
         if !existing_pm_variants.contains(&(pm.clone(), pm_variant.clone())) {
+            skipped.push(SkippedEntry::new(&e, SkipReason::MissingAssets));
             missing_assets.push((pm, pm_variant));
             continue;
         }
         if skip_already_in_za && za_keys.contains(&e.key) {
+            skipped.push(SkippedEntry::new(&e, SkipReason::AlreadyInZa));
             continue;
         }
         filtered.push(SelectedMon {
@@ -106,9 +264,10 @@ pub fn select_missing_in_za(
     unique_pm_variants.sort();
 
     progress.info(format!(
-        "selected species entries: {} (unique pm_variants={})",
+        "selected species entries: {} (unique pm_variants={}, skipped={})",
         filtered.len(),
-        unique_pm_variants.len()
+        unique_pm_variants.len(),
+        skipped.len()
     ));
     progress.phase_end("Catalog & selection");
 
@@ -117,6 +276,11 @@ pub fn select_missing_in_za(
         za_catalog,
         entries: filtered,
         unique_pm_variants,
+        za_only,
+        skipped,
+        sv_keys,
+        za_keys: za_key_set,
+        includes_za_keys: !skip_already_in_za,
     })
 }
 
@@ -125,6 +289,7 @@ pub fn select_by_keys(
     za_dump: &Path,
     keys: &HashSet<SpeciesKey>,
     include_already_in_za: bool,
+    follow_links: bool,
     progress: &ProgressSink,
 ) -> anyhow::Result<CatalogSelection> {
     progress.phase_start("Catalog & selection");
@@ -133,31 +298,42 @@ pub fn select_by_keys(
         poke_root,
         "catalog/catalog/poke_resource_table.trpmcatalog",
         "poke_resource_table.trpmcatalog",
+        follow_links,
     )?;
     let za_catalog = find_under(
         za_dump,
         "ik_pokemon/catalog/catalog/poke_resource_table.trpmcatalog",
         "poke_resource_table.trpmcatalog",
+        follow_links,
     )?;
+    ensure_distinct_catalogs(&sv_catalog, &za_catalog)?;
 
     let sv_entries = read_catalog(&sv_catalog)?;
     let za_entries = read_catalog(&za_catalog)?;
+    let za_only = za_only_entries(&sv_entries, &za_entries);
+    let sv_keys = key_set(&sv_entries);
+    let za_key_set = key_set(&za_entries);
     let za_keys: HashSet<SpeciesKey> = za_entries.iter().map(|e| e.key).collect();
 
     let existing_pm_variants = scan_existing_pm_variants(poke_root);
 
     let mut filtered = Vec::new();
+    let mut skipped = Vec::new();
     for e in sv_entries {
         if !keys.contains(&e.key) {
+            skipped.push(SkippedEntry::new(&e, SkipReason::NotSelected));
             continue;
         }
         if !include_already_in_za && za_keys.contains(&e.key) {
+            skipped.push(SkippedEntry::new(&e, SkipReason::AlreadyInZa));
             continue;
         }
-        let Some((pm, pm_variant)) = parse_pm_from_model_path(&e.model_path) else {
+        let Some((pm, pm_variant)) = parse_model_path(&e.model_path) else {
+            skipped.push(SkippedEntry::new(&e, SkipReason::UnparsableModelPath));
             continue;
         };
         if !existing_pm_variants.contains(&(pm.clone(), pm_variant.clone())) {
+            skipped.push(SkippedEntry::new(&e, SkipReason::MissingAssets));
             continue;
         }
         filtered.push(SelectedMon {
@@ -176,9 +352,10 @@ pub fn select_by_keys(
     unique_pm_variants.sort();
 
     progress.info(format!(
-        "selected keys: {} (unique pm_variants={})",
+        "selected keys: {} (unique pm_variants={}, skipped={})",
         filtered.len(),
-        unique_pm_variants.len()
+        unique_pm_variants.len(),
+        skipped.len()
     ));
     progress.phase_end("Catalog & selection");
 
@@ -187,6 +364,11 @@ pub fn select_by_keys(
         za_catalog,
         entries: filtered,
         unique_pm_variants,
+        za_only,
+        skipped,
+        sv_keys,
+        za_keys: za_key_set,
+        includes_za_keys: include_already_in_za,
     })
 }
 
@@ -201,25 +383,213 @@ pub fn read_catalog_map(
     Ok(out)
 }
 
+#[derive(Debug, Clone)]
+pub struct DonorCandidate {
+    pub key: SpeciesKey,
+    pub name: String,
+    pub pm_variant: String,
+}
+
+/// ZA catalog entries with their display names, sorted by key (the GUI's donor list)
+/// `search` matches name, pm_variant or species number, case-insensitively.
+pub fn list_za_donors(
+    za_dump: &Path,
+    language: &str,
+    name_format: &str,
+    search: Option<&str>,
+    follow_links: bool,
+) -> anyhow::Result<Vec<DonorCandidate>> {
+    let za_catalog = find_under(
+        za_dump,
+        "ik_pokemon/catalog/catalog/poke_resource_table.trpmcatalog",
+        "poke_resource_table.trpmcatalog",
+        follow_links,
+    )?;
+    let name_map = names::load_monsname_map(za_dump, language, false).unwrap_or_default();
+    let q = search.unwrap_or("").trim().to_ascii_lowercase();
+
+    let mut out = Vec::new();
+    for e in read_catalog(&za_catalog)? {
+        let pm_variant = parse_model_path(&e.model_path)
+            .map(|(_, v)| v)
+            .unwrap_or_default();
+        let name = name_map
+            .get(&e.key.species)
+            .cloned()
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| names::placeholder_name(name_format, e.key.species));
+        let matches = q.is_empty()
+            || name.to_ascii_lowercase().contains(&q)
+            || pm_variant.to_ascii_lowercase().contains(&q)
+            || e.key.species.to_string().contains(&q);
+        if matches {
+            out.push(DonorCandidate {
+                key: e.key,
+                name,
+                pm_variant,
+            });
+        }
+    }
+    out.sort_by_key(|d| (d.key.species, d.key.form, d.key.gender));
+    Ok(out)
+}
+
+fn key_set(entries: &[CatalogEntryLite]) -> BTreeSet<(u16, u16, u8)> {
+    entries
+        .iter()
+        .map(|e| (e.key.species, e.key.form, e.key.gender))
+        .collect()
+}
+
+fn za_only_entries(sv: &[CatalogEntryLite], za: &[CatalogEntryLite]) -> Vec<ZaOnlyEntry> {
+    let sv_keys: HashSet<SpeciesKey> = sv.iter().map(|e| e.key).collect();
+    let mut out = za
+        .iter()
+        .filter(|e| !sv_keys.contains(&e.key))
+        .map(|e| ZaOnlyEntry {
+            species: e.key.species,
+            form: e.key.form,
+            gender: e.key.gender,
+            model_path: e.model_path.clone(),
+        })
+        .collect::<Vec<_>>();
+    out.sort_by_key(|e| (e.species, e.form, e.gender));
+    out
+}
+
+/// Writes `_report/za_only.json`: ZA mons with no SV entry, i.e. what can't be back-ported
+pub fn write_za_only_report(
+    report_dir: &Path,
+    selection: &CatalogSelection,
+    progress: &ProgressSink,
+) -> anyhow::Result<PathBuf> {
+    fs::create_dir_all(report_dir)?;
+    let path = report_dir.join("za_only.json");
+    fs::write(&path, serde_json::to_vec_pretty(&selection.za_only)?)?;
+    progress.info(format!(
+        "[report] wrote {:?} (za_only={})",
+        path,
+        selection.za_only.len()
+    ));
+    Ok(path)
+}
+
+/// Writes `_report/skipped.json`: every SV catalog entry the selection dropped and why
+pub fn write_skipped_report(
+    report_dir: &Path,
+    selection: &CatalogSelection,
+    progress: &ProgressSink,
+) -> anyhow::Result<PathBuf> {
+    let mut skipped = selection.skipped.clone();
+    skipped.sort_by_key(|e| (e.species, e.form, e.gender));
+    fs::create_dir_all(report_dir)?;
+    let path = report_dir.join("skipped.json");
+    fs::write(&path, serde_json::to_vec_pretty(&skipped)?)?;
+    progress.info(format!(
+        "[report] wrote {:?} (skipped={})",
+        path,
+        skipped.len()
+    ));
+    Ok(path)
+}
+
+/// Writes `_report/coverage.csv`: one row per key in either catalog, flagging where it exists
+/// and whether this run converted it
+pub fn write_coverage_report(
+    report_dir: &Path,
+    selection: &CatalogSelection,
+    progress: &ProgressSink,
+) -> anyhow::Result<PathBuf> {
+    let converted: BTreeSet<(u16, u16, u8)> = selection
+        .entries
+        .iter()
+        .map(|e| (e.key.species, e.key.form, e.key.gender))
+        .collect();
+    let all: BTreeSet<_> = selection
+        .sv_keys
+        .union(&selection.za_keys)
+        .copied()
+        .collect();
+    let flag = |b: bool| if b { 1 } else { 0 };
+
+    let mut csv = String::from("species,form,gender,in_sv,in_za,converted\n");
+    for k @ (species, form, gender) in &all {
+        csv.push_str(&format!(
+            "{species},{form},{gender},{},{},{}\n",
+            flag(selection.sv_keys.contains(k)),
+            flag(selection.za_keys.contains(k)),
+            flag(converted.contains(k)),
+        ));
+    }
+    fs::create_dir_all(report_dir)?;
+    let path = report_dir.join("coverage.csv");
+    fs::write(&path, csv)?;
+    progress.info(format!(
+        "[report] wrote {:?} (keys={}, converted={})",
+        path,
+        all.len(),
+        converted.len()
+    ));
+    Ok(path)
+}
+
+/// Both roots resolving to one catalog (e.g. SV root pointed at the ZA dump) makes selection meaningless
+fn ensure_distinct_catalogs(sv_catalog: &Path, za_catalog: &Path) -> anyhow::Result<()> {
+    if canonicalish(sv_catalog) == canonicalish(za_catalog) {
+        anyhow::bail!(
+            "SV and ZA catalogs resolve to the same file: {:?}; check that the SV root and ZA dump point at different dumps",
+            canonicalish(sv_catalog)
+        );
+    }
+    Ok(())
+}
+
+/// Smallest buffer that can hold a flatbuffer: root uoffset, table soffset and a 4-byte vtable
+const MIN_CATALOG_LEN: usize = 12;
+
+/// Reads a catalog file, turning an unreadable, empty or truncated file into a readable error
+/// instead of the parser's "out of bounds u32 at 0"
+pub fn read_catalog_bytes(path: &Path) -> anyhow::Result<Vec<u8>> {
+    let b = fs::read(path).map_err(|e| SvzaError::CatalogParse {
+        path: path.to_path_buf(),
+        reason: format!("could not read the file: {e}"),
+    })?;
+    if b.len() < MIN_CATALOG_LEN {
+        return Err(SvzaError::CatalogParse {
+            path: path.to_path_buf(),
+            reason: format!(
+                "catalog file is empty or not a trpmcatalog ({} bytes)",
+                b.len()
+            ),
+        }
+        .into());
+    }
+    Ok(b)
+}
+
 fn read_catalog(path: &Path) -> anyhow::Result<Vec<CatalogEntryLite>> {
-    let b = fs::read(path)?;
-    trpmcatalog::read_entries(b)
+    let b = read_catalog_bytes(path)?;
+    trpmcatalog::read_entries(b).map_err(|e| catalog_parse_error(path, e))
 }
 
-fn parse_pm_from_model_path(model_path: &str) -> Option<(String, String)> {
-    let mp = model_path.replace('\\', "/");
-    let mut parts = mp.split('/').filter(|s| !s.is_empty());
-    let pm = parts.next()?.to_string();
-    let pm_variant = parts.next()?.to_string();
-    if !pm.starts_with("pm") || pm.len() != 6 {
-        return None;
+/// Every entry of the catalog at `path`; parse failures come back as `SvzaError::CatalogParse`
+pub fn read_catalog_doc(path: &Path) -> anyhow::Result<CatalogDoc> {
+    let b = read_catalog_bytes(path)?;
+    trpmcatalog::read_doc(b).map_err(|e| catalog_parse_error(path, e))
+}
+
+fn catalog_parse_error(path: &Path, e: anyhow::Error) -> anyhow::Error {
+    SvzaError::CatalogParse {
+        path: path.to_path_buf(),
+        reason: format!("{e:#}"),
     }
-    Some((pm, pm_variant))
+    .into()
 }
 
-fn scan_existing_pm_variants(poke_root: &Path) -> HashSet<(String, String)> {
+/// `(pm, pm_variant)` dirs under `poke_root/data`, ordered so anything derived from them is stable
+pub fn scan_existing_pm_variants(poke_root: &Path) -> BTreeSet<(String, String)> {
     let data_dir = poke_root.join("data");
-    let mut out = HashSet::new();
+    let mut out = BTreeSet::new();
     let Ok(pm_dirs) = fs::read_dir(&data_dir) else {
         return out;
     };
@@ -246,10 +616,7 @@ fn scan_existing_pm_variants(poke_root: &Path) -> HashSet<(String, String)> {
                 continue;
             }
             let name = v.file_name().to_string_lossy().to_string();
-            if !name.starts_with(&(pm.clone() + "_")) {
-                continue;
-            }
-            if name.split('_').count() < 3 {
+            if parse_pm_variant_name(&name).is_none_or(|p| p.pm != pm) {
                 continue;
             }
             out.insert((pm.clone(), name));
@@ -258,13 +625,143 @@ fn scan_existing_pm_variants(poke_root: &Path) -> HashSet<(String, String)> {
     out
 }
 
-fn is_pm_dir(name: &str) -> bool {
-    if name.len() != 6 {
-        return false;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(species: u16, form: u16, gender: u8) -> SpeciesKey {
+        SpeciesKey {
+            species,
+            form,
+            gender,
+        }
+    }
+
+    fn is_catalog_parse(e: &anyhow::Error) -> bool {
+        matches!(
+            e.downcast_ref::<SvzaError>(),
+            Some(SvzaError::CatalogParse { .. })
+        )
+    }
+
+    #[test]
+    fn read_catalog_bytes_rejects_empty_and_truncated_files() {
+        let td = tempfile::tempdir().unwrap();
+        let empty = td.path().join("empty.trpmcatalog");
+        fs::write(&empty, b"").unwrap();
+        let err = read_catalog_bytes(&empty).unwrap_err();
+        assert!(is_catalog_parse(&err), "{err:#}");
+        assert!(err.to_string().contains("(0 bytes)"), "{err:#}");
+
+        let truncated = td.path().join("truncated.trpmcatalog");
+        fs::write(&truncated, [0u8; MIN_CATALOG_LEN - 1]).unwrap();
+        let err = read_catalog_bytes(&truncated).unwrap_err();
+        assert!(is_catalog_parse(&err), "{err:#}");
+
+        let missing = read_catalog_bytes(&td.path().join("missing.trpmcatalog")).unwrap_err();
+        assert!(is_catalog_parse(&missing), "{missing:#}");
+    }
+
+    #[test]
+    fn read_catalog_doc_wraps_parse_failures() {
+        let td = tempfile::tempdir().unwrap();
+        let path = td.path().join("garbage.trpmcatalog");
+        // long enough to pass the size check, but the root offset points past the end
+        fs::write(&path, [0xFFu8; MIN_CATALOG_LEN]).unwrap();
+        let err = read_catalog_doc(&path).unwrap_err();
+        assert!(is_catalog_parse(&err), "{err:#}");
+    }
+
+    fn strings(tokens: &[&str]) -> Vec<String> {
+        tokens.iter().map(|t| t.to_string()).collect()
+    }
+
+    fn selection(keys: &[SpeciesKey]) -> CatalogSelection {
+        let entries = keys
+            .iter()
+            .map(|&k| {
+                let pm = format!("pm{:04}", k.species);
+                let pm_variant = format!("{pm}_{:02}_00", k.form);
+                SelectedMon {
+                    key: k,
+                    model_path: format!("{pm}/{pm_variant}/{pm_variant}.trmdl"),
+                    pm,
+                    pm_variant,
+                }
+            })
+            .collect();
+        CatalogSelection {
+            sv_catalog: PathBuf::new(),
+            za_catalog: PathBuf::new(),
+            entries,
+            unique_pm_variants: Vec::new(),
+            za_only: Vec::new(),
+            skipped: Vec::new(),
+            sv_keys: BTreeSet::new(),
+            za_keys: BTreeSet::new(),
+            includes_za_keys: false,
+        }
+    }
+
+    fn kept(only: &[&str], exclude: &[&str]) -> Vec<SpeciesKey> {
+        let filter = KeyFilter::parse(&strings(only), &strings(exclude)).unwrap();
+        let mut sel = selection(&[
+            key(25, 0, 0),
+            key(25, 0, 1),
+            key(25, 1, 0),
+            key(26, 0, 0),
+            key(27, 2, 1),
+        ]);
+        let (progress, _rx) = ProgressSink::new();
+        sel.apply_filter(&filter, &progress);
+        sel.entries.iter().map(|e| e.key).collect()
+    }
+
+    #[test]
+    fn parse_accepts_lists_and_rejects_garbage() {
+        assert!(KeyFilter::parse(&[], &[]).unwrap().is_empty());
+        assert!(!KeyFilter::parse(&strings(&["25, 26:1", " 30-32 "]), &[])
+            .unwrap()
+            .is_empty());
+        for bad in ["pikachu", "25:x", "25:0:0:0", "30-25", "-5", "25:-1"] {
+            assert!(KeyFilter::parse(&strings(&[bad]), &[]).is_err(), "{bad:?}");
+            assert!(KeyFilter::excluding(&strings(&[bad])).is_err(), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn bare_species_matches_every_form_and_gender() {
+        assert_eq!(
+            kept(&["25"], &[]),
+            [key(25, 0, 0), key(25, 0, 1), key(25, 1, 0)]
+        );
+    }
+
+    #[test]
+    fn form_and_gender_narrow_the_match() {
+        assert_eq!(kept(&["25:1"], &[]), [key(25, 1, 0)]);
+        assert_eq!(kept(&["25:0:1"], &[]), [key(25, 0, 1)]);
+        assert_eq!(kept(&["26-27"], &[]), [key(26, 0, 0), key(27, 2, 1)]);
     }
-    let b = name.as_bytes();
-    if b[0] != b'p' || b[1] != b'm' {
-        return false;
+
+    #[test]
+    fn exclude_applies_after_only() {
+        assert_eq!(kept(&["25"], &["25:0"]), [key(25, 1, 0)]);
+        assert_eq!(kept(&[], &["25", "27:2:1"]), [key(26, 0, 0)]);
+        assert_eq!(kept(&[], &[]).len(), 5);
+    }
+
+    #[test]
+    fn filtered_entries_are_recorded_as_skipped() {
+        let filter = KeyFilter::excluding(&strings(&["25"])).unwrap();
+        let mut sel = selection(&[key(25, 0, 0), key(26, 0, 0)]);
+        let (progress, _rx) = ProgressSink::new();
+        sel.apply_filter(&filter, &progress);
+        assert_eq!(sel.skipped.len(), 1);
+        assert!(matches!(sel.skipped[0].reason, SkipReason::Filtered));
+        assert_eq!(
+            sel.unique_pm_variants,
+            [("pm0026".to_string(), "pm0026_00_00".to_string())]
+        );
     }
-    b[2..].iter().all(|c| c.is_ascii_digit())
 }
@@ -0,0 +1,138 @@
+use crate::{
+    config::AppConfig,
+    paths::{canonicalish, resolve_out_root},
+    progress::ProgressSink,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Top-level game folders a run writes under the game root; nothing else is ever removed
+pub const GAME_SUBTREES: [&str; 3] = ["ik_pokemon", "param_chr", "avalon"];
+/// Tool folders a run writes under `out_root` itself
+pub const TOOL_SUBTREES: [&str; 2] = ["_report", "_cache"];
+
+/// The tool-owned subtrees that currently exist under `out_root` and `game_root`
+/// (the same folder unless the output layout nests game files, see `AppConfig::game_root`)
+pub fn existing_subtrees(out_root: &Path, game_root: &Path) -> Vec<PathBuf> {
+    GAME_SUBTREES
+        .iter()
+        .map(|name| game_root.join(name))
+        .chain(TOOL_SUBTREES.iter().map(|name| out_root.join(name)))
+        .filter(|p| p.exists())
+        .collect()
+}
+
+/// Deletes the tool-owned subtrees under the output root, leaving anything else in place
+/// Refuses when the output root is (or would delete) the SV or ZA dump; returns the number removed
+pub fn clean_output(cfg: &AppConfig, progress: &ProgressSink) -> anyhow::Result<usize> {
+    let out_root = resolve_out_root(
+        cfg.out_root
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Output root not set"))?,
+    )?;
+    if out_root.as_os_str().is_empty() || out_root.parent().is_none() {
+        anyhow::bail!("refusing to clean output root {out_root:?}");
+    }
+    let game_root = cfg.game_root(&out_root)?;
+    for (label, dump) in [("SV root", &cfg.sv_root), ("ZA dump", &cfg.za_dump)] {
+        let Some(dump) = dump.as_ref().map(|p| canonicalish(p)) else {
+            continue;
+        };
+        if dump == out_root {
+            anyhow::bail!("refusing to clean: output root is the {label} ({dump:?})");
+        }
+        if let Some(sub) = existing_subtrees(&out_root, &game_root)
+            .into_iter()
+            .find(|sub| dump.starts_with(canonicalish(sub)))
+        {
+            anyhow::bail!("refusing to clean: {label} {dump:?} lives under {sub:?}");
+        }
+    }
+
+    let subtrees = existing_subtrees(&out_root, &game_root);
+    if subtrees.is_empty() {
+        progress.info(format!("[clean] nothing to remove under {out_root:?}"));
+        return Ok(0);
+    }
+    for sub in &subtrees {
+        if sub.is_dir() {
+            fs::remove_dir_all(sub)?;
+        } else {
+            fs::remove_file(sub)?;
+        }
+        progress.info(format!("[clean] removed {sub:?}"));
+    }
+    Ok(subtrees.len())
+}
+
+/// Suffixes of the one-time backups phases leave next to files they overwrite
+pub const BACKUP_SUFFIXES: [&str; 6] = [
+    ".pre_patch.bak",
+    ".pre_param_patch.bak",
+    ".pre_personal_patch.bak",
+    ".pre_za_base.bak",
+    ".pre_nohead.bak",
+    ".sv.bak",
+];
+
+/// Backup files under the tool-owned subtrees of `out_root`, sorted
+pub fn find_backups(out_root: &Path, game_root: &Path) -> Vec<PathBuf> {
+    let mut out = Vec::new();
+    for sub in existing_subtrees(out_root, game_root) {
+        for e in walkdir::WalkDir::new(&sub)
+            .follow_links(false)
+            .into_iter()
+            .flatten()
+        {
+            if !e.file_type().is_file() {
+                continue;
+            }
+            let name = e.file_name().to_string_lossy();
+            if BACKUP_SUFFIXES.iter().any(|s| name.ends_with(s)) {
+                out.push(e.path().to_path_buf());
+            }
+        }
+    }
+    out.sort();
+    out
+}
+
+/// Lists every backup under the output root, then deletes them unless `dry_run`
+/// Returns the backups found (removed ones when not a dry run)
+pub fn prune_backups(
+    cfg: &AppConfig,
+    dry_run: bool,
+    progress: &ProgressSink,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let out_root = resolve_out_root(
+        cfg.out_root
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Output root not set"))?,
+    )?;
+    let game_root = cfg.game_root(&out_root)?;
+    let backups = find_backups(&out_root, &game_root);
+    let mut bytes = 0u64;
+    for p in &backups {
+        bytes += fs::metadata(p).map(|m| m.len()).unwrap_or(0);
+        progress.info(format!("[prune] {}", p.display()));
+    }
+    let mb = bytes as f64 / (1024.0 * 1024.0);
+    if dry_run || backups.is_empty() {
+        progress.info(format!(
+            "[prune] {} backup(s), {mb:.1} MiB under {out_root:?}{}",
+            backups.len(),
+            if dry_run { " (dry run)" } else { "" }
+        ));
+        return Ok(backups);
+    }
+    for p in &backups {
+        fs::remove_file(p)?;
+    }
+    progress.info(format!(
+        "[prune] removed {} backup(s), {mb:.1} MiB",
+        backups.len()
+    ));
+    Ok(backups)
+}
@@ -1,14 +1,18 @@
 use crate::{
-    backend::{anim_sync, ensure, lookat, za_base},
+    backend::{anim_ids, anim_sync, ensure, lookat, patch_catalog, za_base},
     config::AppConfig,
-    progress::ProgressSink,
+    paths::walk_dir,
+    progress::{ProgressSink, WarningKind},
 };
+use serde::{Deserialize, Serialize};
 use std::{
+    collections::{BTreeMap, HashSet},
     fs,
     path::{Path, PathBuf},
+    time::UNIX_EPOCH,
 };
-use walkdir::WalkDir;
 
+#[allow(clippy::too_many_arguments)]
 pub fn copy_pm_variants(
     poke_root: &Path,
     za_dump: &Path,
@@ -16,31 +20,78 @@ pub fn copy_pm_variants(
     cfg: &AppConfig,
     pm_variants: &[(String, String)],
     donor_by_target_pm_variant: Option<&std::collections::HashMap<String, String>>,
+    catalog_mons: &[patch_catalog::PatchMon],
     progress: &ProgressSink,
 ) -> anyhow::Result<Vec<anim_sync::AnimSyncStats>> {
     progress.phase_start("Copy pm packages");
+    let data_out = cfg.out_path(out_root, "ik_pokemon/data")?;
 
     let mut stats = Vec::new();
+    let mut missing_assets = BTreeMap::<String, Vec<String>>::new();
+    let mut copy_counts = BTreeMap::<String, CopyCounts>::new();
+    let state_path = copy_state_path(out_root);
+    let mut state = CopyState::load(&state_path);
     let total = pm_variants.len().max(1) as u64;
     let mut done = 0u64;
     for (pm, pm_variant) in pm_variants {
         done += 1;
         progress.progress(done, total);
 
-        let src = poke_root.join("data").join(pm).join(pm_variant);
-        let dst = out_root
-            .join("ik_pokemon")
-            .join("data")
-            .join(pm)
-            .join(pm_variant);
+        let base_src = poke_root.join("data").join(pm).join(pm_variant);
+        let dst = data_out.join(pm).join(pm_variant);
 
-        if !src.is_dir() {
-            progress.warn(format!("missing src pm dir: {:?}", src));
+        // overlays first so their files win, then the dump
+        let srcs = cfg
+            .overlay_data_roots
+            .iter()
+            .map(|r| r.join(pm).join(pm_variant))
+            .chain(std::iter::once(base_src.clone()))
+            .filter(|p| p.is_dir())
+            .collect::<Vec<_>>();
+        let Some(src) = srcs
+            .iter()
+            .find(|p| **p == base_src)
+            .or(srcs.first())
+            .cloned()
+        else {
+            progress.warn_kind(
+                WarningKind::MissingAsset,
+                format!("missing src pm dir: {:?}", base_src),
+            );
             continue;
-        }
+        };
 
         ensure_dir(&dst)?;
-        copy_tree_missing_only(&src, &dst)?;
+        let (copied, skipped) = copy_tree_missing_only(
+            &srcs,
+            &dst,
+            cfg.refresh_changed,
+            cfg.follow_symlinks,
+            state.files.entry(pm_variant.clone()).or_default(),
+        )?;
+        copy_counts.insert(
+            pm_variant.clone(),
+            CopyCounts {
+                copied: copied.iter().sum(),
+                skipped,
+            },
+        );
+        let from_roots = srcs
+            .iter()
+            .zip(copied)
+            .filter(|(_, n)| *n > 0)
+            .map(|(s, n)| format!("{s:?} ({n} files)"))
+            .collect::<Vec<_>>();
+        if !cfg.overlay_data_roots.is_empty() {
+            progress.info(format!(
+                "[copy] {pm_variant} from: {}",
+                if from_roots.is_empty() {
+                    "nothing new".to_string()
+                } else {
+                    from_roots.join(", ")
+                }
+            ));
+        }
 
         if let Some(map) = donor_by_target_pm_variant {
             if let Some(donor_variant) = map.get(pm_variant) {
@@ -54,7 +105,7 @@ pub fn copy_pm_variants(
                 )?;
             }
             if cfg.no_head_look_at {
-                lookat::za_patch_no_head_lookat(&dst, progress)?;
+                lookat::za_patch_no_head_lookat(&dst, &cfg.look_at_groups(), progress)?;
             }
         } else {
             if cfg.use_za_base_config {
@@ -65,49 +116,246 @@ pub fn copy_pm_variants(
                     progress,
                 )?;
                 if cfg.no_head_look_at {
-                    lookat::za_patch_no_head_lookat(&dst, progress)?;
+                    lookat::za_patch_no_head_lookat(&dst, &cfg.look_at_groups(), progress)?;
                 }
             } else {
-                lookat::sv_style_disable_tralk(&dst, progress)?;
+                lookat::sv_style_disable_tralk(&dst, cfg.keep_tralk, progress)?;
             }
         }
 
         let anim = anim_sync::sync_tracr_resources_from_sv(&dst, &src, progress)?;
         stats.push(anim);
+        progress.metric("copy.pm_variants", stats.len() as i64);
+        progress.metric(
+            "anim.filled",
+            stats.iter().map(|s| s.filled as i64).sum::<i64>(),
+        );
+        progress.metric(
+            "anim.missing_after",
+            stats.iter().map(|s| s.missing_after as i64).sum::<i64>(),
+        );
 
         ensure_icons(&dst, pm_variant, progress)?;
         mirror_sv_motion_files_to_za_names(&dst, pm_variant)?;
 
-        ensure::ensure_defence_hkx(za_dump, &cfg.za_base_donor_pm_variant, &dst, progress)?;
+        // per-target donor when the template maps one, same as the za_base overlay above
+        let hkx_donor = donor_by_target_pm_variant
+            .and_then(|m| m.get(pm_variant))
+            .unwrap_or(&cfg.za_base_donor_pm_variant);
+        ensure::ensure_defence_hkx(za_dump, hkx_donor, &dst, cfg.follow_symlinks, progress)?;
+
+        let missing =
+            patch_catalog::missing_variant_assets(&data_out, pm, pm_variant, catalog_mons);
+        if !missing.is_empty() {
+            missing_assets.insert(pm_variant.clone(), missing);
+        }
+    }
+
+    if let Err(e) = state.save(&state_path) {
+        progress.warn(format!("[copy] failed to write {:?}: {e}", state_path));
     }
+    report_missing_assets(
+        &cfg.report_dir(out_root),
+        cfg.generate_reports,
+        &missing_assets,
+        progress,
+    );
+    report_copy_counts(
+        &cfg.report_dir(out_root),
+        cfg.generate_reports,
+        &copy_counts,
+        progress,
+    );
 
+    let (tracks, refs, filled, missing_src, missing_after) =
+        stats.iter().fold((0, 0, 0, 0, 0), |acc, s| {
+            (
+                acc.0 + s.tracks,
+                acc.1 + s.refs,
+                acc.2 + s.filled,
+                acc.3 + s.missing_src,
+                acc.4 + s.missing_after,
+            )
+        });
+    let incomplete = stats.iter().filter(|s| s.missing_after > 0).count();
+    progress.info(format!(
+        "[anim] total: variants={} tracks={tracks} refs={refs} filled={filled} missing_src={missing_src} missing_after={missing_after} (variants with missing_after: {incomplete})",
+        stats.len()
+    ));
     progress.phase_end("Copy pm packages");
     Ok(stats)
 }
 
+/// Warns about variants whose output lacks files the patched catalog will reference
+/// and writes them to `_report/missing_assets.json`
+fn report_missing_assets(
+    report_dir: &Path,
+    generate_reports: bool,
+    missing_assets: &BTreeMap<String, Vec<String>>,
+    progress: &ProgressSink,
+) {
+    if missing_assets.is_empty() {
+        progress.info("[check] all copied variants have their catalog-referenced files");
+    } else {
+        let files = missing_assets.values().map(Vec::len).sum::<usize>();
+        let show = missing_assets.iter().take(10).collect::<Vec<_>>();
+        progress.warn_kind(
+            WarningKind::MissingAsset,
+            format!(
+                "[check] {} variant(s) missing {files} catalog-referenced file(s) (first 10): {:?}",
+                missing_assets.len(),
+                show
+            ),
+        );
+    }
+    if !generate_reports {
+        return;
+    }
+    let _ = fs::create_dir_all(report_dir);
+    let path = report_dir.join("missing_assets.json");
+    if let Ok(text) = serde_json::to_string_pretty(missing_assets) {
+        let _ = fs::write(&path, text + "\n");
+        progress.info(format!("[report] wrote {:?}", path));
+    }
+}
+
+/// Files a variant got from its source roots vs ones already present in the output
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+struct CopyCounts {
+    copied: usize,
+    skipped: usize,
+}
+
+/// Logs the copy totals and writes the per-variant counts to `_report/copy.json`
+fn report_copy_counts(
+    report_dir: &Path,
+    generate_reports: bool,
+    counts: &BTreeMap<String, CopyCounts>,
+    progress: &ProgressSink,
+) {
+    let copied = counts.values().map(|c| c.copied).sum::<usize>();
+    let skipped = counts.values().map(|c| c.skipped).sum::<usize>();
+    let untouched = counts.values().filter(|c| c.copied == 0).count();
+    progress.metric("copy.files_copied", copied as i64);
+    progress.metric("copy.files_skipped", skipped as i64);
+    progress.info(format!(
+        "[copy] total: variants={} files copied={copied} skipped={skipped} (variants with nothing new: {untouched})",
+        counts.len()
+    ));
+    if !generate_reports {
+        return;
+    }
+    let _ = fs::create_dir_all(report_dir);
+    let path = report_dir.join("copy.json");
+    if let Ok(text) = serde_json::to_string_pretty(counts) {
+        let _ = fs::write(&path, text + "\n");
+        progress.info(format!("[report] wrote {:?}", path));
+    }
+}
+
 fn ensure_dir(path: &Path) -> anyhow::Result<()> {
     fs::create_dir_all(path)?;
     Ok(())
 }
 
-fn copy_tree_missing_only(src: &Path, dst: &Path) -> anyhow::Result<()> {
-    for entry in WalkDir::new(src).follow_links(false) {
-        let entry = entry?;
-        let rel = entry.path().strip_prefix(src)?;
-        let out = dst.join(rel);
-        if entry.file_type().is_dir() {
-            fs::create_dir_all(&out)?;
-            continue;
-        }
-        if out.exists() {
-            continue;
-        }
-        if let Some(parent) = out.parent() {
+/// Source each output file was copied from, as it was at copy time, per pm_variant and path
+/// relative to it. The output itself can't be compared: textures, tracn and tralk are patched in
+/// place after the copy and would always look changed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct CopyState {
+    files: BTreeMap<String, BTreeMap<String, SourceStamp>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct SourceStamp {
+    src: PathBuf,
+    len: u64,
+    mtime: u64,
+}
+
+impl SourceStamp {
+    fn of(src: &Path) -> Option<Self> {
+        let m = fs::metadata(src).ok()?;
+        let mtime = m
+            .modified()
+            .ok()?
+            .duration_since(UNIX_EPOCH)
+            .ok()?
+            .as_nanos();
+        Some(Self {
+            src: src.to_path_buf(),
+            len: m.len(),
+            mtime: u64::try_from(mtime).ok()?,
+        })
+    }
+}
+
+impl CopyState {
+    /// Missing or unreadable state just means `--refresh-changed` recopies everything once
+    fn load(path: &Path) -> Self {
+        fs::read(path)
+            .ok()
+            .and_then(|b| serde_json::from_slice(&b).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
             fs::create_dir_all(parent)?;
         }
-        fs::copy(entry.path(), &out)?;
+        fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
     }
-    Ok(())
+}
+
+fn copy_state_path(out_root: &Path) -> PathBuf {
+    out_root.join("_cache").join("copy_state.json")
+}
+
+/// Copies files from `srcs` (earlier roots win per relative path) that don't exist yet under `dst`
+/// With `refresh_changed`, existing files are recopied when the winning source is not the one
+/// `recorded` for them (other root, size or mtime), including files copied before it was kept.
+/// Returns how many files were copied from each root, and how many were left as already present.
+fn copy_tree_missing_only(
+    srcs: &[PathBuf],
+    dst: &Path,
+    refresh_changed: bool,
+    follow_links: bool,
+    recorded: &mut BTreeMap<String, SourceStamp>,
+) -> anyhow::Result<(Vec<usize>, usize)> {
+    let mut claimed = HashSet::<PathBuf>::new();
+    let mut copied = vec![0usize; srcs.len()];
+    let mut skipped = 0usize;
+    for (i, src) in srcs.iter().enumerate() {
+        for entry in walk_dir(src, follow_links) {
+            let entry = entry?;
+            let rel = entry.path().strip_prefix(src)?;
+            let out = dst.join(rel);
+            if entry.file_type().is_dir() {
+                fs::create_dir_all(&out)?;
+                continue;
+            }
+            if !claimed.insert(rel.to_path_buf()) {
+                continue;
+            }
+            let key = rel.to_string_lossy().replace('\\', "/");
+            let stamp = SourceStamp::of(entry.path());
+            if out.exists() && !(refresh_changed && recorded.get(&key) != stamp.as_ref()) {
+                skipped += 1;
+                continue;
+            }
+            if let Some(parent) = out.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(entry.path(), &out)?;
+            copied[i] += 1;
+            match stamp {
+                Some(stamp) => recorded.insert(key, stamp),
+                None => recorded.remove(&key),
+            };
+        }
+    }
+    Ok((copied, skipped))
 }
 
 fn ensure_icons(
@@ -131,10 +379,13 @@ fn ensure_icons(
 
     let donor = pick_icon_donor(dst_pm_variant_dir, pm_variant)?;
     let Some(donor) = donor else {
-        progress.warn(format!(
-            "no icon donor found for {pm_variant} under {:?}",
-            dst_pm_variant_dir
-        ));
+        progress.warn_kind(
+            WarningKind::MissingDonor,
+            format!(
+                "no icon donor found for {pm_variant} under {:?}",
+                dst_pm_variant_dir
+            ),
+        );
         return Ok(());
     };
 
@@ -200,11 +451,9 @@ fn mirror_sv_motion_files_to_za_names(
             let Ok(motion_id) = parts[3].parse::<i32>() else {
                 continue;
             };
-            if !(20000..=29999).contains(&motion_id) {
+            let Some((za0, za1)) = anim_ids::za_ids_from_sv_motion_id(motion_id) else {
                 continue;
-            }
-            let za0 = motion_id - 20000;
-            let za1 = 10000 + za0;
+            };
             let prefix = format!("{}_{}_{}", parts[0], parts[1], parts[2]);
             let suffix = parts[4];
 
@@ -220,3 +469,40 @@ fn mirror_sv_motion_files_to_za_names(
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refresh_changed_follows_the_source_not_the_patched_output() {
+        let td = tempfile::tempdir().unwrap();
+        let (src, dst) = (td.path().join("src"), td.path().join("dst"));
+        fs::create_dir_all(&src).unwrap();
+        fs::write(src.join("a.bntx"), b"source").unwrap();
+        let srcs = [src.clone()];
+        let mut recorded = BTreeMap::new();
+
+        let (copied, skipped) =
+            copy_tree_missing_only(&srcs, &dst, true, false, &mut recorded).unwrap();
+        assert_eq!((copied, skipped), (vec![1], 0));
+
+        // patched in place by a later phase: a different size than the source
+        fs::write(dst.join("a.bntx"), b"converted texture").unwrap();
+        let (copied, skipped) =
+            copy_tree_missing_only(&srcs, &dst, true, false, &mut recorded).unwrap();
+        assert_eq!((copied, skipped), (vec![0], 1));
+
+        fs::write(src.join("a.bntx"), b"edited source").unwrap();
+        let (copied, _) = copy_tree_missing_only(&srcs, &dst, false, false, &mut recorded).unwrap();
+        assert_eq!(copied, [0]);
+        let (copied, _) = copy_tree_missing_only(&srcs, &dst, true, false, &mut recorded).unwrap();
+        assert_eq!(copied, [1]);
+        assert_eq!(fs::read(dst.join("a.bntx")).unwrap(), b"edited source");
+
+        // nothing recorded (copied before the state existed): copied again once
+        recorded.clear();
+        let (copied, _) = copy_tree_missing_only(&srcs, &dst, true, false, &mut recorded).unwrap();
+        assert_eq!(copied, [1]);
+    }
+}
@@ -0,0 +1,130 @@
+use crate::{
+    backend::{
+        anim_sync::AnimSyncStats,
+        catalog::{CatalogSelection, SkippedEntry},
+        names::ConvertedMon,
+    },
+    fb::trpmcatalog::{self, CatalogEntryFull},
+    progress::ProgressSink,
+};
+use serde::Serialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use walkdir::WalkDir;
+
+#[derive(Debug, Serialize)]
+struct DiagBundle<'a> {
+    species: u16,
+    /// Selected entries (one per form/gender) with everything the run knows about them
+    entries: Vec<DiagEntry<'a>>,
+    /// Entries of this species the selection dropped, with the reason
+    skipped: Vec<&'a SkippedEntry>,
+}
+
+#[derive(Debug, Serialize)]
+struct DiagEntry<'a> {
+    mon: &'a ConvertedMon,
+    model_path: &'a str,
+    anim: Option<&'a AnimSyncStats>,
+    files: Vec<DiagFile>,
+    /// ZA dump catalog entry for the key (None when the key is new to ZA)
+    catalog_before: Option<CatalogEntryFull>,
+    /// Output catalog entry for the key after patching
+    catalog_after: Option<CatalogEntryFull>,
+}
+
+#[derive(Debug, Serialize)]
+struct DiagFile {
+    path: String,
+    size: u64,
+}
+
+/// Writes `_report/diag_<species>.json`: selection, donor, copied files, anim sync stats and
+/// catalog entries before/after for every selected form/gender of `species`
+pub fn write_diagnostic_bundle(
+    out_root: &Path,
+    report_dir: &Path,
+    species: u16,
+    selection: &CatalogSelection,
+    converted: &[ConvertedMon],
+    anim_stats: &[AnimSyncStats],
+    progress: &ProgressSink,
+) -> anyhow::Result<PathBuf> {
+    let before = read_catalog_entries(&selection.za_catalog);
+    let after = read_catalog_entries(
+        &out_root
+            .join("ik_pokemon")
+            .join("catalog")
+            .join("catalog")
+            .join("poke_resource_table.trpmcatalog"),
+    );
+    let data_root = out_root.join("ik_pokemon").join("data");
+
+    let mut entries = Vec::new();
+    for (sel, mon) in selection.entries.iter().zip(converted) {
+        if sel.key.species != species {
+            continue;
+        }
+        let find = |doc: &[CatalogEntryFull]| doc.iter().find(|e| e.key == sel.key).cloned();
+        entries.push(DiagEntry {
+            mon,
+            model_path: &sel.model_path,
+            anim: anim_stats.iter().find(|s| s.pm_variant == sel.pm_variant),
+            files: list_files(&data_root.join(&sel.pm).join(&sel.pm_variant)),
+            catalog_before: find(&before),
+            catalog_after: find(&after),
+        });
+    }
+    let skipped = selection
+        .skipped
+        .iter()
+        .filter(|s| s.species == species)
+        .collect::<Vec<_>>();
+    if entries.is_empty() {
+        progress.warn(format!(
+            "[diag] species {species} is not in the selection (skipped entries: {})",
+            skipped.len()
+        ));
+    }
+
+    let bundle = DiagBundle {
+        species,
+        entries,
+        skipped,
+    };
+    fs::create_dir_all(report_dir)?;
+    let path = report_dir.join(format!("diag_{species}.json"));
+    fs::write(&path, serde_json::to_vec_pretty(&bundle)?)?;
+    progress.info(format!("[diag] wrote {:?}", path));
+    Ok(path)
+}
+
+fn read_catalog_entries(path: &Path) -> Vec<CatalogEntryFull> {
+    fs::read(path)
+        .ok()
+        .and_then(|b| trpmcatalog::read_doc(b).ok())
+        .map(|d| d.entries)
+        .unwrap_or_default()
+}
+
+fn list_files(dir: &Path) -> Vec<DiagFile> {
+    let mut out = WalkDir::new(dir)
+        .follow_links(false)
+        .into_iter()
+        .flatten()
+        .filter(|e| e.file_type().is_file())
+        .map(|e| DiagFile {
+            path: e
+                .path()
+                .strip_prefix(dir)
+                .unwrap_or(e.path())
+                .to_string_lossy()
+                .replace('\\', "/"),
+            size: e.metadata().map(|m| m.len()).unwrap_or(0),
+        })
+        .collect::<Vec<_>>();
+    out.sort_by(|a, b| a.path.cmp(&b.path));
+    out
+}
@@ -1,32 +1,30 @@
-use crate::progress::ProgressSink;
+use crate::paths::{parse_pm_variant_name, pm_variant_dir_name, walk_dir};
+use crate::progress::{ProgressSink, WarningKind};
 use std::{fs, path::Path};
-use walkdir::WalkDir;
 
 pub fn ensure_defence_hkx(
     za_dump: &Path,
     donor_pm_variant: &str,
     target_pm_dir: &Path,
+    follow_links: bool,
     progress: &ProgressSink,
 ) -> anyhow::Result<()> {
-    let pm_variant = target_pm_dir
-        .file_name()
-        .ok_or_else(|| anyhow::anyhow!("unexpected pm dir: {target_pm_dir:?}"))?
-        .to_string_lossy()
-        .to_string();
+    let pm_variant = pm_variant_dir_name(target_pm_dir)?;
     let dst = target_pm_dir.join(format!("{pm_variant}_defence.hkx"));
     if dst.is_file() {
         return Ok(());
     }
 
-    let donor_pm = donor_pm_variant
-        .split_once('_')
-        .map(|(a, _)| a)
-        .unwrap_or(donor_pm_variant);
+    let donor_pm = parse_pm_variant_name(donor_pm_variant)
+        .map(|p| p.pm)
+        .ok_or_else(|| {
+            anyhow::anyhow!("expected a pmXXXX_YY_ZZ donor, got {donor_pm_variant:?}")
+        })?;
 
     let direct = za_dump
         .join("ik_pokemon")
         .join("data")
-        .join(donor_pm)
+        .join(&donor_pm)
         .join(donor_pm_variant)
         .join(format!("{donor_pm_variant}_defence.hkx"));
 
@@ -36,7 +34,7 @@ pub fn ensure_defence_hkx(
         let root = za_dump.join("ik_pokemon").join("data");
         let want = format!("{donor_pm_variant}_defence.hkx");
         let mut found = None;
-        for e in WalkDir::new(&root).follow_links(false) {
+        for e in walk_dir(&root, follow_links) {
             let e = e?;
             if !e.file_type().is_file() {
                 continue;
@@ -49,14 +47,15 @@ pub fn ensure_defence_hkx(
         if let Some(p) = found {
             p
         } else {
-            progress.warn(format!(
-                "[hkx] donor defence hkx not found for {donor_pm_variant}"
-            ));
+            progress.warn_kind(
+                WarningKind::MissingDonor,
+                format!("[hkx] donor defence hkx not found for {donor_pm_variant}"),
+            );
             return Ok(());
         }
     };
 
     fs::copy(src, &dst)?;
-    progress.info(format!("[hkx] copied defence hkx: {pm_variant}"));
+    progress.detail(1, format!("[hkx] copied defence hkx: {pm_variant}"));
     Ok(())
 }
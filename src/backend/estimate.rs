@@ -0,0 +1,75 @@
+use crate::progress::ProgressSink;
+use std::path::Path;
+use walkdir::WalkDir;
+
+// catalog + param/personal arrays + reports; generous, these are all small
+const FIXED_OVERHEAD_BYTES: u64 = 16 * 1024 * 1024;
+
+pub fn report_output_size_estimate(
+    poke_root: &Path,
+    out_root: &Path,
+    pm_variants: &[(String, String)],
+    texture_convert: bool,
+    progress: &ProgressSink,
+) {
+    let mut bytes = 0u64;
+    for (pm, pm_variant) in pm_variants {
+        let src = poke_root.join("data").join(pm).join(pm_variant);
+        bytes += tree_size(&src);
+    }
+    if texture_convert {
+        // bntx index cache and per-file temp dirs while converting
+        bytes += bytes / 20;
+    }
+    bytes += FIXED_OVERHEAD_BYTES;
+
+    progress.info(format!("estimated output size: ~{}", format_bytes(bytes)));
+
+    if let Some(free) = available_space(out_root) {
+        if free < bytes {
+            progress.warn(format!(
+                "output drive may be too small: ~{} free, ~{} needed",
+                format_bytes(free),
+                format_bytes(bytes)
+            ));
+        }
+    }
+}
+
+fn tree_size(root: &Path) -> u64 {
+    WalkDir::new(root)
+        .follow_links(false)
+        .into_iter()
+        .flatten()
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| e.metadata().ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+#[cfg(feature = "free-space")]
+fn available_space(out_root: &Path) -> Option<u64> {
+    // the output folder may not exist yet; query the closest existing ancestor
+    let existing = out_root.ancestors().find(|p| p.exists())?;
+    fs2::available_space(existing).ok()
+}
+
+#[cfg(not(feature = "free-space"))]
+fn available_space(_out_root: &Path) -> Option<u64> {
+    None
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut v = bytes as f64;
+    let mut unit = 0usize;
+    while v >= 1024.0 && unit + 1 < UNITS.len() {
+        v /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{v:.1} {}", UNITS[unit])
+    }
+}
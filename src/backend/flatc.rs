@@ -1,3 +1,9 @@
+use crate::{
+    backend::profile::{run_tool, ToolProfile},
+    error::SvzaError,
+};
+use anyhow::Context as _;
+use serde_json::Value;
 use std::{
     fs,
     path::{Path, PathBuf},
@@ -10,6 +16,7 @@ pub fn flatc_dump_json(
     includes: &[PathBuf],
     src_bin: &Path,
     out_dir: &Path,
+    profile: Option<&ToolProfile>,
 ) -> anyhow::Result<PathBuf> {
     fs::create_dir_all(out_dir)?;
     let mut cmd = Command::new(flatc);
@@ -24,21 +31,18 @@ pub fn flatc_dump_json(
         .arg(schema)
         .arg("--")
         .arg(src_bin);
-    let out = cmd.output()?;
+    let out = run_tool(&mut cmd, "flatc", profile)?;
     if !out.status.success() {
-        anyhow::bail!(
-            "flatc dump failed: {}\n{}",
-            out.status,
-            String::from_utf8_lossy(&out.stdout)
-        );
+        return Err(SvzaError::FlatcFailed {
+            step: "dump",
+            status: out.status.to_string(),
+            output: String::from_utf8_lossy(&out.stdout).to_string(),
+        }
+        .into());
     }
     let expected = out_dir.join(format!(
         "{}.json",
-        src_bin
-            .file_stem()
-            .unwrap_or_default()
-            .to_string_lossy()
-            .to_string()
+        src_bin.file_stem().unwrap_or_default().to_string_lossy()
     ));
     if expected.is_file() {
         return Ok(expected);
@@ -64,6 +68,7 @@ pub fn flatc_build_bin(
     includes: &[PathBuf],
     src_json: &Path,
     out_bin: &Path,
+    profile: Option<&ToolProfile>,
 ) -> anyhow::Result<()> {
     if let Some(parent) = out_bin.parent() {
         fs::create_dir_all(parent)?;
@@ -80,13 +85,14 @@ pub fn flatc_build_bin(
         .arg(tmp.path())
         .arg(schema)
         .arg(src_json);
-    let out = cmd.output()?;
+    let out = run_tool(&mut cmd, "flatc", profile)?;
     if !out.status.success() {
-        anyhow::bail!(
-            "flatc build failed: {}\n{}",
-            out.status,
-            String::from_utf8_lossy(&out.stdout)
-        );
+        return Err(SvzaError::FlatcFailed {
+            step: "build",
+            status: out.status.to_string(),
+            output: String::from_utf8_lossy(&out.stdout).to_string(),
+        }
+        .into());
     }
     let mut outs = Vec::new();
     for e in fs::read_dir(tmp.path())? {
@@ -102,3 +108,73 @@ pub fn flatc_build_bin(
     fs::copy(&outs[0], out_bin)?;
     Ok(())
 }
+
+/// Per-run knobs shared by the flatc-backed patchers
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FlatcOptions<'a> {
+    /// Where [`keep_intermediates`] copies the JSON, when set
+    pub keep_json_dir: Option<&'a Path>,
+    /// Dump each built bin back to JSON and check it before it replaces the output
+    pub verify: bool,
+    /// Collects flatc call timings when `--profile` is on
+    pub profile: Option<&'a ToolProfile>,
+}
+
+/// [`flatc_build_bin`], plus with `opts.verify` a round trip: the bin is built into a tempdir, dumped
+/// back and handed to `check`, and only copied to `out_bin` once the check passes
+pub fn flatc_build_bin_checked(
+    flatc: &Path,
+    schema: &Path,
+    includes: &[PathBuf],
+    src_json: &Path,
+    out_bin: &Path,
+    opts: FlatcOptions,
+    check: impl FnOnce(&Value) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    if !opts.verify {
+        return flatc_build_bin(flatc, schema, includes, src_json, out_bin, opts.profile);
+    }
+    let td = tempfile::tempdir()?;
+    let built = td
+        .path()
+        .join(out_bin.file_name().unwrap_or("out.bin".as_ref()));
+    flatc_build_bin(flatc, schema, includes, src_json, &built, opts.profile)?;
+    let json = flatc_dump_json(
+        flatc,
+        schema,
+        includes,
+        &built,
+        &td.path().join("dump"),
+        opts.profile,
+    )?;
+    let doc: Value = serde_json::from_slice(&fs::read(&json)?)?;
+    check(&doc)
+        .with_context(|| format!("verify-flatc: {out_bin:?} does not read back as written"))?;
+    if let Some(parent) = out_bin.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::copy(&built, out_bin)?;
+    Ok(())
+}
+
+/// Copies the JSON files flatc read/wrote in `work_dir` into `keep_dir/<name>/` (when set)
+/// so a bad insert can be inspected after the tempdir is gone
+pub fn keep_intermediates(
+    work_dir: &Path,
+    keep_dir: Option<&Path>,
+    name: &str,
+) -> anyhow::Result<()> {
+    let Some(keep_dir) = keep_dir else {
+        return Ok(());
+    };
+    let dst = keep_dir.join(name);
+    fs::create_dir_all(&dst)?;
+    for e in fs::read_dir(work_dir)? {
+        let e = e?;
+        if e.file_type()?.is_file() && e.path().extension().and_then(|x| x.to_str()) == Some("json")
+        {
+            fs::copy(e.path(), dst.join(e.file_name()))?;
+        }
+    }
+    Ok(())
+}
@@ -1,18 +1,19 @@
 use crate::{
-    fb::{tracn, tralk},
+    fb::{
+        tracn,
+        tralk::{self, NoLookAtParams},
+    },
+    paths::pm_variant_dir_name,
     progress::ProgressSink,
 };
-use std::{fs, path::Path};
+use std::{collections::BTreeMap, fs, path::Path};
 
 pub fn sv_style_disable_tralk(
     pm_variant_dir: &Path,
+    keep_tralk: bool,
     progress: &ProgressSink,
 ) -> anyhow::Result<()> {
-    let pm = pm_variant_dir
-        .file_name()
-        .ok_or_else(|| anyhow::anyhow!("unexpected pm dir: {pm_variant_dir:?}"))?
-        .to_string_lossy()
-        .to_string();
+    let pm = pm_variant_dir_name(pm_variant_dir)?;
 
     let tracn = pm_variant_dir.join(format!("{pm}_base.tracn"));
     if tracn.is_file() {
@@ -20,7 +21,10 @@ pub fn sv_style_disable_tralk(
         let changed = tracn::strip_tralk_filenames_in_place(&mut b)?;
         if changed > 0 {
             fs::write(&tracn, b)?;
-            progress.info(format!("[lookat] stripped .tralk refs: {pm} ({changed})"));
+            progress.detail(
+                1,
+                format!("[lookat] stripped .tralk refs: {pm} ({changed})"),
+            );
         }
     }
 
@@ -30,22 +34,24 @@ pub fn sv_style_disable_tralk(
         if !bak.exists() {
             fs::copy(&tralk_path, &bak)?;
         }
-        fs::remove_file(&tralk_path)?;
-        progress.info(format!("[lookat] removed SV tralk: {pm}"));
+        if keep_tralk {
+            progress.detail(1, format!("[lookat] kept SV tralk (unreferenced): {pm}"));
+        } else {
+            fs::remove_file(&tralk_path)?;
+            progress.detail(1, format!("[lookat] removed SV tralk: {pm}"));
+        }
     }
 
     Ok(())
 }
 
+/// Patches every configured look-at group in the ZA `base.tralk`, each with its own params
 pub fn za_patch_no_head_lookat(
     pm_variant_dir: &Path,
+    groups: &BTreeMap<String, NoLookAtParams>,
     progress: &ProgressSink,
 ) -> anyhow::Result<()> {
-    let pm = pm_variant_dir
-        .file_name()
-        .ok_or_else(|| anyhow::anyhow!("unexpected pm dir: {pm_variant_dir:?}"))?
-        .to_string_lossy()
-        .to_string();
+    let pm = pm_variant_dir_name(pm_variant_dir)?;
     let tralk_path = pm_variant_dir.join(format!("{pm}_base.tralk"));
     if !tralk_path.is_file() {
         progress.warn(format!("[lookat] missing ZA base.tralk to patch: {pm}"));
@@ -58,14 +64,26 @@ pub fn za_patch_no_head_lookat(
     }
 
     let mut b = fs::read(&tralk_path)?;
-    let changed = tralk::patch_no_head_joint_rotation_in_place(&mut b)?;
-    fs::write(&tralk_path, b)?;
-    if changed == 0 {
+    let res = tralk::patch_look_at_groups_in_place(&mut b, groups)?;
+    if res.values().any(|r| r.changed > 0) {
+        fs::write(&tralk_path, b)?;
+    }
+    for (name, r) in &res {
+        if r.changed > 0 {
+            progress.detail(1, format!("[lookat] patched look-at group {name:?}: {pm}"));
+        } else if r.already > 0 {
+            progress.detail(
+                1,
+                format!("[lookat] look-at group {name:?} already patched: {pm}"),
+            );
+        } else {
+            progress.detail(1, format!("[lookat] no look-at group {name:?}: {pm}"));
+        }
+    }
+    if res.values().all(|r| r.changed == 0 && r.already == 0) {
         progress.warn(format!(
-            "[lookat] did not find head JointRotation group: {pm}"
+            "[lookat] did not find any configured JointRotation group: {pm}"
         ));
-    } else {
-        progress.info(format!("[lookat] patched no-head-look-at: {pm}"));
     }
     Ok(())
 }
@@ -2,26 +2,83 @@ use crate::template::{preferred_template_dirs, DonorTemplate, Key, TemplateStore
 use crate::{
     cancel::CancelToken,
     config::AppConfig,
-    paths::{canonicalish, detect_sv_layout, find_under},
-    progress::ProgressSink,
+    error::SvzaError,
+    paths::{
+        canonicalish, detect_sv_layout, looks_like_output_root, parse_model_path,
+        parse_pm_variant_name, resolve_out_root, write_output_marker,
+    },
+    pool::TexLimits,
+    progress::{ProgressSink, RunSummary, WarningKind},
+};
+use plan::{skip_reason, Phase};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet as StdHashSet},
+    path::Path,
 };
-use serde_json;
-use std::collections::{BTreeMap, HashMap, HashSet as StdHashSet};
 
+mod anim_ids;
 mod anim_sync;
-mod catalog;
+pub mod catalog;
+pub mod clean;
 mod copy_pm;
+mod diag;
 mod ensure;
+mod estimate;
 mod flatc;
 mod lookat;
 pub mod names;
 mod param_arrays;
 mod patch_catalog;
 mod personal;
-mod textures;
+pub mod plan;
+mod preflight;
+pub mod profile;
+mod selection_report;
+pub mod textures;
 mod za_base;
 
-pub fn run(cfg: &AppConfig, progress: ProgressSink, cancel: CancelToken) -> anyhow::Result<()> {
+/// ZA-only markers: the ik_pokemon catalog next to the avalon/ and param_chr/ trees
+/// SV dumps can carry ik_pokemon/ too, so the catalog alone doesn't tell them apart.
+fn looks_like_za_dump(root: &Path) -> bool {
+    root.join("ik_pokemon").join("catalog").is_dir()
+        && root.join("avalon").is_dir()
+        && param_arrays::param_inputs(root).model_bfbs.is_file()
+}
+
+type SelectionWithDonors = (
+    catalog::CatalogSelection,
+    Option<HashMap<String, String>>,
+    Option<BTreeMap<u16, u16>>,
+    Option<BTreeMap<Key, Key>>,
+);
+
+pub fn run(
+    cfg: &AppConfig,
+    progress: ProgressSink,
+    cancel: CancelToken,
+) -> anyhow::Result<RunSummary> {
+    run_with_limits(cfg, progress, cancel, &TexLimits::for_run(cfg))
+}
+
+/// `run` with texture limits owned by the caller, so concurrent runs can share them
+pub fn run_with_limits(
+    cfg: &AppConfig,
+    progress: ProgressSink,
+    cancel: CancelToken,
+    tex_limits: &TexLimits,
+) -> anyhow::Result<RunSummary> {
+    let collector = progress.clone();
+    run_pipeline(cfg, progress, cancel, tex_limits).map(|s| s.with_warnings(&collector))
+}
+
+fn run_pipeline(
+    cfg: &AppConfig,
+    progress: ProgressSink,
+    cancel: CancelToken,
+    tex_limits: &TexLimits,
+) -> anyhow::Result<RunSummary> {
+    let progress = progress.with_verbosity(cfg.verbosity);
+    let mut summary = RunSummary::default();
     progress.phase_start("Validate paths");
 
     let sv_root = cfg
@@ -42,11 +99,11 @@ pub fn run(cfg: &AppConfig, progress: ProgressSink, cancel: CancelToken) -> anyh
 
     let sv_root = canonicalish(&sv_root);
     let za_dump = canonicalish(&za_dump);
-    let out_root = canonicalish(&out_root);
+    let out_root = resolve_out_root(&out_root)?;
 
     if cancel.is_canceled() {
         progress.warn("canceled");
-        return Ok(());
+        return Ok(summary.canceled());
     }
 
     let mut done = 0u64;
@@ -57,14 +114,22 @@ pub fn run(cfg: &AppConfig, progress: ProgressSink, cancel: CancelToken) -> anyh
     };
 
     if !sv_root.is_dir() {
-        progress.error(format!("SV root is not a directory: {sv_root:?}"));
-        anyhow::bail!("SV root is not a directory: {sv_root:?}");
+        let err = SvzaError::PathNotFound {
+            what: "SV root",
+            path: sv_root.clone(),
+        };
+        progress.error(err.to_string());
+        return Err(err.into());
     }
     bump(&progress);
 
     if !za_dump.is_dir() {
-        progress.error(format!("ZA dump is not a directory: {za_dump:?}"));
-        anyhow::bail!("ZA dump is not a directory: {za_dump:?}");
+        let err = SvzaError::PathNotFound {
+            what: "ZA dump",
+            path: za_dump.clone(),
+        };
+        progress.error(err.to_string());
+        return Err(err.into());
     }
     bump(&progress);
 
@@ -74,18 +139,46 @@ pub fn run(cfg: &AppConfig, progress: ProgressSink, cancel: CancelToken) -> anyh
     bump(&progress);
 
     let Some((layout, poke_root)) = detect_sv_layout(&sv_root) else {
-        progress.error(format!(
-            "SV root must contain either 'pokemon/' or 'ik_pokemon/': {sv_root:?}"
-        ));
-        anyhow::bail!("SV root must contain either 'pokemon/' or 'ik_pokemon/': {sv_root:?}");
+        let msg = if looks_like_output_root(&sv_root) {
+            format!("SV root looks like a converted output folder, not an SV dump: {sv_root:?}")
+        } else {
+            format!(
+                "SV root must contain either 'pokemon/' or 'ik_pokemon/' with catalog/ and data/pmXXXX: {sv_root:?}"
+            )
+        };
+        progress.error(&msg);
+        anyhow::bail!(msg);
     };
 
+    if looks_like_za_dump(&sv_root) && !looks_like_za_dump(&za_dump) {
+        let msg = format!(
+            "the SV root looks like a ZA dump (ik_pokemon/catalog, avalon/ and \
+             poke_model_param_array.bfbs) but the ZA dump does not; are the two paths swapped? \
+             SV root: {sv_root:?}, ZA dump: {za_dump:?}"
+        );
+        if cfg.strict {
+            progress.error(&msg);
+            anyhow::bail!(msg);
+        }
+        progress.warn(format!("{msg} (pass --strict to make this an error)"));
+    }
+
     progress.info(format!("SV layout: {:?} ({:?})", layout, poke_root));
     progress.info(format!("ZA dump: {:?}", za_dump));
     progress.info(format!("Output: {:?}", out_root));
+    let game_root = cfg.game_root(&out_root)?;
+    if game_root != out_root {
+        progress.info(format!("Game files: {:?}", game_root));
+    }
+    let report_dir = cfg.report_dir(&out_root);
+    if cfg.report_dir.is_some() {
+        progress.info(format!("Reports: {:?}", report_dir));
+    }
+    write_output_marker(&out_root)?;
+    let profile = cfg.profile.then(profile::ToolProfile::default);
     bump(&progress);
 
-    if cfg.texture_convert {
+    if cfg.texture_convert && !cfg.catalog_only && !cfg.skip_textures {
         let ultimate = cfg.ultimate_tex_cli.as_ref().ok_or_else(|| {
             anyhow::anyhow!("texture_convert enabled but ultimate_tex_cli not set")
         })?;
@@ -93,57 +186,49 @@ pub fn run(cfg: &AppConfig, progress: ProgressSink, cancel: CancelToken) -> anyh
     }
     bump(&progress);
 
-    let za_catalog_rel = "ik_pokemon/catalog/catalog/poke_resource_table.trpmcatalog";
-    match find_under(&za_dump, za_catalog_rel, "poke_resource_table.trpmcatalog") {
-        Ok(p) => progress.info(format!("ZA catalog: {p:?}")),
-        Err(e) => progress.warn(format!("ZA catalog not found yet ({za_catalog_rel}): {e}")),
-    }
-
-    let sv_catalog_rel = "catalog/catalog/poke_resource_table.trpmcatalog";
-    match find_under(
-        &poke_root,
-        sv_catalog_rel,
-        "poke_resource_table.trpmcatalog",
-    ) {
-        Ok(p) => progress.info(format!("SV catalog: {p:?}")),
-        Err(e) => progress.warn(format!("SV catalog not found yet ({sv_catalog_rel}): {e}")),
-    }
+    preflight::run_preflight(cfg, &poke_root, &za_dump, &out_root, &progress);
     bump(&progress);
 
     progress.phase_end("Validate paths");
 
     if cancel.is_canceled() {
         progress.warn("canceled");
-        return Ok(());
+        return Ok(summary.canceled());
     }
 
-    let (selection, donor_by_target_pm_variant, donor_by_species): (
-        catalog::CatalogSelection,
-        Option<HashMap<String, String>>,
-        Option<BTreeMap<u16, u16>>,
-    ) = if cfg.legacy_mode {
-        let selection = catalog::select_missing_in_za(
+    let key_filter = catalog::KeyFilter::parse(&cfg.only_species, &cfg.exclude_species)?;
+    // Removed keys must not be copied, get param/personal entries or be mirrored either;
+    // otherwise they'd ship as assets with no catalog row
+    let remove_filter = catalog::KeyFilter::excluding(&cfg.remove_species)?;
+    let (mut selection, donor_by_target_pm_variant, donor_by_species, donor_by_target): SelectionWithDonors = if cfg
+        .legacy_mode
+    {
+        let mut selection = catalog::select_missing_in_za(
             &poke_root,
             &za_dump,
             cfg.skip_pokemon_already_in_za,
+            cfg.follow_symlinks,
             &progress,
         )?;
-        (selection, None, None)
+        selection.apply_filter(&key_filter, &progress);
+        selection.apply_filter(&remove_filter, &progress);
+        (selection, None, None, None)
     } else {
-        let tpl = load_autosave_template(cfg).unwrap_or_default();
+        let tpl = load_autosave_template(cfg)?;
 
         let keys: StdHashSet<_> = tpl
             .selected_targets
             .iter()
             .copied()
-            .map(|k| crate::fb::trpmcatalog::SpeciesKey::from(k))
+            .map(crate::fb::trpmcatalog::SpeciesKey::from)
             .collect();
 
-        let selection = if keys.is_empty() {
+        let mut selection = if keys.is_empty() {
             catalog::select_missing_in_za(
                 &poke_root,
                 &za_dump,
                 cfg.skip_pokemon_already_in_za,
+                cfg.follow_symlinks,
                 &progress,
             )?
         } else {
@@ -152,9 +237,12 @@ pub fn run(cfg: &AppConfig, progress: ProgressSink, cancel: CancelToken) -> anyh
                 &za_dump,
                 &keys,
                 tpl.include_targets_already_in_za,
+                cfg.follow_symlinks,
                 &progress,
             )?
         };
+        selection.apply_filter(&key_filter, &progress);
+        selection.apply_filter(&remove_filter, &progress);
 
         let za_model_path_by_key = catalog::read_catalog_map(&selection.za_catalog)?;
         let donor_map = tpl.assignment_map();
@@ -165,6 +253,8 @@ pub fn run(cfg: &AppConfig, progress: ProgressSink, cancel: CancelToken) -> anyh
 
         let mut donor_by_target_pm_variant = HashMap::<String, String>::new();
         let mut donor_by_species = BTreeMap::<u16, u16>::new();
+        let mut donor_by_target = BTreeMap::<Key, Key>::new();
+        let mut gender_mismatches = Vec::new();
 
         for e in &selection.entries {
             let tkey = Key::from(e.key);
@@ -177,23 +267,64 @@ pub fn run(cfg: &AppConfig, progress: ProgressSink, cancel: CancelToken) -> anyh
             };
             let donor_species = donor_key.species;
             donor_by_species.insert(e.key.species, donor_species);
+            donor_by_target.insert(tkey, donor_key);
+            if donor_key.gender != e.key.gender {
+                gender_mismatches.push(format!(
+                    "{}/{}/{} <- {}/{}/{}",
+                    e.key.species,
+                    e.key.form,
+                    e.key.gender,
+                    donor_key.species,
+                    donor_key.form,
+                    donor_key.gender
+                ));
+            }
 
             let dkey = crate::fb::trpmcatalog::SpeciesKey::from(donor_key);
             let Some(model_path) = za_model_path_by_key.get(&dkey) else {
                 continue;
             };
-            let Some((_, donor_pm_variant)) = parse_pm_variant(model_path) else {
+            let Some((_, donor_pm_variant)) = parse_model_path(model_path) else {
                 continue;
             };
             donor_by_target_pm_variant.insert(e.pm_variant.clone(), donor_pm_variant);
         }
 
+        if !gender_mismatches.is_empty() {
+            let show = gender_mismatches.iter().take(20).collect::<Vec<_>>();
+            progress.warn(format!(
+                    "[donor] {} target(s) use a donor of a different gender; param arrays only key on species (first 20, target <- donor as species/form/gender): {:?}",
+                    gender_mismatches.len(),
+                    show
+                ));
+        }
+
         let donor_by_target_pm_variant =
             (!donor_by_target_pm_variant.is_empty()).then_some(donor_by_target_pm_variant);
         let donor_by_species = (!donor_by_species.is_empty()).then_some(donor_by_species);
-
-        (selection, donor_by_target_pm_variant, donor_by_species)
+        let donor_by_target = (!donor_by_target.is_empty()).then_some(donor_by_target);
+
+        (
+            selection,
+            donor_by_target_pm_variant,
+            donor_by_species,
+            donor_by_target,
+        )
     };
+    if cfg.fail_on_missing_assets {
+        let missing = selection.missing_asset_variants(&key_filter);
+        if !missing.is_empty() {
+            let list = missing
+                .iter()
+                .map(|(_, v)| v.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            anyhow::bail!(
+                "--fail-on-missing-assets: {} SV catalog pm_variant(s) have no assets on disk: {list}",
+                missing.len()
+            );
+        }
+    }
     progress.info(format!(
         "catalogs: sv={:?} za={:?}",
         selection.sv_catalog, selection.za_catalog
@@ -203,7 +334,9 @@ pub fn run(cfg: &AppConfig, progress: ProgressSink, cancel: CancelToken) -> anyh
         selection.entries.len(),
         selection.unique_pm_variants.len()
     ));
-    if let Some(e) = selection.entries.get(0) {
+    summary.species_entries = selection.entries.len();
+    summary.pm_variants = selection.unique_pm_variants.len();
+    if let Some(e) = selection.entries.first() {
         progress.info(format!(
             "example: species={} form={} gender={} -> {}/{} ({})",
             e.key.species, e.key.form, e.key.gender, e.pm, e.pm_variant, e.model_path
@@ -211,54 +344,137 @@ pub fn run(cfg: &AppConfig, progress: ProgressSink, cancel: CancelToken) -> anyh
     }
     if cancel.is_canceled() {
         progress.warn("canceled");
-        return Ok(());
+        return Ok(summary.canceled());
     }
 
-    let anim_stats = copy_pm::copy_pm_variants(
-        &poke_root,
-        &za_dump,
-        &out_root,
-        cfg,
-        &selection.unique_pm_variants,
-        donor_by_target_pm_variant.as_ref(),
-        &progress,
-    )?;
+    let sv_entries = patch_catalog::sv_entries_by_key(&selection.sv_catalog).unwrap_or_else(|e| {
+        progress.warn(format!(
+            "[catalog] could not read SV entries ({e}); using the two-locator default and unk_id 0"
+        ));
+        HashMap::new()
+    });
+    let mut mons = selection
+        .entries
+        .iter()
+        .map(|e| {
+            patch_catalog::PatchMon::new(e.key, e.pm.clone(), e.pm_variant.clone(), &sv_entries)
+        })
+        .collect::<Vec<_>>();
 
-    if cancel.is_canceled() {
-        progress.warn("canceled");
-        return Ok(());
-    }
-
-    if cfg.generate_reports {
-        // report
-        {
-            use std::fs;
-            let report_dir = out_root.join("_report");
-            let _ = fs::create_dir_all(&report_dir);
-            let path = report_dir.join("anim_sync.json");
-            if let Ok(text) = serde_json::to_string_pretty(&anim_stats) {
-                let _ = fs::write(&path, text + "\n");
-                progress.info(format!("[report] wrote {:?}", path));
+    let mut anim_stats = Vec::new();
+    if let Some(reason) = skip_reason(cfg, Phase::CopyPm) {
+        progress.phase_skipped(Phase::CopyPm.name(), reason);
+    } else {
+        estimate::report_output_size_estimate(
+            &poke_root,
+            &out_root,
+            &selection.unique_pm_variants,
+            cfg.texture_convert,
+            &progress,
+        );
+
+        anim_stats = copy_pm::copy_pm_variants(
+            &poke_root,
+            &za_dump,
+            &out_root,
+            cfg,
+            &selection.unique_pm_variants,
+            donor_by_target_pm_variant.as_ref(),
+            &mons,
+            &progress,
+        )?;
+        summary.add_anim_stats(&anim_stats);
+
+        if cancel.is_canceled() {
+            progress.warn("canceled");
+            return Ok(summary.canceled());
+        }
+
+        if cfg.generate_reports {
+            // report
+            {
+                use std::fs;
+                let _ = fs::create_dir_all(&report_dir);
+                let path = report_dir.join("anim_sync.json");
+                if let Ok(text) = serde_json::to_string_pretty(&anim_stats) {
+                    let _ = fs::write(&path, text + "\n");
+                    progress.info(format!("[report] wrote {:?}", path));
+                }
             }
+        } else {
+            progress.info("[report] disabled; skipping anim_sync.json");
         }
-    } else {
-        progress.info("[report] disabled; skipping anim_sync.json");
     }
 
-    let mons = selection
-        .entries
-        .iter()
-        .map(|e| patch_catalog::PatchMon {
-            key: e.key,
-            pm: e.pm.clone(),
-            pm_variant: e.pm_variant.clone(),
-        })
-        .collect::<Vec<_>>();
-    let _out_catalog = patch_catalog::patch_za_catalog(&za_dump, &out_root, &mons, &progress)?;
+    if cfg.mirror_genders {
+        let sv_model_paths = catalog::read_catalog_map(&selection.sv_catalog)?;
+        // A key ZA already ships stays ZA's unless the selection opted into replacing those, and
+        // keys the species filters dropped stay dropped
+        let za_keys = &selection.za_keys;
+        let skip_za = !selection.includes_za_keys;
+        let mirrored = patch_catalog::mirror_gender_mons(
+            &mons,
+            &sv_model_paths,
+            &sv_entries,
+            |k| {
+                (skip_za && za_keys.contains(&(k.species, k.form, k.gender)))
+                    || !key_filter.matches(*k)
+                    || !remove_filter.matches(*k)
+            },
+            &progress,
+        );
+        progress.info(format!(
+            "[catalog] mirror genders: added {} entries",
+            mirrored.len()
+        ));
+        // Mirrored keys are converted like selected ones, so reports (converted, coverage) list them
+        let mirrored_keys: StdHashSet<_> = mirrored.iter().map(|m| m.key).collect();
+        selection.skipped.retain(|s| {
+            !mirrored_keys.contains(&crate::fb::trpmcatalog::SpeciesKey {
+                species: s.species,
+                form: s.form,
+                gender: s.gender,
+            })
+        });
+        selection
+            .entries
+            .extend(mirrored.iter().map(|m| catalog::SelectedMon {
+                key: m.key,
+                pm: m.pm.clone(),
+                pm_variant: m.pm_variant.clone(),
+                model_path: sv_model_paths.get(&m.key).cloned().unwrap_or_default(),
+            }));
+        mons.extend(mirrored);
+    }
+    if cfg.catalog_only {
+        if mons.is_empty() {
+            progress.warn("[catalog-only] selection is empty; nothing to patch");
+        }
+        let missing = patch_catalog::missing_output_assets(&game_root, &mons);
+        if !missing.is_empty() {
+            let show = missing.iter().take(20).collect::<Vec<_>>();
+            progress.warn_kind(
+                WarningKind::MissingAsset,
+                format!(
+                    "[catalog-only] catalog references files missing from output: {} (first 20): {:?}",
+                    missing.len(),
+                    show
+                ),
+            );
+        }
+    }
+    if let Some(reason) = skip_reason(cfg, Phase::PatchCatalog) {
+        progress.phase_skipped(Phase::PatchCatalog.name(), reason);
+    } else {
+        patch_catalog::patch_za_catalog(&za_dump, &game_root, &mons, &remove_filter, &progress)?;
+    }
+    if cfg.validate_pokecfg {
+        patch_catalog::validate_pokecfg_refs(&game_root, &mons, &progress);
+    }
 
     if cancel.is_canceled() {
         progress.warn("canceled");
-        return Ok(());
+        return Ok(summary.canceled());
     }
 
     let mut new_species = std::collections::HashSet::<u16>::new();
@@ -272,66 +488,239 @@ pub fn run(cfg: &AppConfig, progress: ProgressSink, cancel: CancelToken) -> anyh
             form: e.key.form,
             gender: e.key.gender,
             name: String::new(),
+            names: BTreeMap::new(),
             pm: e.pm.clone(),
             pm_variant: e.pm_variant.clone(),
+            donor: donor_by_target
+                .as_ref()
+                .and_then(|m| m.get(&Key::from(e.key)).copied()),
+            donor_name: String::new(),
+            donor_pm_variant: donor_by_target_pm_variant
+                .as_ref()
+                .and_then(|m| m.get(&e.pm_variant).cloned()),
         });
     }
 
-    if let Some(flatc_exe) = cfg.flatc.as_ref() {
-        if let Some(map) = donor_by_species.as_ref() {
-            param_arrays::patch_param_arrays_per_species(
-                flatc_exe, &za_dump, &out_root, map, &progress,
-            )?;
-        } else {
-            param_arrays::patch_param_arrays(
-                flatc_exe,
-                &za_dump,
-                &out_root,
-                cfg.donor_dev,
-                &new_species,
-                &progress,
-            )?;
+    let keep_json_dir = cfg.keep_intermediates.then(|| report_dir.join("flatc"));
+    let flatc_opts = flatc::FlatcOptions {
+        keep_json_dir: keep_json_dir.as_deref(),
+        verify: cfg.verify_flatc,
+        profile: profile.as_ref(),
+    };
+    match (skip_reason(cfg, Phase::ParamArrays), cfg.flatc.as_ref()) {
+        (Some(reason), _) => {
+            if reason == plan::FLATC_NOT_SET {
+                progress.warn("[param/personal] flatc not set; skipping param + personal patch");
+            }
+            progress.phase_skipped(Phase::ParamArrays.name(), reason);
         }
-
-        if let Some(pknx_dir) = cfg.pknx_personal_dir.as_ref() {
+        (None, Some(flatc_exe)) => {
+            if cfg.verify_flatc {
+                progress.info("[flatc] verifying built bins by dumping them back");
+            }
+            if let Some(map) = donor_by_species.as_ref() {
+                param_arrays::patch_param_arrays_per_species(
+                    flatc_exe,
+                    &za_dump,
+                    &game_root,
+                    map,
+                    cfg.strict_param,
+                    flatc_opts,
+                    &progress,
+                )?;
+            } else {
+                param_arrays::patch_param_arrays(
+                    flatc_exe,
+                    &za_dump,
+                    &game_root,
+                    cfg.donor_dev,
+                    &new_species,
+                    flatc_opts,
+                    &progress,
+                )?;
+            }
+        }
+        (None, None) => unreachable!("skip_reason covers a missing flatc"),
+    }
+    match (
+        skip_reason(cfg, Phase::PersonalArray),
+        cfg.flatc.as_ref(),
+        cfg.pknx_personal_dir.as_ref(),
+    ) {
+        (Some(reason), ..) => {
+            if reason == plan::PKNX_NOT_SET {
+                progress.warn("[personal] pkNX personal dir not set; skipping personal patch");
+            }
+            progress.phase_skipped(Phase::PersonalArray.name(), reason);
+        }
+        (None, Some(flatc_exe), Some(pknx_dir)) => {
             personal::patch_personal_array_present(
                 flatc_exe,
                 &za_dump,
-                &out_root,
+                &game_root,
+                &cfg.personal_array_path,
                 pknx_dir,
                 &enable_keys,
+                flatc_opts,
                 &progress,
             )?;
-        } else {
-            progress.warn("[personal] pkNX personal dir not set; skipping personal patch");
         }
-    } else {
-        progress.warn("[param/personal] flatc not set; skipping param + personal patch");
+        _ => unreachable!("skip_reason covers a missing flatc or pkNX dir"),
     }
 
-    if cfg.generate_reports {
+    if let Some(reason) = skip_reason(cfg, Phase::NamesReport) {
+        progress.phase_skipped(Phase::NamesReport.name(), reason);
+    } else {
+        let mut langs = vec![cfg.language.clone()];
+        for l in &cfg.report_languages {
+            if !langs.contains(l) {
+                langs.push(l.clone());
+            }
+        }
         let _names_report = names::write_converted_names_report(
             &za_dump,
-            &out_root,
+            &report_dir,
             &converted,
-            &cfg.language,
+            &langs,
+            cfg.strict_names,
             &progress,
         )?;
+        names::write_pm_variant_map_report(&report_dir, &converted, &progress)?;
+        selection_report::write_selection_report(&report_dir, &converted, &progress)?;
+        catalog::write_za_only_report(&report_dir, &selection, &progress)?;
+        catalog::write_skipped_report(&report_dir, &selection, &progress)?;
+        catalog::write_coverage_report(&report_dir, &selection, &progress)?;
+    }
+
+    if cancel.is_canceled() {
+        progress.warn("canceled");
+        return Ok(summary.canceled());
+    }
+
+    if let Some(reason) = skip_reason(cfg, Phase::Textures) {
+        progress.phase_skipped(Phase::Textures.name(), reason);
     } else {
-        progress.info("[report] disabled; skipping converted names report");
+        textures::convert_textures_if_enabled(
+            cfg,
+            &za_dump,
+            &out_root,
+            profile.as_ref(),
+            tex_limits,
+            &progress,
+        )?;
     }
 
+    if let Some(species) = cfg.diagnostic_species {
+        diag::write_diagnostic_bundle(
+            &game_root,
+            &report_dir,
+            species,
+            &selection,
+            &converted,
+            &anim_stats,
+            &progress,
+        )?;
+    }
+    if let Some(profile) = &profile {
+        profile.write_report(&report_dir, &progress)?;
+    }
+    Ok(summary)
+}
+
+/// Runs the copy pipeline (copy, overlay, anim sync, icons, defence) for a single pm_variant
+/// without consulting either catalog
+pub fn run_single_pm_variant(
+    cfg: &AppConfig,
+    pm_variant: &str,
+    donor_pm_variant: Option<&str>,
+    progress: ProgressSink,
+    cancel: CancelToken,
+) -> anyhow::Result<RunSummary> {
+    let collector = progress.clone();
+    let progress = progress.with_verbosity(cfg.verbosity);
+    let mut summary = RunSummary {
+        pm_variants: 1,
+        ..Default::default()
+    };
+    let sv_root = canonicalish(
+        cfg.sv_root
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("SV root not set"))?,
+    );
+    let za_dump = canonicalish(
+        cfg.za_dump
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("ZA dump not set"))?,
+    );
+    let out_root = resolve_out_root(
+        cfg.out_root
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Output root not set"))?,
+    )?;
+    let Some((_, poke_root)) = detect_sv_layout(&sv_root) else {
+        anyhow::bail!("SV root must contain either 'pokemon/' or 'ik_pokemon/': {sv_root:?}");
+    };
+    write_output_marker(&out_root)?;
+
+    let pm = parse_pm_variant_name(pm_variant)
+        .map(|p| p.pm)
+        .ok_or_else(|| anyhow::anyhow!("expected a pmXXXX_YY_ZZ name, got {pm_variant:?}"))?;
+    let variants = vec![(pm, pm_variant.to_string())];
+    let donor_map =
+        donor_pm_variant.map(|d| HashMap::from([(pm_variant.to_string(), d.to_string())]));
+
+    progress.info(format!(
+        "single pm_variant: {pm_variant} (donor={})",
+        donor_pm_variant.unwrap_or("-")
+    ));
     if cancel.is_canceled() {
         progress.warn("canceled");
-        return Ok(());
+        return Ok(summary.canceled().with_warnings(&collector));
     }
 
-    textures::convert_textures_if_enabled(cfg, &za_dump, &out_root, &progress)?;
-    Ok(())
+    let anim_stats = copy_pm::copy_pm_variants(
+        &poke_root,
+        &za_dump,
+        &out_root,
+        cfg,
+        &variants,
+        donor_map.as_ref(),
+        &[],
+        &progress,
+    )?;
+    for s in &anim_stats {
+        progress.info(format!(
+            "[anim] {}: had_tracr={} tracks={} refs={} filled={} missing_src={} missing_after={}",
+            s.pm_variant, s.had_tracr, s.tracks, s.refs, s.filled, s.missing_src, s.missing_after
+        ));
+    }
+    summary.add_anim_stats(&anim_stats);
+    Ok(summary.with_warnings(&collector))
+}
+
+impl RunSummary {
+    fn with_warnings(self, sink: &ProgressSink) -> Self {
+        Self {
+            warnings: sink.warnings(),
+            skipped_phases: sink.skipped_phases(),
+            ..self
+        }
+    }
+
+    fn add_anim_stats(&mut self, stats: &[anim_sync::AnimSyncStats]) {
+        self.copied += stats.len();
+        self.anim_filled += stats.iter().map(|s| s.filled).sum::<usize>();
+        self.anim_missing_after += stats.iter().map(|s| s.missing_after).sum::<usize>();
+    }
 }
 
 fn load_autosave_template(cfg: &AppConfig) -> anyhow::Result<DonorTemplate> {
-    let _ = cfg;
+    if let Some(path) = cfg.template_path.as_ref() {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("failed to read template {path:?}: {e}"))?;
+        return serde_json::from_str(&text)
+            .map_err(|e| anyhow::anyhow!("failed to parse template {path:?}: {e}"));
+    }
     for dir in preferred_template_dirs() {
         let store = TemplateStore::new(dir);
         let path = store.autosave_path();
@@ -341,11 +730,3 @@ fn load_autosave_template(cfg: &AppConfig) -> anyhow::Result<DonorTemplate> {
     }
     Ok(DonorTemplate::default())
 }
-
-fn parse_pm_variant(model_path: &str) -> Option<(String, String)> {
-    let mp = model_path.replace('\\', "/");
-    let mut parts = mp.split('/').filter(|s| !s.is_empty());
-    let pm = parts.next()?.to_string();
-    let pm_variant = parts.next()?.to_string();
-    Some((pm, pm_variant))
-}
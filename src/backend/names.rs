@@ -1,4 +1,4 @@
-use crate::progress::ProgressSink;
+use crate::{progress::ProgressSink, template::Key};
 use serde::Serialize;
 use std::{
     collections::BTreeMap,
@@ -12,30 +12,74 @@ pub struct ConvertedMon {
     pub form: u16,
     pub gender: u8,
     pub name: String,
+    /// Name per report language (primary first); only filled when extra languages are configured
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub names: BTreeMap<String, String>,
     pub pm: String,
     pub pm_variant: String,
+    /// Donor key resolved from the template; all None/empty in the legacy flow
+    pub donor: Option<Key>,
+    pub donor_name: String,
+    pub donor_pm_variant: Option<String>,
 }
 
 pub fn write_converted_names_report(
     za_dump: &Path,
-    out_root: &Path,
+    report_dir: &Path,
     mons: &[ConvertedMon],
-    lang: &str,
+    langs: &[String],
+    strict_utf16: bool,
     progress: &ProgressSink,
 ) -> anyhow::Result<PathBuf> {
     progress.phase_start("Names report");
-    let name_map = load_monsname_map(za_dump, lang).unwrap_or_default();
+    let lang = langs.first().map(String::as_str).unwrap_or("English");
+    // strict mode is for debugging a new dump, so a bad table fails the run instead of blanking names
+    let name_map = match load_monsname_map(za_dump, lang, strict_utf16) {
+        Ok(map) => map,
+        Err(e) if strict_utf16 => return Err(e.context("--strict-names")),
+        Err(_) => BTreeMap::new(),
+    };
+    let extra_maps = if langs.len() > 1 {
+        langs
+            .iter()
+            .map(|l| {
+                let map = match load_monsname_map_no_fallback(za_dump, l, strict_utf16) {
+                    Ok(Some(map)) => map,
+                    Ok(None) => {
+                        progress.warn(format!("[names] no monsname tables for {l}"));
+                        BTreeMap::new()
+                    }
+                    Err(e) if strict_utf16 => {
+                        return Err(e.context(format!("--strict-names ({l})")));
+                    }
+                    Err(e) => {
+                        progress.warn(format!("[names] no names for {l}: {e}"));
+                        BTreeMap::new()
+                    }
+                };
+                Ok((l.clone(), map))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?
+    } else {
+        Vec::new()
+    };
 
     let mut out = Vec::with_capacity(mons.len());
     for m in mons {
         let mut m2 = m.clone();
         m2.name = name_map.get(&m.species).cloned().unwrap_or_default();
+        m2.names = extra_maps
+            .iter()
+            .map(|(l, map)| (l.clone(), map.get(&m.species).cloned().unwrap_or_default()))
+            .collect();
+        if let Some(donor) = m.donor {
+            m2.donor_name = name_map.get(&donor.species).cloned().unwrap_or_default();
+        }
         out.push(m2);
     }
     out.sort_by_key(|m| (m.species, m.form, m.gender, m.pm_variant.clone()));
 
-    let report_dir = out_root.join("_report");
-    fs::create_dir_all(&report_dir)?;
+    fs::create_dir_all(report_dir)?;
     let path = report_dir.join("converted_pokemon.json");
     fs::write(&path, serde_json::to_vec_pretty(&out)?)?;
     progress.info(format!(
@@ -47,26 +91,122 @@ pub fn write_converted_names_report(
     Ok(path)
 }
 
+#[derive(Debug, Clone, Serialize)]
+struct SpeciesRef {
+    species: u16,
+    form: u16,
+    gender: u8,
+}
+
+/// Writes `_report/pm_variant_map.json`: each converted pm_variant with the species keys using it.
+pub fn write_pm_variant_map_report(
+    report_dir: &Path,
+    mons: &[ConvertedMon],
+    progress: &ProgressSink,
+) -> anyhow::Result<PathBuf> {
+    let mut map: BTreeMap<&str, Vec<SpeciesRef>> = BTreeMap::new();
+    for m in mons {
+        map.entry(m.pm_variant.as_str())
+            .or_default()
+            .push(SpeciesRef {
+                species: m.species,
+                form: m.form,
+                gender: m.gender,
+            });
+    }
+    for refs in map.values_mut() {
+        refs.sort_by_key(|r| (r.species, r.form, r.gender));
+    }
+
+    fs::create_dir_all(report_dir)?;
+    let path = report_dir.join("pm_variant_map.json");
+    fs::write(&path, serde_json::to_vec_pretty(&map)?)?;
+    progress.info(format!(
+        "[report] wrote {:?} (pm_variants={})",
+        path,
+        map.len()
+    ));
+    Ok(path)
+}
+
+/// Expands `{species}` / `{species:0N}` in `format`; used when a species has no message-table name
+pub fn placeholder_name(format: &str, species: u16) -> String {
+    let mut out = String::new();
+    let mut rest = format;
+    while let Some(start) = rest.find("{species") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + "{species".len()..];
+        let Some(end) = after.find('}') else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        match &after[..end] {
+            "" => out.push_str(&species.to_string()),
+            spec => match spec
+                .strip_prefix(":0")
+                .and_then(|w| w.parse::<usize>().ok())
+            {
+                Some(width) => out.push_str(&format!("{species:0width$}")),
+                None => out.push_str(&rest[start..start + "{species".len() + end + 1]),
+            },
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Like `load_monsname_map` but without the English fallback; `None` when the dump lacks the language
+fn load_monsname_map_no_fallback(
+    dump_root: &Path,
+    language: &str,
+    strict_utf16: bool,
+) -> anyhow::Result<Option<BTreeMap<u16, String>>> {
+    let base = dump_root
+        .join("ik_message")
+        .join("dat")
+        .join(language.trim())
+        .join("common");
+    let (tbl, dat) = (base.join("monsname.tbl"), base.join("monsname.dat"));
+    if !tbl.is_file() || !dat.is_file() {
+        return Ok(None);
+    }
+    load_monsname_map_exact(&tbl, &dat, strict_utf16).map(Some)
+}
+
+/// `strict_utf16` turns invalid UTF-16 in a decoded name into an error instead of U+FFFD
 pub fn load_monsname_map(
     dump_root: &Path,
     language: &str,
+    strict_utf16: bool,
 ) -> anyhow::Result<BTreeMap<u16, String>> {
-    let mut tried = Vec::new();
-    for lang in candidate_langs(language) {
+    match find_monsname_tables(dump_root, language) {
+        Ok((tbl, dat)) => load_monsname_map_exact(&tbl, &dat, strict_utf16),
+        Err(_) => Ok(BTreeMap::new()),
+    }
+}
+
+/// The `monsname.tbl`/`.dat` pair `load_monsname_map` would read, trying the English fallbacks
+/// On failure returns the pair for the requested language so callers can report it
+pub(super) fn find_monsname_tables(
+    dump_root: &Path,
+    language: &str,
+) -> Result<(PathBuf, PathBuf), (PathBuf, PathBuf)> {
+    let tables = |lang: &str| {
         let base = dump_root
             .join("ik_message")
             .join("dat")
-            .join(&lang)
+            .join(lang)
             .join("common");
-        let tbl = base.join("monsname.tbl");
-        let dat = base.join("monsname.dat");
-        tried.push((lang, tbl.clone(), dat.clone()));
+        (base.join("monsname.tbl"), base.join("monsname.dat"))
+    };
+    for lang in candidate_langs(language) {
+        let (tbl, dat) = tables(&lang);
         if tbl.is_file() && dat.is_file() {
-            return load_monsname_map_exact(&tbl, &dat);
+            return Ok((tbl, dat));
         }
     }
-    let _ = tried;
-    Ok(BTreeMap::new())
+    Err(tables(language.trim()))
 }
 
 fn candidate_langs(language: &str) -> Vec<String> {
@@ -83,9 +223,13 @@ fn candidate_langs(language: &str) -> Vec<String> {
     out
 }
 
-fn load_monsname_map_exact(tbl: &Path, dat: &Path) -> anyhow::Result<BTreeMap<u16, String>> {
-    let keys = read_ahtb_keys(&tbl)?;
-    let strings = decode_dat_strings(&dat)?;
+fn load_monsname_map_exact(
+    tbl: &Path,
+    dat: &Path,
+    strict_utf16: bool,
+) -> anyhow::Result<BTreeMap<u16, String>> {
+    let keys = read_ahtb_keys(tbl)?;
+    let strings = decode_dat_strings(dat, strict_utf16)?;
     let mut out = BTreeMap::new();
     for (i, k) in keys.iter().enumerate() {
         if k == "msg_monsname_max" {
@@ -142,7 +286,9 @@ fn crypt_utf16_codes(codes: &[u16], str_id: u16) -> Vec<u16> {
     out
 }
 
-fn decode_dat_strings(dat_path: &Path) -> anyhow::Result<Vec<String>> {
+/// Lossy by default; with `strict_utf16` an undecodable string is an error, which is how a wrong
+/// key or offset shows up instead of as mojibake
+fn decode_dat_strings(dat_path: &Path, strict_utf16: bool) -> anyhow::Result<Vec<String>> {
     let b = fs::read(dat_path)?;
     if b.len() < 16 {
         return Ok(Vec::new());
@@ -180,8 +326,63 @@ fn decode_dat_strings(dat_path: &Path) -> anyhow::Result<Vec<String>> {
             Some(i) => &dec[..i],
             None => &dec[..],
         };
-        let s = String::from_utf16_lossy(dec);
+        let s = if strict_utf16 {
+            String::from_utf16(dec).map_err(|e| {
+                anyhow::anyhow!("{dat_path:?}: string {str_id} is not valid UTF-16: {e}")
+            })?
+        } else {
+            String::from_utf16_lossy(dec)
+        };
         out.push(s);
     }
     Ok(out)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Single-language `.dat` holding `strings` as raw UTF-16 code units, encrypted like the game's
+    fn write_dat(path: &Path, strings: &[&[u16]]) {
+        let lang0 = 16usize;
+        let params = lang0 + 4;
+        let mut text_ofs = 4 + strings.len() * 8;
+        let mut b = vec![0u8; params + strings.len() * 8];
+        b[0..2].copy_from_slice(&1u16.to_le_bytes());
+        b[2..4].copy_from_slice(&(strings.len() as u16).to_le_bytes());
+        b[12..16].copy_from_slice(&(lang0 as u32).to_le_bytes());
+        for (i, codes) in strings.iter().enumerate() {
+            let p = params + i * 8;
+            b[p..p + 4].copy_from_slice(&(text_ofs as u32).to_le_bytes());
+            b[p + 4..p + 6].copy_from_slice(&(codes.len() as u16).to_le_bytes());
+            for c in crypt_utf16_codes(codes, i as u16) {
+                b.extend_from_slice(&c.to_le_bytes());
+            }
+            text_ofs += codes.len() * 2;
+        }
+        fs::write(path, b).unwrap();
+    }
+
+    #[test]
+    fn decode_dat_strings_round_trips_valid_text() {
+        let td = tempfile::tempdir().unwrap();
+        let dat = td.path().join("monsname.dat");
+        let pikachu: Vec<u16> = "Pikachu".encode_utf16().collect();
+        write_dat(&dat, &[&pikachu, &[0x0045, 0x0000, 0x0046]]);
+        for strict in [false, true] {
+            assert_eq!(decode_dat_strings(&dat, strict).unwrap(), ["Pikachu", "E"]);
+        }
+    }
+
+    #[test]
+    fn decode_dat_strings_invalid_utf16_fails_only_when_strict() {
+        let td = tempfile::tempdir().unwrap();
+        let dat = td.path().join("monsname.dat");
+        // a lone high surrogate
+        write_dat(&dat, &[&[0x0041, 0xD800, 0x0042]]);
+
+        let err = decode_dat_strings(&dat, true).unwrap_err();
+        assert!(err.to_string().contains("not valid UTF-16"), "{err}");
+        assert_eq!(decode_dat_strings(&dat, false).unwrap(), ["A\u{FFFD}B"]);
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{backend::flatc, progress::ProgressSink};
+use crate::{
+    backend::flatc::{self, FlatcOptions},
+    progress::{ProgressSink, WarningKind},
+};
 use serde_json::Value;
 use std::{
     collections::HashSet,
@@ -6,45 +9,57 @@ use std::{
     path::{Path, PathBuf},
 };
 
+/// The ZA param arrays and their reflection schemas read by the param phase
+pub(super) struct ParamInputs {
+    pub model_bin: PathBuf,
+    pub model_bfbs: PathBuf,
+    pub move_bin: PathBuf,
+    pub move_bfbs: PathBuf,
+}
+
+pub(super) fn param_inputs(za_dump: &Path) -> ParamInputs {
+    let model = za_dump
+        .join("param_chr")
+        .join("data")
+        .join("pokemon")
+        .join("poke_model_param");
+    let movement = za_dump
+        .join("param_chr")
+        .join("data")
+        .join("character")
+        .join("pokemon")
+        .join("poke_movement_param");
+    ParamInputs {
+        model_bin: model.join("poke_model_param_array.bin"),
+        model_bfbs: model.join("poke_model_param_array.bfbs"),
+        move_bin: movement.join("poke_movement_param_array.bin"),
+        move_bfbs: movement.join("poke_movement_param_array.bfbs"),
+    }
+}
+
 pub fn patch_param_arrays(
     flatc_exe: &Path,
     za_dump: &Path,
     out_root: &Path,
     donor_dev: u32,
     new_species: &HashSet<u16>,
+    flatc_opts: FlatcOptions,
     progress: &ProgressSink,
 ) -> anyhow::Result<()> {
     progress.phase_start("Patch param arrays");
 
-    let model_bin_in = za_dump
-        .join("param_chr")
-        .join("data")
-        .join("pokemon")
-        .join("poke_model_param")
-        .join("poke_model_param_array.bin");
-    let model_bfbs = za_dump
-        .join("param_chr")
-        .join("data")
-        .join("pokemon")
-        .join("poke_model_param")
-        .join("poke_model_param_array.bfbs");
-    let move_bin_in = za_dump
-        .join("param_chr")
-        .join("data")
-        .join("character")
-        .join("pokemon")
-        .join("poke_movement_param")
-        .join("poke_movement_param_array.bin");
-    let move_bfbs = za_dump
-        .join("param_chr")
-        .join("data")
-        .join("character")
-        .join("pokemon")
-        .join("poke_movement_param")
-        .join("poke_movement_param_array.bfbs");
+    let ParamInputs {
+        model_bin: model_bin_in,
+        model_bfbs,
+        move_bin: move_bin_in,
+        move_bfbs,
+    } = param_inputs(za_dump);
 
     if !model_bin_in.is_file() || !model_bfbs.is_file() {
-        progress.warn("[param] missing ZA model param bin/bfbs; skipping");
+        progress.warn_kind(
+            WarningKind::MissingAsset,
+            "[param] missing ZA model param bin/bfbs; skipping",
+        );
     } else {
         let model_out = out_root
             .join("param_chr")
@@ -60,12 +75,16 @@ pub fn patch_param_arrays(
             "devId",
             donor_dev,
             new_species,
+            flatc_opts,
             progress,
         )?;
     }
 
     if !move_bin_in.is_file() || !move_bfbs.is_file() {
-        progress.warn("[param] missing ZA movement param bin/bfbs; skipping");
+        progress.warn_kind(
+            WarningKind::MissingAsset,
+            "[param] missing ZA movement param bin/bfbs; skipping",
+        );
     } else {
         let move_out = out_root
             .join("param_chr")
@@ -82,6 +101,7 @@ pub fn patch_param_arrays(
             "devNo",
             donor_dev,
             new_species,
+            flatc_opts,
             progress,
         )?;
     }
@@ -95,36 +115,18 @@ pub fn patch_param_arrays_per_species(
     za_dump: &Path,
     out_root: &Path,
     donor_by_species: &std::collections::BTreeMap<u16, u16>,
+    strict: bool,
+    flatc_opts: FlatcOptions,
     progress: &ProgressSink,
 ) -> anyhow::Result<()> {
     progress.phase_start("Patch param arrays");
 
-    let model_bin_in = za_dump
-        .join("param_chr")
-        .join("data")
-        .join("pokemon")
-        .join("poke_model_param")
-        .join("poke_model_param_array.bin");
-    let model_bfbs = za_dump
-        .join("param_chr")
-        .join("data")
-        .join("pokemon")
-        .join("poke_model_param")
-        .join("poke_model_param_array.bfbs");
-    let move_bin_in = za_dump
-        .join("param_chr")
-        .join("data")
-        .join("character")
-        .join("pokemon")
-        .join("poke_movement_param")
-        .join("poke_movement_param_array.bin");
-    let move_bfbs = za_dump
-        .join("param_chr")
-        .join("data")
-        .join("character")
-        .join("pokemon")
-        .join("poke_movement_param")
-        .join("poke_movement_param_array.bfbs");
+    let ParamInputs {
+        model_bin: model_bin_in,
+        model_bfbs,
+        move_bin: move_bin_in,
+        move_bfbs,
+    } = param_inputs(za_dump);
 
     if model_bin_in.is_file() && model_bfbs.is_file() {
         let model_out = out_root
@@ -140,10 +142,15 @@ pub fn patch_param_arrays_per_species(
             &model_out,
             "devId",
             donor_by_species,
+            strict,
+            flatc_opts,
             progress,
         )?;
     } else {
-        progress.warn("[param] missing ZA model param bin/bfbs; skipping");
+        progress.warn_kind(
+            WarningKind::MissingAsset,
+            "[param] missing ZA model param bin/bfbs; skipping",
+        );
     }
 
     if move_bin_in.is_file() && move_bfbs.is_file() {
@@ -161,16 +168,22 @@ pub fn patch_param_arrays_per_species(
             &move_out,
             "devNo",
             donor_by_species,
+            strict,
+            flatc_opts,
             progress,
         )?;
     } else {
-        progress.warn("[param] missing ZA movement param bin/bfbs; skipping");
+        progress.warn_kind(
+            WarningKind::MissingAsset,
+            "[param] missing ZA movement param bin/bfbs; skipping",
+        );
     }
 
     progress.phase_end("Patch param arrays");
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 fn patch_one(
     flatc_exe: &Path,
     bfbs: &Path,
@@ -179,6 +192,7 @@ fn patch_one(
     key: &str,
     donor_dev: u32,
     new_species: &HashSet<u16>,
+    flatc_opts: FlatcOptions,
     progress: &ProgressSink,
 ) -> anyhow::Result<()> {
     if let Some(parent) = out_bin.parent() {
@@ -196,7 +210,8 @@ fn patch_one(
     }
 
     let td = tempfile::tempdir()?;
-    let json_path = flatc::flatc_dump_json(flatc_exe, bfbs, &[], src_bin, td.path())?;
+    let json_path =
+        flatc::flatc_dump_json(flatc_exe, bfbs, &[], src_bin, td.path(), flatc_opts.profile)?;
     let mut obj: Value = serde_json::from_slice(&fs::read(&json_path)?)?;
 
     let values = obj
@@ -228,11 +243,25 @@ fn patch_one(
         if !out_bin.exists() {
             fs::copy(src_bin, out_bin)?;
         }
+        flatc::keep_intermediates(td.path(), flatc_opts.keep_json_dir, &bin_stem(out_bin))?;
         return Ok(());
     }
 
-    let donor_idx = find_index_by_key(values, key, donor_dev as u64)
-        .ok_or_else(|| anyhow::anyhow!("donor not found: {key}={donor_dev}"))?;
+    let Some(donor_idx) = find_index_by_key(values, key, donor_dev as u64) else {
+        let mut valid = existing.iter().copied().collect::<Vec<_>>();
+        valid.sort();
+        valid.truncate(10);
+        progress.warn_kind(WarningKind::MissingDonor, format!(
+            "[param] donor not found: {key}={donor_dev} in {}; leaving it unpatched (pick a --donor-dev that exists, e.g. {:?})",
+            out_bin.file_name().unwrap_or_default().to_string_lossy(),
+            valid
+        ));
+        if !out_bin.exists() {
+            fs::copy(src_bin, out_bin)?;
+        }
+        flatc::keep_intermediates(td.path(), flatc_opts.keep_json_dir, &bin_stem(out_bin))?;
+        return Ok(());
+    };
     let donor_entry = extract_single_root_entry(&values[donor_idx])
         .ok_or_else(|| anyhow::anyhow!("unexpected donor entry shape"))?
         .clone();
@@ -251,9 +280,21 @@ fn patch_one(
         added += 1;
     }
 
+    progress.metric(format!("param.{key}.added"), added as i64);
+
     let out_json = td.path().join("out.json");
     fs::write(&out_json, serde_json::to_vec_pretty(&obj)?)?;
-    flatc::flatc_build_bin(flatc_exe, bfbs, &[], &out_json, out_bin)?;
+    flatc::keep_intermediates(td.path(), flatc_opts.keep_json_dir, &bin_stem(out_bin))?;
+    let expected = param_ids(&obj, key);
+    flatc::flatc_build_bin_checked(
+        flatc_exe,
+        bfbs,
+        &[],
+        &out_json,
+        out_bin,
+        flatc_opts,
+        |doc| check_param_ids(doc, key, &expected),
+    )?;
     progress.info(format!(
         "[param] patched {}: added {} ({}) from donor {}",
         out_bin.file_name().unwrap_or_default().to_string_lossy(),
@@ -264,6 +305,7 @@ fn patch_one(
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 fn patch_one_with_map(
     flatc_exe: &Path,
     bfbs: &Path,
@@ -271,6 +313,8 @@ fn patch_one_with_map(
     out_bin: &Path,
     key: &str,
     donor_by_species: &std::collections::BTreeMap<u16, u16>,
+    strict: bool,
+    flatc_opts: FlatcOptions,
     progress: &ProgressSink,
 ) -> anyhow::Result<()> {
     if let Some(parent) = out_bin.parent() {
@@ -278,7 +322,8 @@ fn patch_one_with_map(
     }
 
     let td = tempfile::tempdir()?;
-    let json_path = flatc::flatc_dump_json(flatc_exe, bfbs, &[], src_bin, td.path())?;
+    let json_path =
+        flatc::flatc_dump_json(flatc_exe, bfbs, &[], src_bin, td.path(), flatc_opts.profile)?;
     let mut obj: Value = serde_json::from_slice(&fs::read(&json_path)?)?;
 
     let values = obj
@@ -307,13 +352,30 @@ fn patch_one_with_map(
         }
     }
 
+    let missing: Vec<String> = donor_by_species
+        .iter()
+        .filter(|(t, d)| !existing.contains(t) && !donor_entry_by_id.contains_key(d))
+        .map(|(t, d)| format!("{t} (donor {d})"))
+        .collect();
+    if strict && !missing.is_empty() {
+        anyhow::bail!(
+            "[param] {} target(s) have no donor entry in {} ({key}): {}",
+            missing.len(),
+            src_bin.file_name().unwrap_or_default().to_string_lossy(),
+            missing.join(", ")
+        );
+    }
+
     let mut added = 0usize;
     for (&target_id, &donor_id) in donor_by_species {
         if existing.contains(&target_id) {
             continue;
         }
         let Some(donor_entry) = donor_entry_by_id.get(&donor_id) else {
-            progress.warn(format!("[param] donor not found: {key}={donor_id}"));
+            progress.warn_kind(
+                WarningKind::MissingDonor,
+                format!("[param] donor not found: {key}={donor_id}"),
+            );
             continue;
         };
         let mut new_entry = donor_entry.clone();
@@ -332,12 +394,25 @@ fn patch_one_with_map(
         if !out_bin.exists() {
             fs::copy(src_bin, out_bin)?;
         }
+        flatc::keep_intermediates(td.path(), flatc_opts.keep_json_dir, &bin_stem(out_bin))?;
         return Ok(());
     }
 
+    progress.metric(format!("param.{key}.added"), added as i64);
+
     let out_json = td.path().join("out.json");
     fs::write(&out_json, serde_json::to_vec_pretty(&obj)?)?;
-    flatc::flatc_build_bin(flatc_exe, bfbs, &[], &out_json, out_bin)?;
+    flatc::keep_intermediates(td.path(), flatc_opts.keep_json_dir, &bin_stem(out_bin))?;
+    let expected = param_ids(&obj, key);
+    flatc::flatc_build_bin_checked(
+        flatc_exe,
+        bfbs,
+        &[],
+        &out_json,
+        out_bin,
+        flatc_opts,
+        |doc| check_param_ids(doc, key, &expected),
+    )?;
     progress.info(format!(
         "[param] patched {}: added {} ({})",
         out_bin.file_name().unwrap_or_default().to_string_lossy(),
@@ -347,6 +422,41 @@ fn patch_one_with_map(
     Ok(())
 }
 
+/// Every `key` id in a param array, in file order
+fn param_ids(obj: &Value, key: &str) -> Vec<u64> {
+    obj.get("values")
+        .and_then(|v| v.as_array())
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|item| extract_single_root_entry(item)?.get(key)?.as_u64())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn check_param_ids(doc: &Value, key: &str, expected: &[u64]) -> anyhow::Result<()> {
+    let got = param_ids(doc, key);
+    if got.len() != expected.len() {
+        anyhow::bail!(
+            "{key}: wrote {} entries, read back {}",
+            expected.len(),
+            got.len()
+        );
+    }
+    if let Some((w, r)) = expected.iter().zip(&got).find(|(w, r)| w != r) {
+        anyhow::bail!("{key}: wrote id {w}, read back {r} at the same position");
+    }
+    Ok(())
+}
+
+fn bin_stem(p: &Path) -> String {
+    p.file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string()
+}
+
 fn extract_single_root_entry(item: &Value) -> Option<&Value> {
     let root = item.get("root")?.as_array()?;
     if root.len() != 1 {
@@ -397,3 +507,36 @@ fn insert_sorted_by_key(
     values.insert(insert_at, new_item.clone());
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn param_doc(ids: &[u64]) -> Value {
+        let values: Vec<Value> = ids
+            .iter()
+            .map(|id| json!({"root": [{"id": id, "name": "x"}]}))
+            .collect();
+        json!({ "values": values })
+    }
+
+    #[test]
+    fn check_param_ids_cases() {
+        let doc = param_doc(&[1, 2, 3]);
+        assert_eq!(param_ids(&doc, "id"), [1, 2, 3]);
+        assert!(check_param_ids(&doc, "id", &[1, 2, 3]).is_ok());
+
+        let err = check_param_ids(&doc, "id", &[1, 2])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("wrote 2 entries, read back 3"), "{err}");
+
+        let err = check_param_ids(&doc, "id", &[1, 4, 3])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("wrote id 4, read back 2"), "{err}");
+
+        assert!(check_param_ids(&json!({}), "id", &[]).is_ok());
+    }
+}
@@ -1,9 +1,14 @@
 use crate::{
-    fb::trpmcatalog::{self, AnimationInfo, CatalogEntryFull, LocatorInfo, SpeciesKey},
-    progress::ProgressSink,
+    backend::catalog::{read_catalog_doc, KeyFilter},
+    fb::{
+        trpmcatalog::{self, AnimationInfo, CatalogEntryFull, LocatorInfo, SpeciesKey},
+        trpokecfg,
+    },
+    paths::{atomic_write, parse_model_path},
+    progress::{ProgressSink, WarningKind},
 };
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fs,
     path::{Path, PathBuf},
 };
@@ -13,12 +18,48 @@ pub struct PatchMon {
     pub key: SpeciesKey,
     pub pm: String,
     pub pm_variant: String,
+    /// Locators carried over from the SV catalog entry; empty falls back to the two-locator default
+    pub locators: Vec<LocatorInfo>,
+    /// Animations carried over from the SV catalog entry; empty falls back to one `.tracn`
+    pub animations: Vec<AnimationInfo>,
+    /// `unk_id` of the SV catalog entry; 0 when the key has no SV entry
+    pub unk_id: u32,
+}
+
+impl PatchMon {
+    /// A mon for `key`, carrying over locators, animations and `unk_id` from its SV catalog entry
+    /// when there is one
+    pub fn new(
+        key: SpeciesKey,
+        pm: String,
+        pm_variant: String,
+        sv_entries: &HashMap<SpeciesKey, SvEntryInfo>,
+    ) -> Self {
+        let sv = sv_entries.get(&key);
+        Self {
+            key,
+            pm,
+            pm_variant,
+            locators: sv.map(|e| e.locators.clone()).unwrap_or_default(),
+            animations: sv.map(|e| e.animations.clone()).unwrap_or_default(),
+            unk_id: sv.map_or(0, |e| e.unk_id),
+        }
+    }
+}
+
+/// What an SV catalog entry contributes to the synthesized ZA entry
+#[derive(Debug, Clone, Default)]
+pub struct SvEntryInfo {
+    pub locators: Vec<LocatorInfo>,
+    pub animations: Vec<AnimationInfo>,
+    pub unk_id: u32,
 }
 
 pub fn patch_za_catalog(
     za_dump: &Path,
     out_root: &Path,
     mons: &[PatchMon],
+    remove: &KeyFilter,
     progress: &ProgressSink,
 ) -> anyhow::Result<PathBuf> {
     progress.phase_start("Patch ZA catalog");
@@ -31,7 +72,7 @@ pub fn patch_za_catalog(
         anyhow::bail!("ZA catalog not found at expected path: {in_path:?}");
     }
 
-    let mut doc = trpmcatalog::read_doc(fs::read(&in_path)?)?;
+    let mut doc = read_catalog_doc(&in_path)?;
     let mut index = HashMap::<SpeciesKey, usize>::new();
     for (i, e) in doc.entries.iter().enumerate() {
         index.insert(e.key, i);
@@ -39,32 +80,7 @@ pub fn patch_za_catalog(
 
     let mut changed = 0usize;
     for m in mons {
-        let base = format!("{}/{}", m.pm, m.pm_variant);
-        let entry = CatalogEntryFull {
-            key: m.key,
-            model_path: format!("{base}/{}.trmdl", m.pm_variant),
-            material_table_path: format!("{base}/{}.trmmt", m.pm_variant),
-            config_path: format!("{base}/{}.trpokecfg", m.pm_variant),
-            animations: vec![AnimationInfo {
-                form_number: m.key.form as i16,
-                path: format!("{base}/{}.tracn", m.pm_variant),
-            }],
-            locators: vec![
-                LocatorInfo {
-                    form_number: m.key.form as i16,
-                    loc_index: 0,
-                    loc_path: format!("{base}/{}_00000.trskl", m.pm_variant),
-                },
-                LocatorInfo {
-                    form_number: m.key.form as i16,
-                    loc_index: 1,
-                    loc_path: format!("{base}/{}_20000.trskl", m.pm_variant),
-                },
-            ],
-            icon_path: format!("{base}/{}_00.bntx", m.pm_variant),
-            unk_id: 0,
-            defence_path: format!("{base}/{}_defence.hkx", m.pm_variant),
-        };
+        let entry = synth_entry(m);
 
         if let Some(i) = index.get(&m.key).copied() {
             doc.entries[i] = entry;
@@ -73,6 +89,22 @@ pub fn patch_za_catalog(
         }
         changed += 1;
     }
+    // `remove` is an exclude-only filter: entries it rejects are the ones to delete
+    let before = doc.entries.len();
+    doc.entries.retain(|e| {
+        let keep = remove.matches(e.key);
+        if !keep {
+            progress.info(format!(
+                "[catalog] removed species={} form={} gender={} ({})",
+                e.key.species, e.key.form, e.key.gender, e.model_path
+            ));
+        }
+        keep
+    });
+    let removed = before - doc.entries.len();
+    if removed > 0 {
+        progress.info(format!("[catalog] removed entries: {removed}"));
+    }
 
     let out_path = out_root
         .join("ik_pokemon")
@@ -82,17 +114,477 @@ pub fn patch_za_catalog(
     if let Some(parent) = out_path.parent() {
         fs::create_dir_all(parent)?;
     }
+    let bin = trpmcatalog::write_doc(&doc)?;
+    // leave the file (and its mtime) alone when a re-run would produce the same bytes
+    if fs::read(&out_path).is_ok_and(|old| old == bin) {
+        progress.info(format!(
+            "[catalog] catalog unchanged ({changed} entries); not rewritten"
+        ));
+        progress.phase_end("Patch ZA catalog");
+        return Ok(out_path);
+    }
     if out_path.is_file() {
         let bak = out_path.with_extension("trpmcatalog.pre_patch.bak");
         if !bak.exists() {
             fs::copy(&out_path, bak)?;
         }
     }
-
-    let bin = trpmcatalog::write_doc(&doc)?;
-    fs::write(&out_path, bin)?;
+    atomic_write(&out_path, &bin)?;
 
     progress.info(format!("[catalog] patched entries: {changed}"));
     progress.phase_end("Patch ZA catalog");
     Ok(out_path)
 }
+
+/// For each selected mon, the SV catalog entries of its other genders that use the same model
+/// `sv_model_paths` is the SV catalog's key -> model path; returned mons are not in `mons` yet.
+/// Keys in `skip` (e.g. ones ZA already ships, when those are left alone, or ones the species
+/// filters reject) are never mirrored.
+pub fn mirror_gender_mons(
+    mons: &[PatchMon],
+    sv_model_paths: &HashMap<SpeciesKey, String>,
+    sv_entries: &HashMap<SpeciesKey, SvEntryInfo>,
+    skip: impl Fn(&SpeciesKey) -> bool,
+    progress: &ProgressSink,
+) -> Vec<PatchMon> {
+    let mut taken: HashSet<SpeciesKey> = mons.iter().map(|m| m.key).collect();
+    let mut sv_keys: Vec<&SpeciesKey> = sv_model_paths.keys().collect();
+    sv_keys.sort_by_key(|k| (k.species, k.form, k.gender));
+
+    let mut out = Vec::new();
+    for m in mons {
+        for &k in &sv_keys {
+            if (k.species, k.form) != (m.key.species, m.key.form) || taken.contains(k) || skip(k) {
+                continue;
+            }
+            let same_model = sv_model_paths
+                .get(k)
+                .and_then(|p| parse_model_path(p))
+                .is_some_and(|(_, v)| v == m.pm_variant);
+            if !same_model {
+                continue;
+            }
+            taken.insert(*k);
+            progress.info(format!(
+                "[catalog] mirrored gender {} -> {}: species={} form={} ({})",
+                m.key.gender, k.gender, k.species, k.form, m.pm_variant
+            ));
+            out.push(PatchMon::new(
+                *k,
+                m.pm.clone(),
+                m.pm_variant.clone(),
+                sv_entries,
+            ));
+        }
+    }
+    out
+}
+
+/// Lists catalog-referenced files for `mons` that are missing under `out_root/ik_pokemon/data`
+pub fn missing_output_assets(out_root: &Path, mons: &[PatchMon]) -> Vec<PathBuf> {
+    let data_root = out_root.join("ik_pokemon").join("data");
+    let mut out = Vec::new();
+    for m in mons {
+        for rel in referenced_paths(synth_entry(m)) {
+            let p = data_root.join(&rel);
+            if !p.is_file() && !out.contains(&p) {
+                out.push(p);
+            }
+        }
+    }
+    out
+}
+
+/// Catalog-referenced files for one pm_variant that are missing from `data_root`, relative to it
+/// Paths come from the entries of `mons` on that pm_variant (SV animations and locators included);
+/// with none, from the default entry the catalog patch would synthesize.
+pub fn missing_variant_assets(
+    data_root: &Path,
+    pm: &str,
+    pm_variant: &str,
+    mons: &[PatchMon],
+) -> Vec<String> {
+    let default_mon;
+    let mut on_variant = mons
+        .iter()
+        .filter(|m| m.pm_variant == pm_variant)
+        .collect::<Vec<_>>();
+    if on_variant.is_empty() {
+        default_mon = PatchMon::new(
+            SpeciesKey {
+                species: 0,
+                form: 0,
+                gender: 0,
+            },
+            pm.to_string(),
+            pm_variant.to_string(),
+            &HashMap::new(),
+        );
+        on_variant.push(&default_mon);
+    }
+    let mut out = Vec::new();
+    for m in on_variant {
+        for rel in referenced_paths(synth_entry(m)) {
+            if !data_root.join(&rel).is_file() && !out.contains(&rel) {
+                out.push(rel);
+            }
+        }
+    }
+    out
+}
+
+fn referenced_paths(e: CatalogEntryFull) -> Vec<String> {
+    let mut rels = vec![
+        e.model_path,
+        e.material_table_path,
+        e.config_path,
+        e.icon_path,
+        e.defence_path,
+    ];
+    rels.extend(e.animations.into_iter().map(|a| a.path));
+    rels.extend(e.locators.into_iter().map(|l| l.loc_path));
+    rels
+}
+
+/// Reads each converted `.trpokecfg` in the output and warns when it is missing or its model,
+/// material or skeleton still names another pm_variant (e.g. a donor left behind by the ZA base
+/// overlay); returns the number of problems
+pub fn validate_pokecfg_refs(out_root: &Path, mons: &[PatchMon], progress: &ProgressSink) -> usize {
+    let data_root = out_root.join("ik_pokemon").join("data");
+    let mut seen = HashSet::new();
+    let mut problems = 0usize;
+    for m in mons {
+        if !seen.insert(m.pm_variant.as_str()) {
+            continue;
+        }
+        let dir = data_root.join(&m.pm).join(&m.pm_variant);
+        let main_cfg = dir.join(format!("{}.trpokecfg", m.pm_variant));
+        if !main_cfg.is_file() {
+            progress.warn_kind(
+                WarningKind::MissingAsset,
+                format!("[pokecfg] missing {:?}", main_cfg),
+            );
+            problems += 1;
+        }
+        let oybn_cfg = dir.join(format!("{}_oybn.trpokecfg", m.pm_variant));
+        for p in [main_cfg, oybn_cfg] {
+            let Ok(b) = fs::read(&p) else {
+                continue;
+            };
+            match trpokecfg::read_pm_variant_refs(&b) {
+                Ok(refs) => {
+                    let foreign = refs
+                        .into_iter()
+                        .filter(|r| *r != m.pm_variant)
+                        .collect::<Vec<_>>();
+                    if !foreign.is_empty() {
+                        progress.warn(format!(
+                            "[pokecfg] {:?} references {:?} instead of {}",
+                            p, foreign, m.pm_variant
+                        ));
+                        problems += 1;
+                    }
+                }
+                Err(e) => {
+                    progress.warn(format!("[pokecfg] failed to read {:?}: {e}", p));
+                    problems += 1;
+                }
+            }
+        }
+    }
+    progress.info(format!(
+        "[pokecfg] validated {} pm_variants, problems={problems}",
+        seen.len()
+    ));
+    problems
+}
+
+/// Locators, animations and `unk_id` of every SV catalog entry, keyed by species key
+pub fn sv_entries_by_key(sv_catalog: &Path) -> anyhow::Result<HashMap<SpeciesKey, SvEntryInfo>> {
+    let doc = read_catalog_doc(sv_catalog)?;
+    Ok(doc
+        .entries
+        .into_iter()
+        .map(|e| {
+            (
+                e.key,
+                SvEntryInfo {
+                    locators: e.locators,
+                    animations: e.animations,
+                    unk_id: e.unk_id,
+                },
+            )
+        })
+        .collect())
+}
+
+fn synth_entry(m: &PatchMon) -> CatalogEntryFull {
+    let base = format!("{}/{}", m.pm, m.pm_variant);
+    // SV paths point at the same pm_variant folder; only the file name is carried over
+    let locators = if m.locators.is_empty() {
+        default_locators(m, &base)
+    } else {
+        m.locators
+            .iter()
+            .map(|l| LocatorInfo {
+                form_number: l.form_number,
+                loc_index: l.loc_index,
+                loc_path: under_base(&base, &l.loc_path),
+            })
+            .collect()
+    };
+    let animations = if m.animations.is_empty() {
+        vec![AnimationInfo {
+            form_number: m.key.form as i16,
+            path: format!("{base}/{}.tracn", m.pm_variant),
+        }]
+    } else {
+        m.animations
+            .iter()
+            .map(|a| AnimationInfo {
+                form_number: a.form_number,
+                path: under_base(&base, &a.path),
+            })
+            .collect()
+    };
+    CatalogEntryFull {
+        key: m.key,
+        model_path: format!("{base}/{}.trmdl", m.pm_variant),
+        material_table_path: format!("{base}/{}.trmmt", m.pm_variant),
+        config_path: format!("{base}/{}.trpokecfg", m.pm_variant),
+        animations,
+        locators,
+        icon_path: format!("{base}/{}_00.bntx", m.pm_variant),
+        unk_id: m.unk_id,
+        defence_path: format!("{base}/{}_defence.hkx", m.pm_variant),
+    }
+}
+
+/// `sv_path`'s file name re-rooted under the output `pm/pm_variant` folder
+fn under_base(base: &str, sv_path: &str) -> String {
+    format!(
+        "{base}/{}",
+        sv_path.rsplit(['/', '\\']).next().unwrap_or(sv_path)
+    )
+}
+
+fn default_locators(m: &PatchMon, base: &str) -> Vec<LocatorInfo> {
+    vec![
+        LocatorInfo {
+            form_number: m.key.form as i16,
+            loc_index: 0,
+            loc_path: format!("{base}/{}_00000.trskl", m.pm_variant),
+        },
+        LocatorInfo {
+            form_number: m.key.form as i16,
+            loc_index: 1,
+            loc_path: format!("{base}/{}_20000.trskl", m.pm_variant),
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fb::testbuf::{self, Field};
+
+    fn key(form: u16) -> SpeciesKey {
+        SpeciesKey {
+            species: 25,
+            form,
+            gender: 0,
+        }
+    }
+
+    #[test]
+    fn missing_variant_assets_uses_sv_entry_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("pm0025").join("pm0025_00_00");
+        fs::create_dir_all(&base).unwrap();
+        for f in [
+            "pm0025_00_00.trmdl",
+            "pm0025_00_00.trmmt",
+            "pm0025_00_00.trpokecfg",
+            "pm0025_00_00_00.bntx",
+            "pm0025_00_00_defence.hkx",
+            // present under the default names only
+            "pm0025_00_00.tracn",
+            "pm0025_00_00_00000.trskl",
+        ] {
+            fs::write(base.join(f), b"").unwrap();
+        }
+        let sv_entries = HashMap::from([(
+            key(0),
+            SvEntryInfo {
+                animations: vec![AnimationInfo {
+                    form_number: 0,
+                    path: "pokemon/data/pm0025/pm0025_00_00/pm0025_00_00_base.tracn".into(),
+                }],
+                locators: vec![LocatorInfo {
+                    form_number: 0,
+                    loc_index: 0,
+                    loc_path: "pokemon/data/pm0025/pm0025_00_00/pm0025_00_00_00000.trskl".into(),
+                }],
+                unk_id: 7,
+            },
+        )]);
+        let mons = [PatchMon::new(
+            key(0),
+            "pm0025".into(),
+            "pm0025_00_00".into(),
+            &sv_entries,
+        )];
+
+        let missing = missing_variant_assets(dir.path(), "pm0025", "pm0025_00_00", &mons);
+        assert_eq!(missing, ["pm0025/pm0025_00_00/pm0025_00_00_base.tracn"]);
+
+        // Without a selected entry the default synthesized paths are checked
+        let missing = missing_variant_assets(dir.path(), "pm0025", "pm0025_00_00", &[]);
+        assert_eq!(missing, ["pm0025/pm0025_00_00/pm0025_00_00_20000.trskl"]);
+
+        // unk_id comes from the SV entry, and is 0 for a key without one
+        assert_eq!(synth_entry(&mons[0]).unk_id, 7);
+        let no_sv = PatchMon::new(key(1), "pm0025".into(), "pm0025_01_00".into(), &sv_entries);
+        assert_eq!(synth_entry(&no_sv).unk_id, 0);
+    }
+
+    #[test]
+    fn synth_entry_rewrites_sv_animations_under_the_output_variant() {
+        let sv_entries = HashMap::from([(
+            key(1),
+            SvEntryInfo {
+                animations: vec![
+                    AnimationInfo {
+                        form_number: 1,
+                        path: "pokemon/data/pm0025/pm0025_00_00/pm0025_01_00_base.tracn".into(),
+                    },
+                    AnimationInfo {
+                        form_number: 3,
+                        path: "pokemon\\data\\pm0025\\pm0025_00_00\\pm0025_01_00_extra.tracn"
+                            .into(),
+                    },
+                ],
+                ..SvEntryInfo::default()
+            },
+        )]);
+        let m = PatchMon::new(key(1), "pm0025".into(), "pm0025_01_00".into(), &sv_entries);
+        assert_eq!(
+            synth_entry(&m).animations,
+            [
+                AnimationInfo {
+                    form_number: 1,
+                    path: "pm0025/pm0025_01_00/pm0025_01_00_base.tracn".into(),
+                },
+                AnimationInfo {
+                    form_number: 3,
+                    path: "pm0025/pm0025_01_00/pm0025_01_00_extra.tracn".into(),
+                },
+            ]
+        );
+
+        // No SV animations: one synthesized .tracn for the key's form
+        let m = PatchMon::new(key(2), "pm0025".into(), "pm0025_02_00".into(), &sv_entries);
+        assert_eq!(
+            synth_entry(&m).animations,
+            [AnimationInfo {
+                form_number: 2,
+                path: "pm0025/pm0025_02_00/pm0025_02_00.tracn".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn mirror_gender_mons_only_adds_unskipped_same_model_genders() {
+        let k = |species, form, gender| SpeciesKey {
+            species,
+            form,
+            gender,
+        };
+        let model =
+            |pm_variant: &str| format!("{}/{pm_variant}/{pm_variant}.trmdl", &pm_variant[..6]);
+        let sv_model_paths = HashMap::from([
+            (k(25, 0, 0), model("pm0025_00_00")),
+            // same model as the selected gender
+            (k(25, 0, 1), model("pm0025_00_00")),
+            // other form
+            (k(25, 1, 1), model("pm0025_00_00")),
+            // different model
+            (k(26, 0, 1), model("pm0026_00_01")),
+            // ZA already ships it
+            (k(27, 0, 1), model("pm0027_00_00")),
+            // excluded by the species filter
+            (k(28, 0, 1), model("pm0028_00_00")),
+        ]);
+        let mons = [25, 26, 27, 28].map(|species| {
+            let pm_variant = format!("pm{species:04}_00_00");
+            PatchMon::new(
+                k(species, 0, 0),
+                pm_variant[..6].to_string(),
+                pm_variant,
+                &HashMap::new(),
+            )
+        });
+        let za_keys = HashSet::from([k(27, 0, 1)]);
+        let filter = KeyFilter::parse(&[], &["28:0:1".to_string()]).unwrap();
+        let (progress, _rx) = ProgressSink::new();
+
+        let mirrored = mirror_gender_mons(
+            &mons,
+            &sv_model_paths,
+            &HashMap::new(),
+            |k| za_keys.contains(k) || !filter.matches(*k),
+            &progress,
+        );
+        let keys = mirrored.iter().map(|m| m.key).collect::<Vec<_>>();
+        assert_eq!(keys, [k(25, 0, 1)]);
+        assert_eq!(mirrored[0].pm_variant, "pm0025_00_00");
+    }
+
+    #[test]
+    fn validate_pokecfg_refs_flags_only_leftover_donor_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let data_root = dir.path().join("ik_pokemon").join("data");
+        let write_cfg = |pm_variant: &str, fields: Vec<(usize, Field)>| {
+            let base = data_root.join(&pm_variant[..6]).join(pm_variant);
+            fs::create_dir_all(&base).unwrap();
+            fs::write(
+                base.join(format!("{pm_variant}.trpokecfg")),
+                testbuf::table(fields, None),
+            )
+            .unwrap();
+        };
+        write_cfg(
+            "pm0025_00_00",
+            vec![
+                (0, Field::Str("pm0025_00_00.trmdl")),
+                (1, Field::Str("common_eye.trmmt")),
+                (2, Field::Str("pm0025_00_00.trskl")),
+                // unrelated field holding another pm_variant's name
+                (5, Field::Str("pm0133_00_00")),
+            ],
+        );
+        write_cfg(
+            "pm0026_00_00",
+            vec![
+                (0, Field::Str("pm0026_00_00.trmdl")),
+                (1, Field::Str("pm0026_00_00.trmmt")),
+                (2, Field::Str("pm0133_00_00.trskl")),
+            ],
+        );
+        let mons = ["pm0025_00_00", "pm0026_00_00"].map(|pm_variant| {
+            PatchMon::new(
+                key(0),
+                pm_variant[..6].to_string(),
+                pm_variant.to_string(),
+                &HashMap::new(),
+            )
+        });
+        let (progress, _rx) = ProgressSink::new();
+
+        assert_eq!(validate_pokecfg_refs(dir.path(), &mons, &progress), 1);
+        let warnings = progress.warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].msg.contains("pm0026_00_00.trpokecfg"));
+        assert!(warnings[0].msg.contains("pm0133_00_00"));
+    }
+}
@@ -1,34 +1,53 @@
-use crate::{backend::flatc, progress::ProgressSink};
+use crate::{
+    backend::flatc::{self, FlatcOptions},
+    paths::check_relative_path,
+    progress::ProgressSink,
+};
 use serde_json::Value;
 use std::{
-    collections::HashSet,
+    collections::{BTreeSet, HashSet},
     fs,
     path::{Path, PathBuf},
 };
 
+pub(super) const PERSONAL_SCHEMA: &str = "PersonalTable.fbs";
+
+/// `rel` is `AppConfig::personal_array_path`, resolved the same way under the dump and output
+/// Paths that would leave `root` are an error rather than something to patch.
+pub(super) fn personal_array_at(root: &Path, rel: &str) -> anyhow::Result<PathBuf> {
+    check_relative_path(rel).map_err(|e| anyhow::anyhow!("personal array path: {e}"))?;
+    Ok(rel
+        .split(['/', '\\'])
+        .filter(|s| !s.is_empty())
+        .fold(root.to_path_buf(), |p, s| p.join(s)))
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn patch_personal_array_present(
     flatc_exe: &Path,
     za_dump: &Path,
     out_root: &Path,
+    personal_rel: &str,
     pknx_personal_dir: &Path,
     enable_keys: &HashSet<(u16, u16)>,
+    flatc_opts: FlatcOptions,
     progress: &ProgressSink,
 ) -> anyhow::Result<()> {
     progress.phase_start("Patch personal array");
 
-    let personal_in = za_dump
-        .join("avalon")
-        .join("data")
-        .join("personal_array.bin");
+    let personal_in = personal_array_at(za_dump, personal_rel)?;
     if !personal_in.is_file() {
-        progress.warn("[personal] personal_array.bin not found; skipping");
-        progress.phase_end("Patch personal array");
+        progress.warn(format!("[personal] {:?} not found; skipping", personal_in));
+        progress.phase_skipped(
+            "Patch personal array",
+            format!("{} not found", personal_in.display()),
+        );
         return Ok(());
     }
-    let schema = pknx_personal_dir.join("PersonalTable.fbs");
+    let schema = pknx_personal_dir.join(PERSONAL_SCHEMA);
     if !schema.is_file() {
         progress.warn(format!("[personal] missing schema: {:?}", schema));
-        progress.phase_end("Patch personal array");
+        progress.phase_skipped("Patch personal array", "PersonalTable.fbs not found");
         return Ok(());
     }
 
@@ -39,6 +58,7 @@ pub fn patch_personal_array_present(
         &[pknx_personal_dir.to_path_buf()],
         &personal_in,
         td.path(),
+        flatc_opts.profile,
     )?;
     let mut doc: Value = serde_json::from_slice(&fs::read(&json_path)?)?;
 
@@ -83,10 +103,7 @@ pub fn patch_personal_array_present(
         ));
     }
 
-    let out_personal = out_root
-        .join("avalon")
-        .join("data")
-        .join("personal_array.bin");
+    let out_personal = personal_array_at(out_root, personal_rel)?;
     if let Some(parent) = out_personal.parent() {
         fs::create_dir_all(parent)?;
     }
@@ -103,12 +120,16 @@ pub fn patch_personal_array_present(
 
     let out_json = td.path().join("out.json");
     fs::write(&out_json, serde_json::to_vec_pretty(&doc)?)?;
-    flatc::flatc_build_bin(
+    flatc::keep_intermediates(td.path(), flatc_opts.keep_json_dir, "personal_array")?;
+    let expected = present_keys(&doc);
+    flatc::flatc_build_bin_checked(
         flatc_exe,
         &schema,
         &[pknx_personal_dir.to_path_buf()],
         &out_json,
         &out_personal,
+        flatc_opts,
+        |back| check_present_keys(back, &expected),
     )?;
     progress.info(format!(
         "[personal] enabled {} entries (requested {})",
@@ -118,3 +139,82 @@ pub fn patch_personal_array_present(
     progress.phase_end("Patch personal array");
     Ok(())
 }
+
+/// Entry count and the `(SpeciesInternal, Form)` keys marked present in a dumped personal table
+fn present_keys(doc: &Value) -> (usize, BTreeSet<(i64, i64)>) {
+    let Some(table) = doc.get("Table").and_then(|v| v.as_array()) else {
+        return (0, BTreeSet::new());
+    };
+    let present = table
+        .iter()
+        .filter(|e| e.get("IsPresentInGame").and_then(|v| v.as_bool()) == Some(true))
+        .filter_map(|e| {
+            let info = e.get("Info")?;
+            Some((
+                info.get("SpeciesInternal")?.as_i64()?,
+                info.get("Form").and_then(|v| v.as_i64()).unwrap_or(0),
+            ))
+        })
+        .collect();
+    (table.len(), present)
+}
+
+fn check_present_keys(
+    back: &Value,
+    expected: &(usize, BTreeSet<(i64, i64)>),
+) -> anyhow::Result<()> {
+    let got = present_keys(back);
+    if got != *expected {
+        anyhow::bail!(
+            "wrote {} entries ({} present), read back {} ({} present)",
+            expected.0,
+            expected.1.len(),
+            got.0,
+            got.1.len()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entry(species: i64, form: i64, present: bool) -> Value {
+        json!({"Info": {"SpeciesInternal": species, "Form": form}, "IsPresentInGame": present})
+    }
+
+    #[test]
+    fn personal_array_at_stays_under_root() {
+        let root = Path::new("dump");
+        assert_eq!(
+            personal_array_at(root, "avalon\\data//personal_array.bin").unwrap(),
+            root.join("avalon").join("data").join("personal_array.bin")
+        );
+        assert!(personal_array_at(root, "../personal_array.bin").is_err());
+        assert!(personal_array_at(root, "/etc/personal_array.bin").is_err());
+    }
+
+    #[test]
+    fn present_keys_reads_present_entries() {
+        let doc = json!({"Table": [entry(25, 0, true), entry(25, 1, false), entry(26, 0, true)]});
+        let (count, present) = present_keys(&doc);
+        assert_eq!(count, 3);
+        assert_eq!(present, BTreeSet::from([(25, 0), (26, 0)]));
+        assert_eq!(present_keys(&json!({})), (0, BTreeSet::new()));
+    }
+
+    #[test]
+    fn check_present_keys_cases() {
+        let doc = json!({"Table": [entry(25, 0, true), entry(26, 0, false)]});
+        let expected = present_keys(&doc);
+        assert!(check_present_keys(&doc, &expected).is_ok());
+
+        let fewer = json!({"Table": [entry(25, 0, true)]});
+        assert!(check_present_keys(&fewer, &expected).is_err());
+
+        let flipped = json!({"Table": [entry(25, 0, true), entry(26, 0, true)]});
+        assert!(check_present_keys(&flipped, &expected).is_err());
+    }
+}
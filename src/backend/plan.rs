@@ -0,0 +1,210 @@
+use crate::config::AppConfig;
+
+/// Skip reasons that point at a missing setting rather than a choice; `run` warns about these
+pub(crate) const FLATC_NOT_SET: &str = "flatc not set";
+pub(crate) const PKNX_NOT_SET: &str = "pkNX personal dir not set";
+
+/// The phases of `backend::run`, in the order it goes through them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    ValidatePaths,
+    Selection,
+    CopyPm,
+    PatchCatalog,
+    ParamArrays,
+    PersonalArray,
+    NamesReport,
+    Textures,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 8] = [
+        Phase::ValidatePaths,
+        Phase::Selection,
+        Phase::CopyPm,
+        Phase::PatchCatalog,
+        Phase::ParamArrays,
+        Phase::PersonalArray,
+        Phase::NamesReport,
+        Phase::Textures,
+    ];
+
+    /// The name progress events and the GUI use for the phase
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::ValidatePaths => "Validate paths",
+            Phase::Selection => "Catalog & selection",
+            Phase::CopyPm => "Copy pm packages",
+            Phase::PatchCatalog => "Patch ZA catalog",
+            Phase::ParamArrays => "Patch param arrays",
+            Phase::PersonalArray => "Patch personal array",
+            Phase::NamesReport => "Names report",
+            Phase::Textures => "Texture convert",
+        }
+    }
+}
+
+/// Why `run` skips `phase` under `cfg`, decided from the config alone; None when it runs
+/// Phases can still skip themselves later on inputs missing from disk.
+pub fn skip_reason(cfg: &AppConfig, phase: Phase) -> Option<&'static str> {
+    // param/personal arrays are left as written by the last full run under --catalog-only
+    let flatc_skip = if cfg.catalog_only {
+        Some("catalog-only")
+    } else if cfg.flatc.is_none() {
+        Some(FLATC_NOT_SET)
+    } else {
+        None
+    };
+    match phase {
+        Phase::ValidatePaths | Phase::Selection => None,
+        Phase::CopyPm => cfg.catalog_only.then_some("catalog-only"),
+        Phase::PatchCatalog => cfg.skip_catalog.then_some("disabled"),
+        Phase::ParamArrays => flatc_skip.or(cfg.skip_param.then_some("disabled")),
+        Phase::PersonalArray => flatc_skip
+            .or(cfg.skip_personal.then_some("disabled"))
+            .or(cfg.pknx_personal_dir.is_none().then_some(PKNX_NOT_SET)),
+        Phase::NamesReport => (!cfg.generate_reports).then_some("reports disabled"),
+        Phase::Textures => {
+            if cfg.skip_textures {
+                Some("disabled")
+            } else if cfg.catalog_only {
+                Some("catalog-only")
+            } else if !cfg.texture_convert {
+                Some("texture conversion off")
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// One step of [`phase_plan`]; `skip` carries the reason `run` would report for skipping it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlannedPhase {
+    pub name: &'static str,
+    pub skip: Option<&'static str>,
+}
+
+/// The phases `backend::run` goes through for `cfg`, in order, without touching the disk
+pub fn phase_plan(cfg: &AppConfig) -> Vec<PlannedPhase> {
+    Phase::ALL
+        .iter()
+        .map(|&p| PlannedPhase {
+            name: p.name(),
+            skip: skip_reason(cfg, p),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Every phase runs: the tools it needs are set and nothing is turned off
+    fn full_cfg() -> AppConfig {
+        AppConfig {
+            flatc: Some(PathBuf::from("flatc")),
+            pknx_personal_dir: Some(PathBuf::from("pknx")),
+            texture_convert: true,
+            generate_reports: true,
+            ..AppConfig::default()
+        }
+    }
+
+    fn skips(cfg: &AppConfig) -> Vec<(&'static str, &'static str)> {
+        phase_plan(cfg)
+            .into_iter()
+            .filter_map(|p| Some((p.name, p.skip?)))
+            .collect()
+    }
+
+    #[test]
+    fn plan_lists_every_phase_in_run_order() {
+        let names: Vec<_> = phase_plan(&full_cfg()).iter().map(|p| p.name).collect();
+        assert_eq!(
+            names,
+            [
+                "Validate paths",
+                "Catalog & selection",
+                "Copy pm packages",
+                "Patch ZA catalog",
+                "Patch param arrays",
+                "Patch personal array",
+                "Names report",
+                "Texture convert",
+            ]
+        );
+        assert_eq!(skips(&full_cfg()), []);
+    }
+
+    #[test]
+    fn plan_for_the_default_config() {
+        assert_eq!(
+            skips(&AppConfig::default()),
+            [
+                ("Patch param arrays", FLATC_NOT_SET),
+                ("Patch personal array", FLATC_NOT_SET),
+                ("Texture convert", "texture conversion off"),
+            ]
+        );
+    }
+
+    /// A config change on top of [`full_cfg`] and the skips it should cause
+    type Toggle = (fn(&mut AppConfig), &'static [(&'static str, &'static str)]);
+
+    #[test]
+    fn plan_for_each_toggled_flag() {
+        let cases: [Toggle; 9] = [
+            (
+                |c| c.catalog_only = true,
+                &[
+                    ("Copy pm packages", "catalog-only"),
+                    ("Patch param arrays", "catalog-only"),
+                    ("Patch personal array", "catalog-only"),
+                    ("Texture convert", "catalog-only"),
+                ],
+            ),
+            (
+                |c| c.flatc = None,
+                &[
+                    ("Patch param arrays", FLATC_NOT_SET),
+                    ("Patch personal array", FLATC_NOT_SET),
+                ],
+            ),
+            (
+                |c| c.skip_catalog = true,
+                &[("Patch ZA catalog", "disabled")],
+            ),
+            (
+                |c| c.skip_param = true,
+                &[("Patch param arrays", "disabled")],
+            ),
+            (
+                |c| c.skip_personal = true,
+                &[("Patch personal array", "disabled")],
+            ),
+            (
+                |c| c.pknx_personal_dir = None,
+                &[("Patch personal array", PKNX_NOT_SET)],
+            ),
+            (
+                |c| c.generate_reports = false,
+                &[("Names report", "reports disabled")],
+            ),
+            (
+                |c| c.skip_textures = true,
+                &[("Texture convert", "disabled")],
+            ),
+            (
+                |c| c.texture_convert = false,
+                &[("Texture convert", "texture conversion off")],
+            ),
+        ];
+        for (i, (toggle, want)) in cases.iter().enumerate() {
+            let mut cfg = full_cfg();
+            toggle(&mut cfg);
+            assert_eq!(skips(&cfg), *want, "case {i}");
+        }
+    }
+}
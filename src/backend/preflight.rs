@@ -0,0 +1,128 @@
+use crate::{
+    backend::{names, param_arrays, personal},
+    config::AppConfig,
+    paths::find_under,
+    progress::{ProgressSink, WarningKind},
+};
+use serde::Serialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PreflightItem {
+    pub kind: &'static str,
+    pub path: PathBuf,
+    pub present: bool,
+}
+
+/// Lists every external file the run is about to read so a missing schema or param bin shows up
+/// before the copy phase; writes `_report/preflight.json` when reports are enabled
+/// Only files the current config would actually touch are listed. Never fails the run.
+pub(super) fn run_preflight(
+    cfg: &AppConfig,
+    poke_root: &Path,
+    za_dump: &Path,
+    out_root: &Path,
+    progress: &ProgressSink,
+) {
+    let mut items = Vec::new();
+    let mut push = |kind: &'static str, path: PathBuf| {
+        let present = path.is_file();
+        items.push(PreflightItem {
+            kind,
+            path,
+            present,
+        });
+    };
+
+    for (kind, root, rel) in [
+        (
+            "SV catalog",
+            poke_root,
+            "catalog/catalog/poke_resource_table.trpmcatalog",
+        ),
+        (
+            "ZA catalog",
+            za_dump,
+            "ik_pokemon/catalog/catalog/poke_resource_table.trpmcatalog",
+        ),
+    ] {
+        let path = find_under(
+            root,
+            rel,
+            "poke_resource_table.trpmcatalog",
+            cfg.follow_symlinks,
+        )
+        .unwrap_or_else(|_| root.join(rel));
+        push(kind, path);
+    }
+
+    if !cfg.catalog_only {
+        if let Some(flatc) = cfg.flatc.as_ref() {
+            push("flatc", flatc.clone());
+            if !cfg.skip_param {
+                let p = param_arrays::param_inputs(za_dump);
+                push("model param bin", p.model_bin);
+                push("model param schema", p.model_bfbs);
+                push("movement param bin", p.move_bin);
+                push("movement param schema", p.move_bfbs);
+            }
+            if let (false, Some(pknx_dir)) = (cfg.skip_personal, cfg.pknx_personal_dir.as_ref()) {
+                match personal::personal_array_at(za_dump, &cfg.personal_array_path) {
+                    Ok(path) => push("personal array", path),
+                    Err(e) => progress.error(format!("[preflight] {e}")),
+                }
+                push("personal schema", pknx_dir.join(personal::PERSONAL_SCHEMA));
+            }
+        }
+        if cfg.texture_convert && !cfg.skip_textures {
+            if let Some(ultimate) = cfg.ultimate_tex_cli.as_ref() {
+                push("ultimate_tex_cli", ultimate.clone());
+            }
+        }
+    }
+
+    let mut langs = vec![cfg.language.clone()];
+    if cfg.generate_reports {
+        for l in &cfg.report_languages {
+            if !langs.contains(l) {
+                langs.push(l.clone());
+            }
+        }
+    }
+    for lang in &langs {
+        let (tbl, dat) = names::find_monsname_tables(za_dump, lang).unwrap_or_else(|p| p);
+        push("monsname table", tbl);
+        push("monsname data", dat);
+    }
+
+    let missing = items.iter().filter(|i| !i.present).count();
+    for i in &items {
+        let mark = if i.present { "ok" } else { "MISSING" };
+        progress.info(format!("[preflight] {mark:<7} {}: {:?}", i.kind, i.path));
+    }
+    if missing > 0 {
+        progress.warn_kind(
+            WarningKind::MissingAsset,
+            format!("[preflight] {missing} input file(s) missing"),
+        );
+    }
+
+    if cfg.generate_reports {
+        let path = cfg.report_dir(out_root).join("preflight.json");
+        match write_report(&path, &items) {
+            Ok(()) => progress.info(format!("[report] wrote {:?}", path)),
+            Err(e) => progress.warn(format!("[report] failed to write {:?}: {e}", path)),
+        }
+    }
+}
+
+fn write_report(path: &Path, items: &[PreflightItem]) -> anyhow::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_vec_pretty(items)?)?;
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use crate::progress::ProgressSink;
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Wall time spent in external tools (flatc, ultimate_tex_cli), collected with `--profile`
+#[derive(Debug, Default)]
+pub struct ToolProfile {
+    by_tool: Mutex<BTreeMap<&'static str, ToolStats>>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct ToolStats {
+    pub calls: u64,
+    pub total_ms: f64,
+    pub avg_ms: f64,
+}
+
+impl ToolProfile {
+    pub fn record(&self, tool: &'static str, elapsed: Duration) {
+        let mut by_tool = self.by_tool.lock().unwrap_or_else(|e| e.into_inner());
+        let s = by_tool.entry(tool).or_default();
+        s.calls += 1;
+        s.total_ms += elapsed.as_secs_f64() * 1000.0;
+        s.avg_ms = s.total_ms / s.calls as f64;
+    }
+
+    pub fn stats(&self) -> BTreeMap<&'static str, ToolStats> {
+        self.by_tool
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Logs one line per tool and writes `_report/profile.json`
+    pub fn write_report(
+        &self,
+        report_dir: &Path,
+        progress: &ProgressSink,
+    ) -> anyhow::Result<PathBuf> {
+        let stats = self.stats();
+        for (tool, s) in &stats {
+            progress.info(format!(
+                "[profile] {tool}: calls={} total={:.1}s avg={:.0}ms",
+                s.calls,
+                s.total_ms / 1000.0,
+                s.avg_ms
+            ));
+            progress.metric(format!("profile.{tool}.calls"), s.calls as i64);
+            progress.metric(format!("profile.{tool}.total_ms"), s.total_ms as i64);
+        }
+        fs::create_dir_all(report_dir)?;
+        let path = report_dir.join("profile.json");
+        fs::write(&path, serde_json::to_vec_pretty(&stats)?)?;
+        progress.info(format!("[report] wrote {:?}", path));
+        Ok(path)
+    }
+}
+
+/// `cmd.output()`, timed under `tool` when a profile is being collected
+pub fn run_tool(
+    cmd: &mut Command,
+    tool: &'static str,
+    profile: Option<&ToolProfile>,
+) -> io::Result<Output> {
+    let start = Instant::now();
+    let out = cmd.output();
+    if let Some(p) = profile {
+        p.record(tool, start.elapsed());
+    }
+    out
+}
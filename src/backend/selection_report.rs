@@ -0,0 +1,210 @@
+use crate::{backend::names::ConvertedMon, progress::ProgressSink, template::Key};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// One selected mon as persisted in `_report/selection.json`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SelectionRecord {
+    pub species: u16,
+    pub form: u16,
+    pub gender: u8,
+    pub pm_variant: String,
+    /// Records written before this was the full donor key load as None
+    #[serde(default)]
+    pub donor: Option<Key>,
+    pub donor_pm_variant: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct DonorChange {
+    species: u16,
+    form: u16,
+    gender: u8,
+    pm_variant: String,
+    before: (Option<Key>, Option<String>),
+    after: (Option<Key>, Option<String>),
+}
+
+#[derive(Debug, Default, Serialize)]
+struct SelectionDiff {
+    added: Vec<SelectionRecord>,
+    removed: Vec<SelectionRecord>,
+    donor_changed: Vec<DonorChange>,
+}
+
+/// Writes `_report/selection.json` and, when the previous run left one, `_report/selection_diff.json`
+pub(super) fn write_selection_report(
+    report_dir: &Path,
+    mons: &[ConvertedMon],
+    progress: &ProgressSink,
+) -> anyhow::Result<()> {
+    fs::create_dir_all(report_dir)?;
+    let path = report_dir.join("selection.json");
+
+    let current: Vec<SelectionRecord> = mons
+        .iter()
+        .map(|m| SelectionRecord {
+            species: m.species,
+            form: m.form,
+            gender: m.gender,
+            pm_variant: m.pm_variant.clone(),
+            donor: m.donor,
+            donor_pm_variant: m.donor_pm_variant.clone(),
+        })
+        .collect();
+    let previous: Option<Vec<SelectionRecord>> = fs::read(&path)
+        .ok()
+        .and_then(|b| serde_json::from_slice(&b).ok());
+
+    fs::write(&path, serde_json::to_vec_pretty(&current)?)?;
+    progress.info(format!(
+        "[report] wrote {:?} (mons={})",
+        path,
+        current.len()
+    ));
+
+    let Some(previous) = previous else {
+        return Ok(());
+    };
+    let diff = diff_selection(&previous, &current);
+    for m in &diff.added {
+        progress.info(format!(
+            "[selection] added {:04}_{:02}_{} ({})",
+            m.species, m.form, m.gender, m.pm_variant
+        ));
+    }
+    for m in &diff.removed {
+        progress.info(format!(
+            "[selection] removed {:04}_{:02}_{} ({})",
+            m.species, m.form, m.gender, m.pm_variant
+        ));
+    }
+    for c in &diff.donor_changed {
+        progress.info(format!(
+            "[selection] donor changed {:04}_{:02}_{}: {:?} -> {:?}",
+            c.species, c.form, c.gender, c.before, c.after
+        ));
+    }
+    progress.info(format!(
+        "[selection] vs last run: added={} removed={} donor_changed={}",
+        diff.added.len(),
+        diff.removed.len(),
+        diff.donor_changed.len()
+    ));
+
+    let diff_path = report_dir.join("selection_diff.json");
+    fs::write(&diff_path, serde_json::to_vec_pretty(&diff)?)?;
+    progress.info(format!("[report] wrote {:?}", diff_path));
+    Ok(())
+}
+
+fn diff_selection(previous: &[SelectionRecord], current: &[SelectionRecord]) -> SelectionDiff {
+    let key = |m: &SelectionRecord| (m.species, m.form, m.gender);
+    let before: BTreeMap<_, _> = previous.iter().map(|m| (key(m), m)).collect();
+    let after: BTreeMap<_, _> = current.iter().map(|m| (key(m), m)).collect();
+
+    let mut diff = SelectionDiff::default();
+    for (k, m) in &after {
+        match before.get(k) {
+            None => diff.added.push((*m).clone()),
+            Some(old) if (old.donor, &old.donor_pm_variant) != (m.donor, &m.donor_pm_variant) => {
+                diff.donor_changed.push(DonorChange {
+                    species: m.species,
+                    form: m.form,
+                    gender: m.gender,
+                    pm_variant: m.pm_variant.clone(),
+                    before: (old.donor, old.donor_pm_variant.clone()),
+                    after: (m.donor, m.donor_pm_variant.clone()),
+                });
+            }
+            Some(_) => {}
+        }
+    }
+    for (k, m) in &before {
+        if !after.contains_key(k) {
+            diff.removed.push((*m).clone());
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn donor(species: u16, form: u16) -> Key {
+        Key {
+            species,
+            form,
+            gender: 0,
+        }
+    }
+
+    fn rec(species: u16, d: Option<(Key, &str)>) -> SelectionRecord {
+        SelectionRecord {
+            species,
+            form: 0,
+            gender: 0,
+            pm_variant: format!("pm{species:04}_00_00"),
+            donor: d.map(|d| d.0),
+            donor_pm_variant: d.map(|d| d.1.to_string()),
+        }
+    }
+
+    #[test]
+    fn diff_selection_added_removed_and_donor_changed() {
+        let previous = [
+            rec(25, Some((donor(133, 0), "pm0133_00_00"))),
+            rec(26, None),
+            rec(27, Some((donor(133, 0), "pm0133_00_00"))),
+        ];
+        let current = [
+            rec(25, Some((donor(133, 0), "pm0133_00_00"))),
+            rec(27, Some((donor(52, 0), "pm0052_00_00"))),
+            rec(28, None),
+        ];
+        let diff = diff_selection(&previous, &current);
+        assert_eq!(diff.added, [rec(28, None)]);
+        assert_eq!(diff.removed, [rec(26, None)]);
+        assert_eq!(diff.donor_changed.len(), 1);
+        let c = &diff.donor_changed[0];
+        assert_eq!(c.species, 27);
+        assert_eq!(
+            c.before,
+            (Some(donor(133, 0)), Some("pm0133_00_00".to_string()))
+        );
+        assert_eq!(
+            c.after,
+            (Some(donor(52, 0)), Some("pm0052_00_00".to_string()))
+        );
+    }
+
+    #[test]
+    fn diff_selection_sees_a_donor_form_change() {
+        let previous = [rec(25, Some((donor(133, 0), "pm0133_00_00")))];
+        let current = [rec(25, Some((donor(133, 1), "pm0133_00_00")))];
+        let diff = diff_selection(&previous, &current);
+        assert_eq!(diff.donor_changed.len(), 1);
+        assert_eq!(diff.donor_changed[0].after.0, Some(donor(133, 1)));
+    }
+
+    #[test]
+    fn records_without_a_donor_key_still_load() {
+        let old = r#"[{"species":25,"form":0,"gender":0,"pm_variant":"pm0025_00_00","donor_species":133,"donor_pm_variant":"pm0133_00_00"}]"#;
+        let recs: Vec<SelectionRecord> = serde_json::from_str(old).unwrap();
+        assert_eq!(recs[0].donor, None);
+        assert_eq!(recs[0].donor_pm_variant.as_deref(), Some("pm0133_00_00"));
+    }
+
+    #[test]
+    fn diff_selection_unchanged_is_empty() {
+        let sel = [
+            rec(25, None),
+            rec(26, Some((donor(133, 0), "pm0133_00_00"))),
+        ];
+        let diff = diff_selection(&sel, &sel);
+        assert!(diff.added.is_empty());
+        assert!(diff.removed.is_empty());
+        assert!(diff.donor_changed.is_empty());
+    }
+}
@@ -41,7 +41,7 @@ pub fn read_bmp_rgba(path: &Path) -> anyhow::Result<(i32, i32, Vec<u8>)> {
         (0, 0, 0, 0)
     };
 
-    let row_bytes = (((bpp as usize * width as usize) + 31) / 32) * 4;
+    let row_bytes = (bpp as usize * width as usize).div_ceil(32) * 4;
     if pixel_off + row_bytes * (abs_h as usize) > b.len() {
         anyhow::bail!("bmp pixel data truncated");
     }
@@ -69,22 +69,22 @@ pub fn read_bmp_rgba(path: &Path) -> anyhow::Result<(i32, i32, Vec<u8>)> {
                         )
                     } else {
                         // default BGRA
-                        let bb = b[si + 0];
+                        let bb = b[si];
                         let gg = b[si + 1];
                         let rr = b[si + 2];
                         let aa = b[si + 3];
                         (rr, gg, bb, aa)
                     };
-                    out[di + 0] = r;
+                    out[di] = r;
                     out[di + 1] = g;
                     out[di + 2] = b2;
                     out[di + 3] = a;
                 }
                 24 => {
                     let si = src_row + (x as usize) * 3;
-                    out[di + 0] = b[si + 2];
+                    out[di] = b[si + 2];
                     out[di + 1] = b[si + 1];
-                    out[di + 2] = b[si + 0];
+                    out[di + 2] = b[si];
                     out[di + 3] = 255;
                 }
                 _ => anyhow::bail!("unsupported bpp={bpp}"),
@@ -112,8 +112,8 @@ pub fn write_bmp_rgba(path: &Path, width: i32, height: i32, rgba: &[u8]) -> anyh
     b.extend_from_slice(&0u32.to_le_bytes());
     b.extend_from_slice(&54u32.to_le_bytes());
     b.extend_from_slice(&40u32.to_le_bytes());
-    b.extend_from_slice(&(width as i32).to_le_bytes());
-    b.extend_from_slice(&(height as i32).to_le_bytes());
+    b.extend_from_slice(&width.to_le_bytes());
+    b.extend_from_slice(&height.to_le_bytes());
     b.extend_from_slice(&1u16.to_le_bytes());
     b.extend_from_slice(&32u16.to_le_bytes());
     b.extend_from_slice(&0u32.to_le_bytes());
@@ -127,7 +127,7 @@ pub fn write_bmp_rgba(path: &Path, width: i32, height: i32, rgba: &[u8]) -> anyh
     for y in (0..h).rev() {
         for x in 0..w {
             let si = (y * w + x) * 4;
-            let r = rgba[si + 0];
+            let r = rgba[si];
             let g = rgba[si + 1];
             let bb = rgba[si + 2];
             let a = rgba[si + 3];
@@ -193,7 +193,7 @@ fn mask_bits(mask: u32) -> (u32, u32) {
         return (0, 0);
     }
     let shift = tz(mask);
-    let bits = ((mask >> shift).count_ones()).max(0);
+    let bits = (mask >> shift).count_ones();
     (shift, bits)
 }
 
@@ -1,5 +1,9 @@
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fs, path::Path};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+};
 
 #[derive(Debug, Clone)]
 pub struct BntxMeta {
@@ -10,6 +14,9 @@ pub struct BntxMeta {
     pub base_offset: i64,
     pub format_type: u8,
     pub format_var: u8,
+    /// 0 = block-linear (swizzled), 1 = pitch-linear
+    pub tile_mode: u16,
+    pub block_height_log2: i32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,6 +30,9 @@ pub struct BntxIndexEntry {
     pub base_offset: i64,
     pub ultimate_format: Option<String>,
     pub no_mipmaps: bool,
+    /// [`file_fingerprint`] of the donor file at index time; `None` in caches built before it existed
+    #[serde(default)]
+    pub fingerprint: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +44,66 @@ pub struct BntxIndexDoc {
     pub entries: Vec<BntxIndexEntry>,
     pub by_key: HashMap<String, Vec<usize>>,
     pub by_name: HashMap<String, Vec<usize>>,
+    /// Format map the entries were resolved with; a different map invalidates the cache
+    #[serde(default)]
+    pub format_map: FormatMap,
+    /// Whether symlinked donors were followed; a cache built the other way is rebuilt
+    #[serde(default)]
+    pub follow_links: bool,
+}
+
+/// User-supplied BNTX format codes merged over the built-in `ultimate_format` table
+/// Keys are `"0x21"` (any variant) or `"0x21/6"` (one variant); values are ultimate_tex names.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FormatMap(BTreeMap<String, String>);
+
+impl FormatMap {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let raw: BTreeMap<String, String> = serde_json::from_slice(&fs::read(path)?)
+            .map_err(|e| anyhow::anyhow!("format map {path:?}: {e}"))?;
+        let mut out = BTreeMap::new();
+        for (key, fmt) in raw {
+            if !KNOWN_ULTIMATE_FORMATS.contains(&fmt.as_str()) {
+                anyhow::bail!(
+                    "format map {path:?}: {key:?} maps to unknown format {fmt:?} (known: {})",
+                    KNOWN_ULTIMATE_FORMATS.join(", ")
+                );
+            }
+            let (ty, var) = match key.split_once('/') {
+                Some((ty, var)) => (ty, Some(var)),
+                None => (key.as_str(), None),
+            };
+            let ty = parse_code(ty);
+            let var = var.map(parse_code);
+            let canon = match (ty, var) {
+                (Some(ty), None) => format!("0x{ty:02X}"),
+                (Some(ty), Some(Some(var))) => format!("0x{ty:02X}/{var}"),
+                _ => anyhow::bail!(
+                    "format map {path:?}: bad key {key:?} (expected \"0x21\" or \"0x21/6\")"
+                ),
+            };
+            out.insert(canon, fmt);
+        }
+        Ok(Self(out))
+    }
+
+    /// Variant-specific entries win over type-wide ones, which win over the built-in table
+    pub fn ultimate_format(&self, format_type: u8, format_var: u8) -> Option<String> {
+        self.0
+            .get(&format!("0x{format_type:02X}/{format_var}"))
+            .or_else(|| self.0.get(&format!("0x{format_type:02X}")))
+            .cloned()
+            .or_else(|| ultimate_format(format_type, format_var).map(|s| s.to_string()))
+    }
+}
+
+fn parse_code(s: &str) -> Option<u8> {
+    let s = s.trim();
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u8::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
 }
 
 pub fn read_bntx_metas(path: &Path) -> anyhow::Result<Vec<BntxMeta>> {
@@ -82,6 +152,7 @@ pub fn read_bntx_metas(path: &Path) -> anyhow::Result<Vec<BntxMeta>> {
         cur += 8;
         cur += 1;
         cur += 1;
+        let tile_mode = read_u16le(&b, cur);
         cur += 2;
         cur += 2;
         let mip_count = read_u16le(&b, cur);
@@ -97,7 +168,7 @@ pub fn read_bntx_metas(path: &Path) -> anyhow::Result<Vec<BntxMeta>> {
         cur += 4;
         cur += 4;
         cur += 4;
-        let _block_h_log2 = read_i32le(&b, cur);
+        let block_h_log2 = read_i32le(&b, cur);
         cur += 4;
         cur += 4 * 6;
         let data_len = read_i32le(&b, cur);
@@ -123,7 +194,7 @@ pub fn read_bntx_metas(path: &Path) -> anyhow::Result<Vec<BntxMeta>> {
         let base_off = read_i64le(&b, ptrs_addr as usize);
 
         let fmt_type = ((fmt_u32 >> 8) & 0xFF) as u8;
-        let fmt_var = ((fmt_u32 >> 0) & 0xFF) as u8;
+        let fmt_var = (fmt_u32 & 0xFF) as u8;
         out.push(BntxMeta {
             width,
             height,
@@ -132,11 +203,32 @@ pub fn read_bntx_metas(path: &Path) -> anyhow::Result<Vec<BntxMeta>> {
             base_offset: base_off,
             format_type: fmt_type,
             format_var: fmt_var,
+            tile_mode,
+            block_height_log2: block_h_log2,
         });
     }
     Ok(out)
 }
 
+/// Every format name `ultimate_format` can return
+pub const KNOWN_ULTIMATE_FORMATS: &[&str] = &[
+    "BC1RgbaUnorm",
+    "BC1RgbaUnormSrgb",
+    "BC2RgbaUnorm",
+    "BC2RgbaUnormSrgb",
+    "BC3RgbaUnorm",
+    "BC3RgbaUnormSrgb",
+    "BC4RUnorm",
+    "BC4RSnorm",
+    "BC5RgUnorm",
+    "BC5RgSnorm",
+    "BC6hRgbUfloat",
+    "BC7RgbaUnorm",
+    "BC7RgbaUnormSrgb",
+    "Rgba8Unorm",
+    "Rgba8UnormSrgb",
+];
+
 pub fn ultimate_format(format_type: u8, format_var: u8) -> Option<&'static str> {
     match format_type {
         0x1A => Some(if format_var == 6 {
@@ -201,9 +293,51 @@ pub fn extract_tex_data(bntx_path: &Path) -> anyhow::Result<(Vec<u8>, usize, usi
     Ok((b[boff..end].to_vec(), boff, dlen))
 }
 
-pub fn build_index(dump_root: &Path) -> anyhow::Result<BntxIndexDoc> {
+/// 64-bit FNV-1a; stable across runs and platforms, unlike `DefaultHasher`
+pub fn fnv1a64(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325u64, |h, &b| {
+        (h ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+const FINGERPRINT_SPAN: usize = 1024;
+
+/// Cheap change check for a donor file: hashes the length plus its first and last KB
+pub fn bytes_fingerprint(bytes: &[u8]) -> u64 {
+    let head = &bytes[..bytes.len().min(FINGERPRINT_SPAN)];
+    let tail = &bytes[bytes.len().saturating_sub(FINGERPRINT_SPAN)..];
+    fingerprint_parts(head, tail, bytes.len() as u64)
+}
+
+/// [`bytes_fingerprint`] without reading the whole file
+pub fn file_fingerprint(path: &Path) -> std::io::Result<u64> {
+    use std::io::{Read, Seek, SeekFrom};
+    let mut f = fs::File::open(path)?;
+    let len = f.metadata()?.len();
+    let span = (len as usize).min(FINGERPRINT_SPAN);
+    let mut head = vec![0u8; span];
+    f.read_exact(&mut head)?;
+    let mut tail = vec![0u8; span];
+    f.seek(SeekFrom::Start(len - span as u64))?;
+    f.read_exact(&mut tail)?;
+    Ok(fingerprint_parts(&head, &tail, len))
+}
+
+fn fingerprint_parts(head: &[u8], tail: &[u8], len: u64) -> u64 {
+    let mut buf = Vec::with_capacity(head.len() + tail.len() + 8);
+    buf.extend_from_slice(&len.to_le_bytes());
+    buf.extend_from_slice(head);
+    buf.extend_from_slice(tail);
+    fnv1a64(&buf)
+}
+
+pub fn build_index(
+    dump_root: &Path,
+    format_map: &FormatMap,
+    follow_links: bool,
+) -> anyhow::Result<BntxIndexDoc> {
     let mut files = Vec::new();
-    for e in walkdir::WalkDir::new(dump_root).follow_links(false) {
+    for e in crate::paths::walk_dir(dump_root, follow_links) {
         let e = e?;
         if !e.file_type().is_file() {
             continue;
@@ -228,8 +362,9 @@ pub fn build_index(dump_root: &Path) -> anyhow::Result<BntxIndexDoc> {
             skipped += 1;
             continue;
         }
+        let fingerprint = file_fingerprint(f).ok();
         for t in texs {
-            let ult = ultimate_format(t.format_type, t.format_var).map(|s| s.to_string());
+            let ult = format_map.ultimate_format(t.format_type, t.format_var);
             entries.push(BntxIndexEntry {
                 file_path: f.to_string_lossy().to_string(),
                 file_name: f
@@ -244,6 +379,7 @@ pub fn build_index(dump_root: &Path) -> anyhow::Result<BntxIndexDoc> {
                 base_offset: t.base_offset,
                 ultimate_format: ult,
                 no_mipmaps: t.mip_count <= 1,
+                fingerprint,
             });
         }
     }
@@ -275,6 +411,8 @@ pub fn build_index(dump_root: &Path) -> anyhow::Result<BntxIndexDoc> {
         entries,
         by_key,
         by_name,
+        format_map: format_map.clone(),
+        follow_links,
     })
 }
 
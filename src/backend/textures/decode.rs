@@ -0,0 +1,622 @@
+//! Decode-only BC1/BC7 support for previews and post-conversion checks
+//! Encoding still goes through ultimate_tex_cli.
+
+use crate::backend::textures::bntx::BntxMeta;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BcFormat {
+    Bc1,
+    Bc7,
+}
+
+impl BcFormat {
+    pub fn from_meta(meta: &BntxMeta) -> Option<Self> {
+        match meta.format_type {
+            0x1A => Some(Self::Bc1),
+            0x20 => Some(Self::Bc7),
+            _ => None,
+        }
+    }
+
+    fn block_bytes(self) -> usize {
+        match self {
+            Self::Bc1 => 8,
+            Self::Bc7 => 16,
+        }
+    }
+}
+
+/// Decodes mip 0 of a BNTX texture to RGBA8 (`width * height * 4` bytes)
+/// `data` is the texture's data region starting at mip 0 (as returned by `extract_tex_data`).
+pub fn decode_mip0(meta: &BntxMeta, data: &[u8]) -> anyhow::Result<(u32, u32, Vec<u8>)> {
+    let fmt = BcFormat::from_meta(meta)
+        .ok_or_else(|| anyhow::anyhow!("unsupported format type 0x{:02X}", meta.format_type))?;
+    if meta.width <= 0 || meta.height <= 0 || meta.width > 16384 || meta.height > 16384 {
+        anyhow::bail!("implausible dimensions {}x{}", meta.width, meta.height);
+    }
+    let (w, h) = (meta.width as usize, meta.height as usize);
+    let (wb, hb) = (w.div_ceil(4), h.div_ceil(4));
+    let bpp = fmt.block_bytes();
+
+    let linear = if meta.tile_mode == 1 {
+        let need = wb * hb * bpp;
+        if data.len() < need {
+            anyhow::bail!("data too short: {} < {need}", data.len());
+        }
+        data[..need].to_vec()
+    } else {
+        deswizzle_block_linear(data, wb, hb, bpp, meta.block_height_log2)?
+    };
+
+    let mut rgba = vec![0u8; w * h * 4];
+    for by in 0..hb {
+        for bx in 0..wb {
+            let at = (by * wb + bx) * bpp;
+            let block = &linear[at..at + bpp];
+            let px = match fmt {
+                BcFormat::Bc1 => decode_bc1_block(block),
+                BcFormat::Bc7 => decode_bc7_block(block),
+            };
+            for (i, p) in px.iter().enumerate() {
+                let (x, y) = (bx * 4 + i % 4, by * 4 + i / 4);
+                if x < w && y < h {
+                    let o = (y * w + x) * 4;
+                    rgba[o..o + 4].copy_from_slice(p);
+                }
+            }
+        }
+    }
+    Ok((w as u32, h as u32, rgba))
+}
+
+/// Undoes the Tegra X1 block-linear layout for one surface (GOB = 64 bytes x 8 rows)
+fn deswizzle_block_linear(
+    src: &[u8],
+    wb: usize,
+    hb: usize,
+    bpp: usize,
+    block_height_log2: i32,
+) -> anyhow::Result<Vec<u8>> {
+    let gob_rows = 8usize << block_height_log2.clamp(0, 5);
+    let gobs_per_block = gob_rows / 8;
+    let gobs_x = (wb * bpp).div_ceil(64);
+    let need = gobs_x * 512 * gobs_per_block * hb.div_ceil(gob_rows);
+    if src.len() < need {
+        anyhow::bail!("swizzled data too short: {} < {need}", src.len());
+    }
+
+    let mut out = vec![0u8; wb * hb * bpp];
+    for y in 0..hb {
+        for x in 0..wb {
+            let xb = x * bpp;
+            let gob = (y / gob_rows) * 512 * gobs_per_block * gobs_x
+                + (xb / 64) * 512 * gobs_per_block
+                + ((y % gob_rows) / 8) * 512;
+            let (gx, gy) = (xb % 64, y % 8);
+            let addr = gob
+                + (gx / 32) * 256
+                + (gy / 2) * 64
+                + ((gx % 32) / 16) * 32
+                + (gy % 2) * 16
+                + (gx % 16);
+            let dst = (y * wb + x) * bpp;
+            out[dst..dst + bpp].copy_from_slice(&src[addr..addr + bpp]);
+        }
+    }
+    Ok(out)
+}
+
+fn rgb565(c: u16) -> [u8; 4] {
+    let r = ((c >> 11) & 0x1F) as u8;
+    let g = ((c >> 5) & 0x3F) as u8;
+    let b = (c & 0x1F) as u8;
+    [
+        (r << 3) | (r >> 2),
+        (g << 2) | (g >> 4),
+        (b << 3) | (b >> 2),
+        255,
+    ]
+}
+
+fn decode_bc1_block(b: &[u8]) -> [[u8; 4]; 16] {
+    let c0 = u16::from_le_bytes([b[0], b[1]]);
+    let c1 = u16::from_le_bytes([b[2], b[3]]);
+    let (p0, p1) = (rgb565(c0), rgb565(c1));
+    let mix = |a: u8, b: u8, wa: u16, wb: u16| ((a as u16 * wa + b as u16 * wb) / (wa + wb)) as u8;
+    let mut pal = [p0, p1, [0; 4], [0; 4]];
+    if c0 > c1 {
+        for ch in 0..3 {
+            pal[2][ch] = mix(p0[ch], p1[ch], 2, 1);
+            pal[3][ch] = mix(p0[ch], p1[ch], 1, 2);
+        }
+        pal[2][3] = 255;
+        pal[3][3] = 255;
+    } else {
+        for ch in 0..3 {
+            pal[2][ch] = mix(p0[ch], p1[ch], 1, 1);
+        }
+        pal[2][3] = 255;
+    }
+
+    let idx = u32::from_le_bytes([b[4], b[5], b[6], b[7]]);
+    let mut out = [[0u8; 4]; 16];
+    for (i, px) in out.iter_mut().enumerate() {
+        *px = pal[((idx >> (i * 2)) & 3) as usize];
+    }
+    out
+}
+
+struct Bits {
+    v: u128,
+    pos: u32,
+}
+
+impl Bits {
+    fn read(&mut self, n: u32) -> u8 {
+        if n == 0 {
+            return 0;
+        }
+        let out = ((self.v >> self.pos) & ((1u128 << n) - 1)) as u8;
+        self.pos += n;
+        out
+    }
+}
+
+struct Bc7Mode {
+    subsets: usize,
+    partition_bits: u32,
+    rotation_bits: u32,
+    index_sel_bits: u32,
+    color_bits: u32,
+    alpha_bits: u32,
+    endpoint_pbits: bool,
+    shared_pbits: bool,
+    index_bits: u32,
+    index2_bits: u32,
+}
+
+#[allow(clippy::too_many_arguments)]
+const fn mode(
+    subsets: usize,
+    partition_bits: u32,
+    rotation_bits: u32,
+    index_sel_bits: u32,
+    color_bits: u32,
+    alpha_bits: u32,
+    endpoint_pbits: bool,
+    shared_pbits: bool,
+    index_bits: u32,
+    index2_bits: u32,
+) -> Bc7Mode {
+    Bc7Mode {
+        subsets,
+        partition_bits,
+        rotation_bits,
+        index_sel_bits,
+        color_bits,
+        alpha_bits,
+        endpoint_pbits,
+        shared_pbits,
+        index_bits,
+        index2_bits,
+    }
+}
+
+const BC7_MODES: [Bc7Mode; 8] = [
+    mode(3, 4, 0, 0, 4, 0, true, false, 3, 0),
+    mode(2, 6, 0, 0, 6, 0, false, true, 3, 0),
+    mode(3, 6, 0, 0, 5, 0, false, false, 2, 0),
+    mode(2, 6, 0, 0, 7, 0, true, false, 2, 0),
+    mode(1, 0, 2, 1, 5, 6, false, false, 2, 3),
+    mode(1, 0, 2, 0, 7, 8, false, false, 2, 2),
+    mode(1, 0, 0, 0, 7, 7, true, false, 4, 0),
+    mode(2, 6, 0, 0, 5, 5, true, false, 2, 0),
+];
+
+// bit i set => pixel i is in subset 1
+const PARTITIONS2: [u16; 64] = [
+    0xCCCC, 0x8888, 0xEEEE, 0xECC8, 0xC880, 0xFEEC, 0xFEC8, 0xEC80, 0xC800, 0xFFEC, 0xFE80, 0xE800,
+    0xFFE8, 0xFF00, 0xFFF0, 0xF000, 0xF710, 0x008E, 0x7100, 0x08CE, 0x008C, 0x7310, 0x3100, 0x8CCE,
+    0x088C, 0x3110, 0x6666, 0x366C, 0x17E8, 0x0FF0, 0x718E, 0x399C, 0xAAAA, 0xF0F0, 0x5A5A, 0x33CC,
+    0x3C3C, 0x55AA, 0x9696, 0xA55A, 0x73CE, 0x13C8, 0x324C, 0x3BDC, 0x6996, 0xC33C, 0x9966, 0x0660,
+    0x0272, 0x04E4, 0x4E40, 0x2720, 0xC936, 0x936C, 0x39C6, 0x639C, 0x9336, 0x9CC6, 0x817E, 0xE718,
+    0xCCF0, 0x0FCC, 0x7744, 0xEE22,
+];
+
+const PARTITIONS3: [[u8; 16]; 64] = [
+    [0, 0, 1, 1, 0, 0, 1, 1, 0, 2, 2, 1, 2, 2, 2, 2],
+    [0, 0, 0, 1, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2, 2, 1],
+    [0, 0, 0, 0, 2, 0, 0, 1, 2, 2, 1, 1, 2, 2, 1, 1],
+    [0, 2, 2, 2, 0, 0, 2, 2, 0, 0, 1, 1, 0, 1, 1, 1],
+    [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2],
+    [0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 2, 2, 0, 0, 2, 2],
+    [0, 0, 2, 2, 0, 0, 2, 2, 1, 1, 1, 1, 1, 1, 1, 1],
+    [0, 0, 1, 1, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2, 1, 1],
+    [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2],
+    [0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2],
+    [0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2, 2],
+    [0, 0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2],
+    [0, 1, 1, 2, 0, 1, 1, 2, 0, 1, 1, 2, 0, 1, 1, 2],
+    [0, 1, 2, 2, 0, 1, 2, 2, 0, 1, 2, 2, 0, 1, 2, 2],
+    [0, 0, 1, 1, 0, 1, 1, 2, 1, 1, 2, 2, 1, 2, 2, 2],
+    [0, 0, 1, 1, 2, 0, 0, 1, 2, 2, 0, 0, 2, 2, 2, 0],
+    [0, 0, 0, 1, 0, 0, 1, 1, 0, 1, 1, 2, 1, 1, 2, 2],
+    [0, 1, 1, 1, 0, 0, 1, 1, 2, 0, 0, 1, 2, 2, 0, 0],
+    [0, 0, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2, 1, 1, 2, 2],
+    [0, 0, 2, 2, 0, 0, 2, 2, 0, 0, 2, 2, 1, 1, 1, 1],
+    [0, 1, 1, 1, 0, 1, 1, 1, 0, 2, 2, 2, 0, 2, 2, 2],
+    [0, 0, 0, 1, 0, 0, 0, 1, 2, 2, 2, 1, 2, 2, 2, 1],
+    [0, 0, 0, 0, 0, 0, 1, 1, 0, 1, 2, 2, 0, 1, 2, 2],
+    [0, 0, 0, 0, 1, 1, 0, 0, 2, 2, 1, 0, 2, 2, 1, 0],
+    [0, 1, 2, 2, 0, 1, 2, 2, 0, 0, 1, 1, 0, 0, 0, 0],
+    [0, 0, 1, 2, 0, 0, 1, 2, 1, 1, 2, 2, 2, 2, 2, 2],
+    [0, 1, 1, 0, 1, 2, 2, 1, 1, 2, 2, 1, 0, 1, 1, 0],
+    [0, 0, 0, 0, 0, 1, 1, 0, 1, 2, 2, 1, 1, 2, 2, 1],
+    [0, 0, 2, 2, 1, 1, 0, 2, 1, 1, 0, 2, 0, 0, 2, 2],
+    [0, 1, 1, 0, 0, 1, 1, 0, 2, 0, 0, 2, 2, 2, 2, 2],
+    [0, 0, 1, 1, 0, 1, 2, 2, 0, 1, 2, 2, 0, 0, 1, 1],
+    [0, 0, 0, 0, 2, 0, 0, 0, 2, 2, 1, 1, 2, 2, 2, 1],
+    [0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 2, 1, 2, 2, 2],
+    [0, 2, 2, 2, 0, 0, 2, 2, 0, 0, 1, 2, 0, 0, 1, 1],
+    [0, 0, 1, 1, 0, 0, 1, 2, 0, 0, 2, 2, 0, 2, 2, 2],
+    [0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2, 0],
+    [0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 0, 0, 0, 0],
+    [0, 1, 2, 0, 1, 2, 0, 1, 2, 0, 1, 2, 0, 1, 2, 0],
+    [0, 1, 2, 0, 2, 0, 1, 2, 1, 2, 0, 1, 0, 1, 2, 0],
+    [0, 0, 1, 1, 2, 2, 0, 0, 1, 1, 2, 2, 0, 0, 1, 1],
+    [0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 0, 0, 0, 0, 1, 1],
+    [0, 1, 0, 1, 0, 1, 0, 1, 2, 2, 2, 2, 2, 2, 2, 2],
+    [0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 2, 1, 2, 1, 2, 1],
+    [0, 0, 2, 2, 1, 1, 2, 2, 0, 0, 2, 2, 1, 1, 2, 2],
+    [0, 0, 2, 2, 0, 0, 1, 1, 0, 0, 2, 2, 0, 0, 1, 1],
+    [0, 2, 2, 0, 1, 2, 2, 1, 0, 2, 2, 0, 1, 2, 2, 1],
+    [0, 1, 0, 1, 2, 2, 2, 2, 2, 2, 2, 2, 0, 1, 0, 1],
+    [0, 0, 0, 0, 2, 1, 2, 1, 2, 1, 2, 1, 2, 1, 2, 1],
+    [0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 2, 2, 2, 2],
+    [0, 2, 2, 2, 0, 1, 1, 1, 0, 2, 2, 2, 0, 1, 1, 1],
+    [0, 0, 0, 2, 1, 1, 1, 2, 0, 0, 0, 2, 1, 1, 1, 2],
+    [0, 0, 0, 0, 2, 1, 1, 2, 2, 1, 1, 2, 2, 1, 1, 2],
+    [0, 2, 2, 2, 0, 1, 1, 1, 0, 1, 1, 1, 0, 2, 2, 2],
+    [0, 0, 0, 2, 1, 1, 1, 2, 1, 1, 1, 2, 0, 0, 0, 2],
+    [0, 1, 1, 0, 0, 1, 1, 0, 0, 1, 1, 0, 2, 2, 2, 2],
+    [0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 2, 1, 1, 2],
+    [0, 1, 1, 0, 0, 1, 1, 0, 2, 2, 2, 2, 2, 2, 2, 2],
+    [0, 0, 2, 2, 0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 2, 2],
+    [0, 0, 2, 2, 1, 1, 2, 2, 1, 1, 2, 2, 0, 0, 2, 2],
+    [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2],
+    [0, 0, 0, 2, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 1],
+    [0, 2, 2, 2, 1, 2, 2, 2, 0, 2, 2, 2, 1, 2, 2, 2],
+    [0, 1, 0, 1, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2],
+    [0, 1, 1, 1, 2, 0, 1, 1, 2, 2, 0, 1, 2, 2, 2, 0],
+];
+
+const ANCHOR2: [u8; 64] = [
+    15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 2, 8, 2, 2, 8, 8, 15, 2, 8,
+    2, 2, 8, 8, 2, 2, 15, 15, 6, 8, 2, 8, 15, 15, 2, 8, 2, 2, 2, 15, 15, 6, 6, 2, 6, 8, 15, 15, 2,
+    2, 15, 15, 15, 15, 15, 2, 2, 15,
+];
+
+const ANCHOR3_1: [u8; 64] = [
+    3, 3, 15, 15, 8, 3, 15, 15, 8, 8, 6, 6, 6, 5, 3, 3, 3, 3, 8, 15, 3, 3, 6, 10, 5, 8, 8, 6, 8, 5,
+    15, 15, 8, 15, 3, 5, 6, 10, 8, 15, 15, 3, 15, 5, 15, 15, 15, 15, 3, 15, 5, 5, 5, 8, 5, 10, 5,
+    10, 8, 13, 15, 12, 3, 3,
+];
+
+const ANCHOR3_2: [u8; 64] = [
+    15, 8, 8, 3, 15, 15, 3, 8, 15, 15, 15, 15, 15, 15, 15, 8, 15, 8, 15, 3, 15, 8, 15, 8, 3, 15, 6,
+    10, 15, 15, 10, 8, 15, 3, 15, 10, 10, 8, 9, 10, 6, 15, 8, 15, 3, 6, 6, 8, 15, 3, 15, 15, 15,
+    15, 15, 15, 15, 15, 15, 15, 3, 15, 15, 8,
+];
+
+const WEIGHTS2: [u16; 4] = [0, 21, 43, 64];
+const WEIGHTS3: [u16; 8] = [0, 9, 18, 27, 37, 46, 55, 64];
+const WEIGHTS4: [u16; 16] = [0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64];
+
+fn weight(bits: u32, idx: u8) -> u16 {
+    match bits {
+        2 => WEIGHTS2[idx as usize],
+        3 => WEIGHTS3[idx as usize],
+        _ => WEIGHTS4[idx as usize],
+    }
+}
+
+fn interp(e0: u8, e1: u8, w: u16) -> u8 {
+    (((64 - w) * e0 as u16 + w * e1 as u16 + 32) >> 6) as u8
+}
+
+fn expand(v: u8, bits: u32) -> u8 {
+    if bits >= 8 {
+        return v;
+    }
+    let v = v << (8 - bits);
+    v | (v >> bits)
+}
+
+fn decode_bc7_block(b: &[u8]) -> [[u8; 4]; 16] {
+    let mut bits = Bits {
+        v: u128::from_le_bytes(b[..16].try_into().unwrap_or([0; 16])),
+        pos: 0,
+    };
+    let Some(mode_idx) = (0..8).find(|_| bits.read(1) == 1) else {
+        // reserved mode: spec says transparent black
+        return [[0; 4]; 16];
+    };
+    let m = &BC7_MODES[mode_idx];
+
+    let partition = bits.read(m.partition_bits) as usize;
+    let rotation = bits.read(m.rotation_bits);
+    let index_sel = bits.read(m.index_sel_bits);
+
+    let n = m.subsets * 2;
+    // [endpoint][channel], channels r,g,b,a
+    let mut ep = [[0u8; 4]; 6];
+    for ch in 0..3 {
+        for e in ep.iter_mut().take(n) {
+            e[ch] = bits.read(m.color_bits);
+        }
+    }
+    if m.alpha_bits > 0 {
+        for e in ep.iter_mut().take(n) {
+            e[3] = bits.read(m.alpha_bits);
+        }
+    }
+
+    let mut color_bits = m.color_bits;
+    let mut alpha_bits = m.alpha_bits;
+    if m.endpoint_pbits || m.shared_pbits {
+        let mut pbits = [0u8; 6];
+        if m.endpoint_pbits {
+            for p in pbits.iter_mut().take(n) {
+                *p = bits.read(1);
+            }
+        } else {
+            for s in 0..m.subsets {
+                let p = bits.read(1);
+                pbits[s * 2] = p;
+                pbits[s * 2 + 1] = p;
+            }
+        }
+        for (e, p) in ep.iter_mut().zip(pbits).take(n) {
+            for c in e.iter_mut() {
+                *c = (*c << 1) | p;
+            }
+        }
+        color_bits += 1;
+        if alpha_bits > 0 {
+            alpha_bits += 1;
+        }
+    }
+    for e in ep.iter_mut().take(n) {
+        for c in e.iter_mut().take(3) {
+            *c = expand(*c, color_bits);
+        }
+        e[3] = if alpha_bits > 0 {
+            expand(e[3], alpha_bits)
+        } else {
+            255
+        };
+    }
+
+    let subset_of = |i: usize| -> usize {
+        match m.subsets {
+            2 => ((PARTITIONS2[partition] >> i) & 1) as usize,
+            3 => PARTITIONS3[partition][i] as usize,
+            _ => 0,
+        }
+    };
+    let is_anchor = |i: usize| -> bool {
+        i == 0
+            || match m.subsets {
+                2 => i == ANCHOR2[partition] as usize,
+                3 => i == ANCHOR3_1[partition] as usize || i == ANCHOR3_2[partition] as usize,
+                _ => false,
+            }
+    };
+
+    let mut idx1 = [0u8; 16];
+    for (i, v) in idx1.iter_mut().enumerate() {
+        *v = bits.read(m.index_bits - is_anchor(i) as u32);
+    }
+    let mut idx2 = [0u8; 16];
+    if m.index2_bits > 0 {
+        for (i, v) in idx2.iter_mut().enumerate() {
+            *v = bits.read(m.index2_bits - (i == 0) as u32);
+        }
+    }
+
+    let mut out = [[0u8; 4]; 16];
+    for (i, px) in out.iter_mut().enumerate() {
+        let s = subset_of(i);
+        let (e0, e1) = (ep[s * 2], ep[s * 2 + 1]);
+        let (cw, aw) = if m.index2_bits == 0 {
+            let w = weight(m.index_bits, idx1[i]);
+            (w, w)
+        } else if index_sel == 0 {
+            (
+                weight(m.index_bits, idx1[i]),
+                weight(m.index2_bits, idx2[i]),
+            )
+        } else {
+            (
+                weight(m.index2_bits, idx2[i]),
+                weight(m.index_bits, idx1[i]),
+            )
+        };
+        for ch in 0..3 {
+            px[ch] = interp(e0[ch], e1[ch], cw);
+        }
+        px[3] = interp(e0[3], e1[3], aw);
+        match rotation {
+            1 => px.swap(0, 3),
+            2 => px.swap(1, 3),
+            3 => px.swap(2, 3),
+            _ => {}
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Packs BC7 fields LSB first, the way `Bits` reads them
+    #[derive(Default)]
+    struct BitWriter {
+        v: u128,
+        pos: u32,
+    }
+
+    impl BitWriter {
+        fn put(&mut self, n: u32, val: u32) -> &mut Self {
+            self.v |= (val as u128) << self.pos;
+            self.pos += n;
+            self
+        }
+
+        fn mode(&mut self, m: u32) -> &mut Self {
+            self.put(m + 1, 1 << m)
+        }
+
+        fn block(&self) -> [u8; 16] {
+            assert_eq!(self.pos, 128, "block is not 128 bits");
+            self.v.to_le_bytes()
+        }
+    }
+
+    fn bc1(c0: u16, c1: u16, idx: u32) -> [u8; 8] {
+        let mut b = [0u8; 8];
+        b[..2].copy_from_slice(&c0.to_le_bytes());
+        b[2..4].copy_from_slice(&c1.to_le_bytes());
+        b[4..].copy_from_slice(&idx.to_le_bytes());
+        b
+    }
+
+    #[test]
+    fn bc1_four_color_mode() {
+        // red > blue, so the block interpolates two thirds each way
+        let px = decode_bc1_block(&bc1(0xF800, 0x001F, 0b11_10_01_00));
+        assert_eq!(px[0], [255, 0, 0, 255]);
+        assert_eq!(px[1], [0, 0, 255, 255]);
+        assert_eq!(px[2], [170, 0, 85, 255]);
+        assert_eq!(px[3], [85, 0, 170, 255]);
+        assert_eq!(px[15], [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn bc1_three_color_mode() {
+        // c0 <= c1: index 2 is the midpoint and index 3 transparent black
+        let px = decode_bc1_block(&bc1(0x001F, 0xF800, 0b11_10_01_00));
+        assert_eq!(px[0], [0, 0, 255, 255]);
+        assert_eq!(px[1], [255, 0, 0, 255]);
+        assert_eq!(px[2], [127, 0, 127, 255]);
+        assert_eq!(px[3], [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn bc7_mode6() {
+        let mut w = BitWriter::default();
+        w.mode(6);
+        for (e0, e1) in [(127, 0), (0, 127), (0x40, 0x40), (127, 127)] {
+            w.put(7, e0).put(7, e1);
+        }
+        w.put(1, 1).put(1, 0);
+        // pixel 0 is the anchor and has one bit less
+        w.put(3, 0).put(4, 15).put(4, 8);
+        for _ in 3..16 {
+            w.put(4, 0);
+        }
+        let px = decode_bc7_block(&w.block());
+        assert_eq!(px[0], [255, 1, 129, 255]);
+        assert_eq!(px[1], [0, 254, 128, 254]);
+        assert_eq!(px[2], [120, 135, 128, 254]);
+        assert_eq!(px[3], [255, 1, 129, 255]);
+    }
+
+    #[test]
+    fn bc7_mode1_two_subsets() {
+        let mut w = BitWriter::default();
+        w.mode(1).put(6, 0);
+        // subset 0: red -> green, subset 1: blue -> black
+        for ch in [[63, 0, 0, 0], [0, 63, 0, 0], [0, 0, 63, 0]] {
+            for v in ch {
+                w.put(6, v);
+            }
+        }
+        // shared p-bit per subset
+        w.put(1, 1).put(1, 0);
+        for i in 0..16 {
+            // anchors are pixel 0 and, for partition 0, pixel 15
+            let bits = if i == 0 || i == 15 { 2 } else { 3 };
+            w.put(bits, if i == 1 { 7 } else { 0 });
+        }
+        let px = decode_bc7_block(&w.block());
+        // partition 0 puts columns 2 and 3 in subset 1
+        assert_eq!(px[0], [255, 2, 2, 255]);
+        assert_eq!(px[1], [2, 255, 2, 255]);
+        assert_eq!(px[2], [0, 0, 253, 255]);
+        assert_eq!(px[4], [255, 2, 2, 255]);
+        assert_eq!(px[15], [0, 0, 253, 255]);
+    }
+
+    #[test]
+    fn bc7_reserved_mode_is_transparent_black() {
+        assert_eq!(decode_bc7_block(&[0; 16]), [[0; 4]; 16]);
+    }
+
+    #[test]
+    fn deswizzle_gob_layout() {
+        // 4x2 BC7 blocks fit one GOB: 16-byte columns pair up in 32-byte halves
+        let wb = 4;
+        let hb = 2;
+        let mut src = vec![0u8; 512];
+        let addrs = [0, 32, 256, 288, 16, 48, 272, 304];
+        for (n, &a) in addrs.iter().enumerate() {
+            src[a..a + 16].fill(n as u8 + 1);
+        }
+        let out = deswizzle_block_linear(&src, wb, hb, 16, 0).unwrap();
+        for n in 0..addrs.len() {
+            assert!(out[n * 16..(n + 1) * 16].iter().all(|&b| b == n as u8 + 1));
+        }
+        assert!(deswizzle_block_linear(&src[..511], wb, hb, 16, 0).is_err());
+    }
+
+    #[test]
+    fn deswizzle_block_height() {
+        // 16x9 BC1 blocks: two GOBs wide, and row 8 starts the next GOB down
+        let (wb, hb, bpp) = (16, 9, 8);
+        for (log2, right, below) in [(0, 512, 1024), (1, 1024, 512)] {
+            let mut src = vec![0u8; 4096];
+            src[right..right + bpp].fill(1);
+            src[below..below + bpp].fill(2);
+            let out = deswizzle_block_linear(&src, wb, hb, bpp, log2).unwrap();
+            let at = |x: usize, y: usize| out[(y * wb + x) * bpp];
+            assert_eq!(at(8, 0), 1, "block height log2 {log2}");
+            assert_eq!(at(0, 8), 2, "block height log2 {log2}");
+        }
+    }
+
+    #[test]
+    fn decode_mip0_crops_partial_blocks() {
+        let meta = BntxMeta {
+            width: 6,
+            height: 2,
+            mip_count: 1,
+            data_length: 16,
+            base_offset: 0,
+            format_type: 0x1A,
+            format_var: 6,
+            tile_mode: 1,
+            block_height_log2: 0,
+        };
+        let mut data = bc1(0xF800, 0xF800, 0).to_vec();
+        data.extend_from_slice(&bc1(0x001F, 0x001F, 0));
+        let (w, h, rgba) = decode_mip0(&meta, &data).unwrap();
+        assert_eq!((w, h), (6, 2));
+        assert_eq!(rgba.len(), 6 * 2 * 4);
+        assert_eq!(rgba[..4], [255, 0, 0, 255]);
+        assert_eq!(rgba[4 * 4..5 * 4], [0, 0, 255, 255]);
+        assert_eq!(rgba[(6 + 5) * 4..], [0, 0, 255, 255]);
+        assert!(decode_mip0(&meta, &data[..8]).is_err());
+    }
+}
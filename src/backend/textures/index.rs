@@ -1,23 +1,31 @@
-use crate::backend::textures::bntx::{build_index, BntxIndexDoc};
+use crate::backend::textures::bntx::{build_index, BntxIndexDoc, FormatMap};
 use crate::progress::ProgressSink;
 use std::{fs, path::Path, path::PathBuf};
 
 pub fn load_or_build_index(
     za_dump: &Path,
     cache_path: &Path,
+    format_map: &FormatMap,
+    follow_links: bool,
     progress: &ProgressSink,
 ) -> anyhow::Result<BntxIndexDoc> {
     if cache_path.is_file() {
         let doc: BntxIndexDoc = serde_json::from_slice(&fs::read(cache_path)?)?;
-        progress.info(format!(
-            "[tex] loaded bntx index: {:?} (entries={})",
-            cache_path,
-            doc.entries.len()
-        ));
-        return Ok(doc);
+        if doc.format_map != *format_map {
+            progress.info("[tex] format map changed since the index was built; rebuilding");
+        } else if doc.follow_links != follow_links {
+            progress.info("[tex] --follow-symlinks changed since the index was built; rebuilding");
+        } else {
+            progress.info(format!(
+                "[tex] loaded bntx index: {:?} (entries={})",
+                cache_path,
+                doc.entries.len()
+            ));
+            return Ok(doc);
+        }
     }
     progress.info(format!("[tex] building bntx index: {:?}", cache_path));
-    let doc = build_index(za_dump)?;
+    let doc = build_index(za_dump, format_map, follow_links)?;
     if let Some(parent) = cache_path.parent() {
         fs::create_dir_all(parent)?;
     }
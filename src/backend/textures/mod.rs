@@ -1,15 +1,29 @@
 mod bmp;
 mod bntx;
+mod decode;
 mod index;
+mod state;
 
-use crate::{config::AppConfig, progress::ProgressSink};
-use bntx::{extract_tex_data, read_bntx_metas, ultimate_format, BntxIndexDoc, BntxIndexEntry};
+use crate::{
+    backend::profile::{run_tool, ToolProfile},
+    config::{AppConfig, IconDonorStrategy, TextureScope},
+    error::SvzaError,
+    paths::atomic_write,
+    pool::{Semaphore, TexLimits, WorkPool},
+    progress::{ProgressSink, WarningKind},
+};
+use bntx::{
+    extract_tex_data, read_bntx_metas, BntxIndexDoc, BntxIndexEntry, BntxMeta, FormatMap,
+    KNOWN_ULTIMATE_FORMATS,
+};
 use index::{default_cache_path, load_or_build_index};
+use state::{default_state_path, TexState};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fs,
     path::{Path, PathBuf},
     process::Command,
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
     time::Instant,
 };
 use walkdir::WalkDir;
@@ -18,76 +32,253 @@ pub fn convert_textures_if_enabled(
     cfg: &AppConfig,
     za_dump: &Path,
     out_root: &Path,
+    profile: Option<&ToolProfile>,
+    limits: &TexLimits,
     progress: &ProgressSink,
 ) -> anyhow::Result<()> {
     if !cfg.texture_convert {
+        progress.phase_skipped("Texture convert", "texture conversion off");
         return Ok(());
     }
-    let ultimate = cfg
-        .ultimate_tex_cli
-        .as_ref()
-        .ok_or_else(|| anyhow::anyhow!("texture_convert enabled but ultimate_tex_cli not set"))?;
+    let ultimate = cfg.ultimate_tex_cli.as_ref().ok_or(SvzaError::ToolNotSet {
+        tool: "ultimate_tex_cli",
+    })?;
     if !ultimate.is_file() {
-        anyhow::bail!("ultimate_tex_cli not found: {ultimate:?}");
+        return Err(SvzaError::MissingTool {
+            tool: "ultimate_tex_cli",
+            path: ultimate.clone(),
+        }
+        .into());
+    }
+    for (pattern, fmt) in &cfg.format_overrides {
+        if !KNOWN_ULTIMATE_FORMATS.contains(&fmt.as_str()) {
+            anyhow::bail!(
+                "format override {pattern:?}: unknown format {fmt:?} (known: {})",
+                KNOWN_ULTIMATE_FORMATS.join(", ")
+            );
+        }
     }
 
-    let cache_path = default_cache_path(out_root);
-    let index = load_or_build_index(za_dump, &cache_path, progress)?;
-    convert_dir(
+    let (index, default_icon) = load_donors(cfg, za_dump, out_root, progress)?;
+    let state_path = default_state_path(out_root);
+    let settings = settings_fingerprint(cfg, &index, default_icon.as_ref());
+    // Forced conversions are always tracked, or every run would re-encode them
+    let mut state =
+        (cfg.texture_incremental || cfg.verify_hash || !cfg.format_overrides.is_empty())
+            .then(|| TexState::load(&state_path, settings));
+    let res = convert_dir(
         ultimate,
         &index,
-        &out_root.join("ik_pokemon").join("data"),
-        cfg.texture_allow_resize,
+        &cfg.out_path(out_root, "ik_pokemon/data")?,
+        ConvertOptions {
+            resize: ResizePolicy {
+                allow: cfg.texture_allow_resize,
+                max_dim: cfg.max_resize_dim,
+            },
+            scope: cfg.texture_scope,
+            incremental: cfg.texture_incremental,
+            verify_hash: cfg.verify_hash,
+            fail_fast: cfg.fail_fast,
+            profile,
+            pool: limits.pool,
+            tool_slots: &limits.tool_slots,
+        },
+        &cfg.format_overrides,
+        default_icon,
+        state.as_mut(),
+        progress,
+    );
+    if let Some(state) = &state {
+        if let Err(e) = state.save(&state_path) {
+            progress.warn(format!("[tex] failed to write {:?}: {e}", state_path));
+        }
+    }
+    res
+}
+
+/// The bntx donor index plus the fallback icon donor, as a texture pass would use them
+fn load_donors(
+    cfg: &AppConfig,
+    za_dump: &Path,
+    out_root: &Path,
+    progress: &ProgressSink,
+) -> anyhow::Result<(BntxIndexDoc, Option<BntxIndexEntry>)> {
+    let cache_path = default_cache_path(out_root);
+    let format_map = match cfg.tex_format_map.as_ref() {
+        Some(p) => {
+            let map = FormatMap::load(p)?;
+            progress.info(format!("[tex] format map: {p:?}"));
+            map
+        }
+        None => FormatMap::default(),
+    };
+    let index = load_or_build_index(
+        za_dump,
+        &cache_path,
+        &format_map,
+        cfg.follow_symlinks,
         progress,
-    )
+    )?;
+    let default_icon = select_default_icon_donor(
+        &index.entries,
+        cfg.icon_donor_strategy,
+        cfg.icon_donor_path.as_deref(),
+        za_dump,
+        progress,
+    )?;
+    Ok((index, default_icon))
+}
+
+/// Hash of everything besides the source file that decides what a conversion writes; a state
+/// recorded under another fingerprint is dropped so a changed setting reaches every file
+fn settings_fingerprint(
+    cfg: &AppConfig,
+    index: &BntxIndexDoc,
+    default_icon: Option<&BntxIndexEntry>,
+) -> u64 {
+    let key = serde_json::json!({
+        "format_overrides": cfg.format_overrides,
+        "format_map": index.format_map,
+        "allow_resize": cfg.texture_allow_resize,
+        "max_resize_dim": cfg.max_resize_dim,
+        "icon_donor_strategy": cfg.icon_donor_strategy,
+        "icon_donor": default_icon,
+        "donors": index.entries,
+    });
+    bntx::fnv1a64(key.to_string().as_bytes())
 }
 
+/// Source `.bntx` files under `input_dir` a pass with `scope` would touch, sorted
+fn collect_bntx(input_dir: &Path, scope: TextureScope) -> anyhow::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for e in WalkDir::new(input_dir).follow_links(false) {
+        let e = e?;
+        if !e.file_type().is_file() {
+            continue;
+        }
+        if e.path().extension().and_then(|x| x.to_str()) != Some("bntx") {
+            continue;
+        }
+        if scope == TextureScope::IconsOnly && !is_icon_path(e.path()) {
+            continue;
+        }
+        files.push(e.path().to_path_buf());
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Writes `_report/tex_plan.json`: the donor a texture pass would pick for each output `.bntx`
+/// Runs the same donor selection as the pass but never invokes ultimate_tex_cli.
+pub fn write_tex_plan(
+    cfg: &AppConfig,
+    za_dump: &Path,
+    out_root: &Path,
+    progress: &ProgressSink,
+) -> anyhow::Result<PathBuf> {
+    let input_dir = cfg.out_path(out_root, "ik_pokemon/data")?;
+    if !input_dir.is_dir() {
+        anyhow::bail!("no converted pm data at {input_dir:?}; run the pipeline first");
+    }
+    let (index, default_icon) = load_donors(cfg, za_dump, out_root, progress)?;
+
+    let mut plan = BTreeMap::<String, String>::new();
+    let mut no_donor = 0usize;
+    for src in collect_bntx(&input_dir, cfg.texture_scope)? {
+        let rel = src
+            .strip_prefix(&input_dir)
+            .unwrap_or(&src)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let donor = match read_bntx_metas(&src) {
+            Ok(metas) => metas.first().and_then(|m0| {
+                pick_donor(
+                    &src,
+                    m0,
+                    &index.format_map,
+                    &index.entries,
+                    &index.by_key,
+                    &index.by_name,
+                    &default_icon,
+                )
+            }),
+            Err(e) => {
+                progress.warn_kind(
+                    WarningKind::Texture,
+                    format!("[tex-plan] unreadable {rel}: {e}"),
+                );
+                None
+            }
+        };
+        let choice = match donor {
+            Some(d) => d.file_path.clone(),
+            None => {
+                no_donor += 1;
+                "no donor".to_string()
+            }
+        };
+        plan.insert(rel, choice);
+    }
+
+    let report_dir = cfg.report_dir(out_root);
+    fs::create_dir_all(&report_dir)?;
+    let path = report_dir.join("tex_plan.json");
+    fs::write(&path, serde_json::to_vec_pretty(&plan)?)?;
+    progress.info(format!(
+        "[report] wrote {:?} (files={}, no donor={no_donor})",
+        path,
+        plan.len()
+    ));
+    Ok(path)
+}
+
+#[allow(clippy::too_many_arguments)]
 fn convert_dir(
     ultimate: &Path,
     index: &BntxIndexDoc,
     input_dir: &Path,
-    allow_resize: bool,
+    opts: ConvertOptions,
+    format_overrides: &[(String, String)],
+    default_icon: Option<BntxIndexEntry>,
+    mut state: Option<&mut TexState>,
     progress: &ProgressSink,
 ) -> anyhow::Result<()> {
     progress.phase_start("Texture convert");
     if !input_dir.is_dir() {
         progress.warn(format!("[tex] missing dir: {:?}", input_dir));
-        progress.phase_end("Texture convert");
+        progress.phase_skipped("Texture convert", "no converted pm data");
         return Ok(());
     }
 
     let entries = &index.entries;
-    let default_icon = select_default_icon_donor(entries);
     let by_key = &index.by_key;
     let by_name = &index.by_name;
 
-    let mut files = Vec::new();
-    for e in WalkDir::new(input_dir).follow_links(false) {
-        let e = e?;
-        if !e.file_type().is_file() {
-            continue;
-        }
-        if e.path().extension().and_then(|x| x.to_str()) == Some("bntx") {
-            files.push(e.path().to_path_buf());
-        }
+    let files = collect_bntx(input_dir, opts.scope)?;
+    if opts.scope == TextureScope::IconsOnly {
+        progress.info(format!("[tex] icons only: {} file(s)", files.len()));
     }
-    files.sort();
     let total = files.len().max(1) as u64;
-    let mut done = 0u64;
     let mut ok = 0u64;
     let mut skipped = 0u64;
     let mut failed = 0u64;
-    let start = Instant::now();
+    let mut unchanged = 0u64;
+    let mut size_mismatch = 0u64;
+    let mut stale_donors = 0u64;
+    let mut fail_reasons = BTreeMap::<&'static str, u64>::new();
 
+    // Donor selection only reads headers, so it runs in order before anything is spawned
+    let mut jobs = Vec::new();
     for src in files {
-        done += 1;
-        progress.progress(done, total);
-        if done % 100 == 0 || done == total {
-            let secs = start.elapsed().as_secs_f64().max(0.001);
-            let rate = (done as f64) / secs;
-            let rem = (total - done) as f64;
-            let eta_s = if rate > 0.0 { rem / rate } else { 0.0 };
-            progress.info(format!("[tex] {done}/{total} ETA~{eta_s:.0}s"));
+        let rel = src
+            .strip_prefix(input_dir)
+            .unwrap_or(&src)
+            .to_string_lossy()
+            .replace('\\', "/");
+        if opts.incremental && state.as_deref().is_some_and(|s| s.unchanged(&rel, &src)) {
+            skipped += 1;
+            unchanged += 1;
+            continue;
         }
 
         let metas = match read_bntx_metas(&src) {
@@ -97,49 +288,339 @@ fn convert_dir(
                 continue;
             }
         };
-        let Some(m0) = metas.first() else {
+        let Some(m0) = metas.into_iter().next() else {
             skipped += 1;
             continue;
         };
 
-        let donor = pick_donor(&src, m0, entries, by_key, by_name, &default_icon);
+        let donor = pick_donor(
+            &src,
+            &m0,
+            &index.format_map,
+            entries,
+            by_key,
+            by_name,
+            &default_icon,
+        );
         let Some(donor) = donor else {
             skipped += 1;
             continue;
         };
-        if already_converted(m0, donor) {
-            skipped += 1;
-            continue;
+        let forced = format_override_for(&src, format_overrides);
+        if let Some(fmt) = forced {
+            // An override skips the structural check, so only our own recorded output counts
+            if state.as_deref().is_some_and(|s| {
+                data_region_hash(&src).is_some_and(|h| s.forced_unchanged(&rel, fmt, h))
+            }) {
+                skipped += 1;
+                continue;
+            }
+        } else if already_converted(&m0, &index.format_map, donor) {
+            // Structure alone can't tell a stale splice from a fresh one; with --verify-hash
+            // a data region differing from the one we last wrote is converted again
+            let content_ok = !opts.verify_hash
+                || state
+                    .as_deref()
+                    .is_some_and(|s| data_region_hash(&src).is_some_and(|h| s.check_hash(&rel, h)));
+            if content_ok {
+                skipped += 1;
+                continue;
+            }
+            progress.detail(2, format!("[tex] hash differs, reconverting {:?}", src));
         }
+        jobs.push(TexJob {
+            src,
+            rel,
+            meta: m0,
+            donor,
+            forced,
+        });
+    }
 
-        match convert_one(&src, &src, donor, ultimate, allow_resize, progress) {
-            Ok(true) => ok += 1,
-            Ok(false) => skipped += 1,
-            Err(e) => {
+    report_tex_metrics(progress, ok, skipped, size_mismatch, failed);
+    if !jobs.is_empty() {
+        progress.info(format!(
+            "[tex] converting {} file(s) on {} worker(s), at most {} ultimate_tex_cli at once",
+            jobs.len(),
+            opts.pool.jobs().min(jobs.len()),
+            opts.tool_slots
+                .permits()
+                .min(opts.pool.jobs())
+                .min(jobs.len()),
+        ));
+    }
+    let done = AtomicU64::new(total - jobs.len() as u64);
+    progress.progress(done.load(Ordering::Relaxed), total);
+    let abort = AtomicBool::new(false);
+    let start = Instant::now();
+    let started = done.load(Ordering::Relaxed);
+    let results = opts.pool.map(&jobs, |_, job| {
+        // After a --fail-fast failure, files not yet started are left alone
+        if abort.load(Ordering::Relaxed) {
+            return None;
+        }
+        let res = convert_one(
+            &job.src, &job.src, job.donor, job.forced, ultimate, opts, progress,
+        );
+        if res.is_err() && opts.fail_fast {
+            abort.store(true, Ordering::Relaxed);
+        }
+        let n = done.fetch_add(1, Ordering::Relaxed) + 1;
+        progress.progress(n, total);
+        if (n - started).is_multiple_of(100) {
+            let secs = start.elapsed().as_secs_f64().max(0.001);
+            let rate = ((n - started) as f64) / secs;
+            let rem = (total - n) as f64;
+            let eta_s = if rate > 0.0 { rem / rate } else { 0.0 };
+            progress.detail(1, format!("[tex] {n}/{total} ETA~{eta_s:.0}s"));
+        }
+        Some(res)
+    });
+
+    for (job, res) in jobs.into_iter().zip(results) {
+        let TexJob {
+            src,
+            rel,
+            meta: m0,
+            donor,
+            forced,
+        } = job;
+        let Some(res) = res else {
+            skipped += 1;
+            continue;
+        };
+        match res {
+            Ok(ConvertOutcome::Converted) => {
+                ok += 1;
+                progress.detail(2, format!("[tex] converted {:?}", src));
+                if let Some(s) = state.as_deref_mut() {
+                    if opts.verify_hash || forced.is_some() {
+                        if let Some(h) = data_region_hash(&src) {
+                            if opts.verify_hash {
+                                s.record_hash(rel.clone(), h);
+                            }
+                            if let Some(fmt) = forced {
+                                s.record_forced(rel.clone(), fmt, h);
+                            }
+                        }
+                    }
+                    s.record(rel, &src);
+                }
+                if let Err(e) = verify_converted(&src) {
+                    progress.warn_kind(
+                        WarningKind::Texture,
+                        format!(
+                            "[tex] verify failed {:?}: {e}",
+                            src.file_name().unwrap_or_default()
+                        ),
+                    );
+                }
+            }
+            Ok(ConvertOutcome::Skipped) => skipped += 1,
+            Ok(ConvertOutcome::StaleDonor) => {
+                skipped += 1;
+                stale_donors += 1;
+                progress.warn_kind(
+                    WarningKind::Texture,
+                    format!(
+                        "[tex] donor {:?} changed since the index was built; skipped {:?}",
+                        donor.file_path,
+                        src.file_name().unwrap_or_default()
+                    ),
+                );
+            }
+            Ok(ConvertOutcome::SizeMismatch {
+                format,
+                encoded,
+                donor: donor_len,
+            }) => {
+                size_mismatch += 1;
+                let src_fmt = index
+                    .format_map
+                    .ultimate_format(m0.format_type, m0.format_var)
+                    .unwrap_or_else(|| format!("0x{:02X}/{}", m0.format_type, m0.format_var));
+                progress.warn_kind(
+                    WarningKind::Texture,
+                    format!(
+                        "[tex] size mismatch {:?}: source {src_fmt} (mips={}) encoded as {format} \
+                     is {encoded} bytes, donor {:?} (mips={}) region is {donor_len}",
+                        src.file_name().unwrap_or_default(),
+                        m0.mip_count,
+                        donor.file_name,
+                        donor.mip_count,
+                    ),
+                );
+            }
+            Err(TexFailure { reason, error }) => {
+                let name = src.file_name().unwrap_or_default();
+                if opts.fail_fast {
+                    return Err(error.context(format!(
+                        "texture {name:?} failed [{}] (--fail-fast)",
+                        reason.code()
+                    )));
+                }
                 failed += 1;
-                progress.warn(format!(
-                    "[tex] failed {:?}: {e}",
-                    src.file_name().unwrap_or_default()
-                ));
+                *fail_reasons.entry(reason.code()).or_default() += 1;
+                progress.warn_kind(
+                    WarningKind::Texture,
+                    format!("[tex] failed {name:?} [{}]: {error}", reason.code()),
+                );
             }
         }
     }
 
-    progress.info(format!("[tex] ok={ok} skipped={skipped} failed={failed}"));
+    report_tex_metrics(progress, ok, skipped, size_mismatch, failed);
+    progress.info(format!(
+        "[tex] ok={ok} skipped={skipped} (unchanged since last conversion={unchanged}) \
+         size_mismatch={size_mismatch} failed={failed}"
+    ));
+    if !fail_reasons.is_empty() {
+        let by_reason: Vec<String> = fail_reasons
+            .iter()
+            .map(|(code, n)| {
+                progress.metric(format!("tex.failed.{code}"), *n as i64);
+                format!("{code}={n}")
+            })
+            .collect();
+        progress.info(format!("[tex] failed by reason: {}", by_reason.join(" ")));
+    }
+    if stale_donors > 0 {
+        progress.warn_kind(
+            WarningKind::Texture,
+            format!(
+                "[tex] {stale_donors} donor file(s) changed since indexing; delete \
+                 _cache/bntx_index_za.json to rebuild the index"
+            ),
+        );
+    }
     progress.phase_end("Texture convert");
     Ok(())
 }
 
-fn select_default_icon_donor(entries: &[BntxIndexEntry]) -> Option<BntxIndexEntry> {
-    let mut counts = HashMap::<(i32, i32, String, bool), u32>::new();
-    for e in entries {
+/// Decodes mip 0 of the first texture in a BC1/BC7 `.bntx` to RGBA8 without ultimate_tex_cli
+pub fn decode_bntx_rgba(path: &Path) -> anyhow::Result<(u32, u32, Vec<u8>)> {
+    let metas = read_bntx_metas(path)?;
+    let meta = metas
+        .first()
+        .ok_or_else(|| anyhow::anyhow!("no textures in bntx"))?;
+    let (data, _, _) = extract_tex_data(path)?;
+    decode::decode_mip0(meta, &data)
+}
+
+/// Checks a converted `.bntx` decodes to the dimensions its header claims
+/// Returns Ok(None) for formats the in-process decoder doesn't handle.
+pub fn verify_converted(path: &Path) -> anyhow::Result<Option<(u32, u32)>> {
+    let metas = read_bntx_metas(path)?;
+    let meta = metas
+        .first()
+        .ok_or_else(|| anyhow::anyhow!("no textures in bntx"))?;
+    if decode::BcFormat::from_meta(meta).is_none() {
+        return Ok(None);
+    }
+    let (w, h, rgba) = decode_bntx_rgba(path)?;
+    if (w as i32, h as i32) != (meta.width, meta.height) || rgba.len() != (w * h * 4) as usize {
+        anyhow::bail!(
+            "decoded {w}x{h} but header says {}x{}",
+            meta.width,
+            meta.height
+        );
+    }
+    Ok(Some((w, h)))
+}
+
+fn report_tex_metrics(
+    progress: &ProgressSink,
+    ok: u64,
+    skipped: u64,
+    size_mismatch: u64,
+    failed: u64,
+) {
+    progress.metric("tex.ok", ok as i64);
+    progress.metric("tex.skipped", skipped as i64);
+    progress.metric("tex.size_mismatch", size_mismatch as i64);
+    progress.metric("tex.failed", failed as i64);
+}
+
+/// Picks the fallback donor used for icons no index entry matches
+/// Errors only when a pinned donor is configured but missing from the index.
+fn select_default_icon_donor(
+    entries: &[BntxIndexEntry],
+    strategy: IconDonorStrategy,
+    pinned: Option<&Path>,
+    za_dump: &Path,
+    progress: &ProgressSink,
+) -> anyhow::Result<Option<BntxIndexEntry>> {
+    let chosen = match strategy {
+        IconDonorStrategy::MostCommon => most_common_icon(entries),
+        IconDonorStrategy::Largest => icon_entries(entries)
+            .max_by_key(|(e, _)| i64::from(e.width) * i64::from(e.height))
+            .map(|(e, _)| e.clone()),
+        IconDonorStrategy::PinnedPath => {
+            let pinned = pinned.ok_or_else(|| {
+                anyhow::anyhow!("icon donor strategy is pinned-path but no icon donor is set")
+            })?;
+            Some(pinned_icon(entries, pinned, za_dump)?)
+        }
+    };
+    match &chosen {
+        Some(e) => progress.info(format!(
+            "[tex] default icon donor ({strategy:?}): {} {}x{} {}",
+            e.file_path,
+            e.width,
+            e.height,
+            e.ultimate_format.as_deref().unwrap_or("?")
+        )),
+        None => progress.info(format!(
+            "[tex] default icon donor ({strategy:?}): none found"
+        )),
+    }
+    Ok(chosen)
+}
+
+/// ZA icon entries with a known ultimate format, paired with that format
+fn icon_entries(entries: &[BntxIndexEntry]) -> impl Iterator<Item = (&BntxIndexEntry, &str)> {
+    entries.iter().filter_map(|e| {
         let p = e.file_path.replace('\\', "/").to_lowercase();
         if !p.contains("/ik_pokemon/data/") || !p.contains("/icon/") {
-            continue;
+            return None;
         }
-        let Some(fmt) = e.ultimate_format.clone() else {
-            continue;
-        };
+        Some((e, e.ultimate_format.as_deref()?))
+    })
+}
+
+fn pinned_icon(
+    entries: &[BntxIndexEntry],
+    pinned: &Path,
+    za_dump: &Path,
+) -> anyhow::Result<BntxIndexEntry> {
+    let norm = |p: &str| p.replace('\\', "/").to_lowercase();
+    let full = if pinned.is_absolute() {
+        pinned.to_path_buf()
+    } else {
+        za_dump.join(pinned)
+    };
+    let full = norm(&full.to_string_lossy());
+    let suffix = format!(
+        "/{}",
+        norm(&pinned.to_string_lossy()).trim_start_matches("./")
+    );
+    entries
+        .iter()
+        .find(|e| norm(&e.file_path) == full)
+        .or_else(|| entries.iter().find(|e| norm(&e.file_path).ends_with(&suffix)))
+        .filter(|e| e.ultimate_format.is_some())
+        .cloned()
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "pinned icon donor {pinned:?} is not in the texture index (or has an unknown format)"
+            )
+        })
+}
+
+fn most_common_icon(entries: &[BntxIndexEntry]) -> Option<BntxIndexEntry> {
+    let mut counts = HashMap::<(i32, i32, String, bool), u32>::new();
+    for (e, fmt) in icon_entries(entries) {
+        let fmt = fmt.to_string();
         let k = (e.width, e.height, fmt, e.no_mipmaps);
         *counts.entry(k).or_default() += 1;
     }
@@ -159,12 +640,13 @@ fn select_default_icon_donor(entries: &[BntxIndexEntry]) -> Option<BntxIndexEntr
 fn pick_donor<'a>(
     src_path: &Path,
     meta: &bntx::BntxMeta,
+    format_map: &FormatMap,
     entries: &'a [BntxIndexEntry],
     by_key: &HashMap<String, Vec<usize>>,
     by_name: &HashMap<String, Vec<usize>>,
     default_icon: &'a Option<BntxIndexEntry>,
 ) -> Option<&'a BntxIndexEntry> {
-    let src_ult = ultimate_format(meta.format_type, meta.format_var).map(|s| s.to_string());
+    let src_ult = format_map.ultimate_format(meta.format_type, meta.format_var);
     let src_no_mip = meta.mip_count <= 1;
     if let Some(src_ult) = src_ult {
         let k = format!(
@@ -196,20 +678,28 @@ fn pick_donor<'a>(
         }
     }
 
-    let sp = src_path.to_string_lossy().replace('\\', "/").to_lowercase();
     if let Some(d) = default_icon.as_ref() {
-        if sp.contains("/icon/") || sp.ends_with("_00.bntx") || sp.ends_with("_00_big.bntx") {
+        if is_icon_path(src_path) {
             return Some(d);
         }
     }
     None
 }
 
-fn already_converted(meta: &bntx::BntxMeta, donor: &BntxIndexEntry) -> bool {
+fn is_icon_path(path: &Path) -> bool {
+    let sp = path.to_string_lossy().replace('\\', "/").to_lowercase();
+    sp.contains("/icon/") || sp.ends_with("_00.bntx") || sp.ends_with("_00_big.bntx")
+}
+
+fn already_converted(
+    meta: &bntx::BntxMeta,
+    format_map: &FormatMap,
+    donor: &BntxIndexEntry,
+) -> bool {
     let Some(dfmt) = donor.ultimate_format.as_deref() else {
         return false;
     };
-    let src_ult = ultimate_format(meta.format_type, meta.format_var);
+    let src_ult = format_map.ultimate_format(meta.format_type, meta.format_var);
     let Some(src_ult) = src_ult else {
         return false;
     };
@@ -229,40 +719,186 @@ fn already_converted(meta: &bntx::BntxMeta, donor: &BntxIndexEntry) -> bool {
     true
 }
 
+#[derive(Debug, Clone, Copy)]
+struct ConvertOptions<'a> {
+    resize: ResizePolicy,
+    scope: TextureScope,
+    /// Skip files whose mtime matches the state file
+    incremental: bool,
+    /// Require a recorded data-region hash match before trusting `already_converted`
+    verify_hash: bool,
+    /// Abort the phase on the first failed file instead of counting it
+    fail_fast: bool,
+    profile: Option<&'a ToolProfile>,
+    /// Workers converting files side by side
+    pool: WorkPool,
+    /// Caps concurrent ultimate_tex_cli processes (`--tex-jobs`), possibly shared with other runs
+    tool_slots: &'a Semaphore,
+}
+
+/// A file the donor pass decided to (re)convert
+struct TexJob<'a> {
+    src: PathBuf,
+    rel: String,
+    meta: BntxMeta,
+    donor: &'a BntxIndexEntry,
+    forced: Option<&'a str>,
+}
+
+/// Which step of `convert_one` failed; the code is what shows up in logs and metrics
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FailReason {
+    Io,
+    Decode,
+    ReadBmp,
+    Resize,
+    Encode,
+    Extract,
+    OverrideSize,
+    Write,
+}
+
+impl FailReason {
+    fn code(self) -> &'static str {
+        match self {
+            FailReason::Io => "io",
+            FailReason::Decode => "decode",
+            FailReason::ReadBmp => "read_bmp",
+            FailReason::Resize => "resize",
+            FailReason::Encode => "encode",
+            FailReason::Extract => "extract",
+            FailReason::OverrideSize => "override_size",
+            FailReason::Write => "write",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TexFailure {
+    reason: FailReason,
+    error: anyhow::Error,
+}
+
+impl TexFailure {
+    fn new(reason: FailReason, error: anyhow::Error) -> Self {
+        Self { reason, error }
+    }
+}
+
+trait WithReason<T> {
+    fn reason(self, reason: FailReason) -> Result<T, TexFailure>;
+}
+
+impl<T, E: Into<anyhow::Error>> WithReason<T> for Result<T, E> {
+    fn reason(self, reason: FailReason) -> Result<T, TexFailure> {
+        self.map_err(|e| TexFailure::new(reason, e.into()))
+    }
+}
+
+/// FNV-1a over the first texture's data region
+fn data_region_hash(bntx_path: &Path) -> Option<u64> {
+    let (data, _, _) = extract_tex_data(bntx_path).ok()?;
+    Some(bntx::fnv1a64(&data))
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ResizePolicy {
+    allow: bool,
+    /// Largest donor width/height we'll resize to; guards against corrupt index entries
+    max_dim: u32,
+}
+
+/// Rejects donor dimensions no real texture has before they size a resize buffer
+fn check_resize_target(tw: i32, th: i32, max_dim: u32) -> anyhow::Result<()> {
+    if tw <= 0 || th <= 0 || tw as u32 > max_dim || th as u32 > max_dim {
+        anyhow::bail!(
+            "donor size {tw}x{th} exceeds max_resize_dim {max_dim} (corrupt index entry?)"
+        );
+    }
+    Ok(())
+}
+
+fn format_override_for<'a>(src: &Path, overrides: &'a [(String, String)]) -> Option<&'a str> {
+    let path_l = src.to_string_lossy().replace('\\', "/").to_lowercase();
+    let name_l = src.file_name()?.to_string_lossy().to_lowercase();
+    overrides
+        .iter()
+        .find(|(pattern, _)| {
+            let p = pattern.replace('\\', "/").to_lowercase();
+            let text = if p.contains('/') { &path_l } else { &name_l };
+            wildcard_match(p.as_bytes(), text.as_bytes())
+        })
+        .map(|(_, fmt)| fmt.as_str())
+}
+
+fn wildcard_match(p: &[u8], t: &[u8]) -> bool {
+    match p.split_first() {
+        None => t.is_empty(),
+        Some((b'*', rest)) => (0..=t.len()).any(|i| wildcard_match(rest, &t[i..])),
+        Some((&c, rest)) => t.first() == Some(&c) && wildcard_match(rest, &t[1..]),
+    }
+}
+
+enum ConvertOutcome {
+    Converted,
+    Skipped,
+    /// The re-encode doesn't fit the donor's data region, usually a format or mip-count mismatch
+    SizeMismatch {
+        format: String,
+        encoded: usize,
+        donor: usize,
+    },
+    /// The donor file no longer matches the fingerprint recorded in the index
+    StaleDonor,
+}
+
 fn convert_one(
     src_bntx: &Path,
     dst_bntx: &Path,
     donor: &BntxIndexEntry,
+    forced_format: Option<&str>,
     ultimate: &Path,
-    allow_resize: bool,
+    opts: ConvertOptions,
     _progress: &ProgressSink,
-) -> anyhow::Result<bool> {
-    let Some(fmt) = donor.ultimate_format.as_deref() else {
-        return Ok(false);
+) -> Result<ConvertOutcome, TexFailure> {
+    let resize = opts.resize;
+    let Some(fmt) = forced_format.or(donor.ultimate_format.as_deref()) else {
+        return Ok(ConvertOutcome::Skipped);
     };
     let donor_path = PathBuf::from(&donor.file_path);
     if !donor_path.is_file() {
-        return Ok(false);
+        return Ok(ConvertOutcome::Skipped);
+    }
+    // Offsets come from the index; splicing into an edited donor would corrupt the output
+    let donor_bytes = fs::read(&donor_path).reason(FailReason::Io)?;
+    if donor
+        .fingerprint
+        .is_some_and(|fp| fp != bntx::bytes_fingerprint(&donor_bytes))
+    {
+        return Ok(ConvertOutcome::StaleDonor);
     }
 
     let tmp_base = dst_bntx.parent().unwrap_or(Path::new(".")).join("_tmp");
-    fs::create_dir_all(&tmp_base)?;
+    fs::create_dir_all(&tmp_base).reason(FailReason::Io)?;
     let td = tempfile::Builder::new()
         .prefix("svza_tex_")
-        .tempdir_in(&tmp_base)?;
+        .tempdir_in(&tmp_base)
+        .reason(FailReason::Io)?;
     let decoded_bmp = td.path().join("decoded.bmp");
     let resized_bmp = td.path().join("resized.bmp");
     let encoded_bntx = td.path().join("encoded.bntx");
 
-    run_ultimate(ultimate, &[src_bntx, &decoded_bmp], None)?;
-    let (sw, sh, rgba) = bmp::read_bmp_rgba(&decoded_bmp)?;
+    run_ultimate(ultimate, &[src_bntx, &decoded_bmp], None, opts).reason(FailReason::Decode)?;
+    let decoded_bmp = locate_ultimate_output(&decoded_bmp).reason(FailReason::Decode)?;
+    let (sw, sh, rgba) = bmp::read_bmp_rgba(&decoded_bmp).reason(FailReason::ReadBmp)?;
     let (tw, th) = (donor.width, donor.height);
     let (bmp_in, rgba2) = if (sw, sh) != (tw, th) {
-        if !allow_resize {
-            return Ok(false);
+        if !resize.allow {
+            return Ok(ConvertOutcome::Skipped);
         }
+        check_resize_target(tw, th, resize.max_dim).reason(FailReason::Resize)?;
         let rgba2 = bmp::resize_rgba_bilinear(sw, sh, &rgba, tw, th);
-        bmp::write_bmp_rgba(&resized_bmp, tw, th, &rgba2)?;
+        bmp::write_bmp_rgba(&resized_bmp, tw, th, &rgba2).reason(FailReason::Resize)?;
         (resized_bmp.as_path(), rgba2)
     } else {
         (decoded_bmp.as_path(), rgba)
@@ -274,31 +910,78 @@ fn convert_one(
     if donor.no_mipmaps {
         extra.push("--no-mipmaps".to_string());
     }
-    run_ultimate(ultimate, &args, Some(&extra))?;
+    run_ultimate(ultimate, &args, Some(&extra), opts).reason(FailReason::Encode)?;
+    let encoded_bntx = locate_ultimate_output(&encoded_bntx).reason(FailReason::Encode)?;
 
-    let (enc_data, _enc_off, enc_len) = extract_tex_data(&encoded_bntx)?;
-    let donor_bytes = fs::read(&donor_path)?;
+    let (enc_data, _enc_off, enc_len) =
+        extract_tex_data(&encoded_bntx).reason(FailReason::Extract)?;
     let d_off = donor.base_offset;
     let d_len = donor.data_length;
     if d_off < 0 || d_len <= 0 {
-        return Ok(false);
+        return Ok(ConvertOutcome::Skipped);
     }
     let d_off = d_off as usize;
     let d_len = d_len as usize;
     if d_off + d_len > donor_bytes.len() {
-        return Ok(false);
+        return Ok(ConvertOutcome::Skipped);
     }
     if enc_len != d_len {
-        return Ok(false);
+        if forced_format.is_some() {
+            return Err(TexFailure::new(
+                FailReason::OverrideSize,
+                anyhow::anyhow!(
+                    "override format {fmt} encodes to {enc_len} bytes but the donor region is {d_len}"
+                ),
+            ));
+        }
+        return Ok(ConvertOutcome::SizeMismatch {
+            format: fmt.to_string(),
+            encoded: enc_len,
+            donor: d_len,
+        });
     }
 
     let mut out = donor_bytes;
     out[d_off..d_off + d_len].copy_from_slice(&enc_data);
-    atomic_write(dst_bntx, &out)?;
-    Ok(true)
+    atomic_write(dst_bntx, &out).reason(FailReason::Write)?;
+    Ok(ConvertOutcome::Converted)
 }
 
-fn run_ultimate(ultimate: &Path, args: &[&Path], extra: Option<&[String]>) -> anyhow::Result<()> {
+/// Returns `expected` if ultimate_tex_cli wrote it, else the newest file with the same extension
+/// in its folder (other CLI versions rename outputs), like `flatc_dump_json` does
+fn locate_ultimate_output(expected: &Path) -> anyhow::Result<PathBuf> {
+    if expected.is_file() {
+        return Ok(expected.to_path_buf());
+    }
+    let dir = expected.parent().unwrap_or(Path::new("."));
+    let ext = expected.extension();
+    let mut newest: Option<(std::time::SystemTime, PathBuf)> = None;
+    for e in fs::read_dir(dir)? {
+        let e = e?;
+        if !e.file_type()?.is_file() || e.path().extension() != ext {
+            continue;
+        }
+        let mtime = e.metadata()?.modified()?;
+        if newest.as_ref().is_none_or(|(t, _)| mtime > *t) {
+            newest = Some((mtime, e.path()));
+        }
+    }
+    newest.map(|(_, p)| p).ok_or_else(|| {
+        anyhow::anyhow!(
+            "ultimate_tex_cli exited ok but wrote no {:?} (nor any other .{} file) in {:?}",
+            expected.file_name().unwrap_or_default(),
+            ext.unwrap_or_default().to_string_lossy(),
+            dir
+        )
+    })
+}
+
+fn run_ultimate(
+    ultimate: &Path,
+    args: &[&Path],
+    extra: Option<&[String]>,
+    opts: ConvertOptions,
+) -> anyhow::Result<()> {
     let mut cmd = Command::new(ultimate);
     for a in args {
         cmd.arg(a);
@@ -308,24 +991,152 @@ fn run_ultimate(ultimate: &Path, args: &[&Path], extra: Option<&[String]>) -> an
             cmd.arg(e);
         }
     }
-    let out = cmd.output()?;
+    let out = {
+        let _slot = opts.tool_slots.acquire();
+        run_tool(&mut cmd, "ultimate_tex_cli", opts.profile)?
+    };
     if !out.status.success() {
-        anyhow::bail!(
-            "ultimate_tex_cli failed: {}\n{}",
-            out.status,
-            String::from_utf8_lossy(&out.stderr)
-        );
+        return Err(SvzaError::TextureToolFailed {
+            status: out.status.to_string(),
+            output: String::from_utf8_lossy(&out.stderr).to_string(),
+        }
+        .into());
     }
     Ok(())
 }
 
-fn atomic_write(dst: &Path, data: &[u8]) -> anyhow::Result<()> {
-    if let Some(parent) = dst.parent() {
-        fs::create_dir_all(parent)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index_entry(rel: &str, size: i32, format: Option<&str>) -> BntxIndexEntry {
+        BntxIndexEntry {
+            file_path: format!("/dump/{rel}"),
+            file_name: rel.rsplit('/').next().unwrap().to_string(),
+            width: size,
+            height: size,
+            mip_count: 1,
+            data_length: size * size,
+            base_offset: 0,
+            ultimate_format: format.map(str::to_string),
+            no_mipmaps: false,
+            fingerprint: None,
+        }
+    }
+
+    fn icon_donor_fixture() -> Vec<BntxIndexEntry> {
+        let icon = |pm: &str| format!("ik_pokemon/data/{pm}/{pm}_00_00/icon/{pm}_00_00_00.bntx");
+        vec![
+            index_entry(&icon("pm0001"), 128, Some("BC7Unorm")),
+            index_entry(&icon("pm0002"), 128, Some("BC7Unorm")),
+            index_entry(&icon("pm0003"), 256, Some("BC1Unorm")),
+            // bigger, but no known format
+            index_entry(&icon("pm0004"), 512, None),
+            // biggest, but not an icon
+            index_entry(
+                "ik_pokemon/data/pm0005/pm0005_00_00/tex/body.bntx",
+                1024,
+                Some("BC7Unorm"),
+            ),
+        ]
+    }
+
+    fn pick_icon(
+        strategy: IconDonorStrategy,
+        pinned: Option<&str>,
+    ) -> anyhow::Result<Option<BntxIndexEntry>> {
+        let (progress, _rx) = ProgressSink::new();
+        select_default_icon_donor(
+            &icon_donor_fixture(),
+            strategy,
+            pinned.map(Path::new),
+            Path::new("/dump"),
+            &progress,
+        )
+    }
+
+    fn picked_pm(strategy: IconDonorStrategy, pinned: Option<&str>) -> String {
+        let e = pick_icon(strategy, pinned).unwrap().unwrap();
+        e.file_name[..6].to_string()
+    }
+
+    #[test]
+    fn icon_donor_most_common_picks_the_commonest_shape_and_format() {
+        assert_eq!(picked_pm(IconDonorStrategy::MostCommon, None), "pm0001");
+    }
+
+    #[test]
+    fn icon_donor_largest_skips_non_icons_and_unknown_formats() {
+        assert_eq!(picked_pm(IconDonorStrategy::Largest, None), "pm0003");
+    }
+
+    #[test]
+    fn icon_donor_pinned_path_matches_relative_and_full_paths() {
+        let rel = "ik_pokemon/data/pm0002/pm0002_00_00/icon/pm0002_00_00_00.bntx";
+        assert_eq!(
+            picked_pm(IconDonorStrategy::PinnedPath, Some(rel)),
+            "pm0002"
+        );
+        assert_eq!(
+            picked_pm(IconDonorStrategy::PinnedPath, Some(&format!("/dump/{rel}"))),
+            "pm0002"
+        );
+        // a suffix of the indexed path is enough, whatever the separators and case
+        assert_eq!(
+            picked_pm(
+                IconDonorStrategy::PinnedPath,
+                Some("PM0002_00_00\\icon\\pm0002_00_00_00.bntx")
+            ),
+            "pm0002"
+        );
+    }
+
+    #[test]
+    fn icon_donor_pinned_path_without_a_match_is_an_error() {
+        let missing = "ik_pokemon/data/pm0009/pm0009_00_00/icon/pm0009_00_00_00.bntx";
+        assert!(pick_icon(IconDonorStrategy::PinnedPath, Some(missing)).is_err());
+        // indexed, but with no format to convert to
+        let unknown = "ik_pokemon/data/pm0004/pm0004_00_00/icon/pm0004_00_00_00.bntx";
+        assert!(pick_icon(IconDonorStrategy::PinnedPath, Some(unknown)).is_err());
+        assert!(pick_icon(IconDonorStrategy::PinnedPath, None).is_err());
+    }
+
+    #[test]
+    fn locate_ultimate_output_picks_the_newest_same_extension_file() {
+        let td = tempfile::tempdir().unwrap();
+        let write_at = |name: &str, secs: u64| {
+            let p = td.path().join(name);
+            fs::write(&p, b"x").unwrap();
+            fs::File::options()
+                .write(true)
+                .open(&p)
+                .unwrap()
+                .set_modified(std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs))
+                .unwrap();
+            p
+        };
+        let expected = td.path().join("tex.dds");
+
+        assert!(locate_ultimate_output(&expected).is_err());
+
+        write_at("stale.dds", 1_000);
+        let renamed = write_at("tex_out.dds", 2_000);
+        // newest of all, but not what the CLI was asked to write
+        write_at("tex.png", 3_000);
+        assert_eq!(locate_ultimate_output(&expected).unwrap(), renamed);
+
+        // the expected name wins whatever the mtimes
+        let exact = write_at("tex.dds", 500);
+        assert_eq!(locate_ultimate_output(&expected).unwrap(), exact);
+    }
+
+    #[test]
+    fn check_resize_target_bounds() {
+        assert!(check_resize_target(256, 256, 4096).is_ok());
+        assert!(check_resize_target(4096, 4096, 4096).is_ok());
+        assert!(check_resize_target(4097, 256, 4096).is_err());
+        assert!(check_resize_target(256, 65536, 4096).is_err());
+        assert!(check_resize_target(0, 256, 4096).is_err());
+        assert!(check_resize_target(256, -1, 4096).is_err());
     }
-    let tmp = PathBuf::from(format!("{}{}", dst.to_string_lossy(), ".tmp"));
-    fs::write(&tmp, data)?;
-    let _ = fs::remove_file(dst);
-    fs::rename(&tmp, dst)?;
-    Ok(())
 }
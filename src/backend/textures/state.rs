@@ -0,0 +1,192 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+/// Per-file mtime recorded after the last successful conversion, keyed by path relative to the
+/// converted `data/` dir; a file whose mtime still matches was not touched since and is skipped
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TexState {
+    /// Fingerprint of the conversion settings the entries were recorded under; see
+    /// [`TexState::load`]. States written before it existed load as 0 and are dropped
+    #[serde(default)]
+    pub settings: u64,
+    pub files: BTreeMap<String, u64>,
+    /// Data-region hash written by the last conversion, only kept with `--verify-hash`
+    #[serde(default)]
+    pub hashes: BTreeMap<String, u64>,
+    /// Files converted under a `--format-override`, which skip the structural check
+    #[serde(default)]
+    pub forced: BTreeMap<String, ForcedConversion>,
+}
+
+/// What a forced conversion wrote: the override format and the data-region hash of the output
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForcedConversion {
+    pub format: String,
+    pub hash: u64,
+}
+
+impl TexState {
+    /// Missing or unreadable state just means every file gets checked again, and so does a state
+    /// recorded under different `settings`: a file untouched since then may still need a new
+    /// format, size or donor
+    pub fn load(path: &Path, settings: u64) -> Self {
+        fs::read(path)
+            .ok()
+            .and_then(|b| serde_json::from_slice::<Self>(&b).ok())
+            .filter(|s| s.settings == settings)
+            .unwrap_or(Self {
+                settings,
+                ..Self::default()
+            })
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn unchanged(&self, rel: &str, file: &Path) -> bool {
+        match (self.files.get(rel), mtime_nanos(file)) {
+            (Some(&recorded), Some(now)) => recorded == now,
+            _ => false,
+        }
+    }
+
+    /// True only when `hash` matches the one recorded by our last conversion of `rel`. A file
+    /// without one (converted before `--verify-hash` was first used) can't be told apart from a
+    /// stale splice, so it is converted again and its hash recorded then.
+    pub fn check_hash(&self, rel: &str, hash: u64) -> bool {
+        self.hashes.get(rel) == Some(&hash)
+    }
+
+    pub fn record_hash(&mut self, rel: String, hash: u64) {
+        self.hashes.insert(rel, hash);
+    }
+
+    /// True when `rel` was last converted to `format` and still holds what that conversion wrote,
+    /// so encoding it again would only lose quality
+    pub fn forced_unchanged(&self, rel: &str, format: &str, hash: u64) -> bool {
+        self.forced
+            .get(rel)
+            .is_some_and(|f| f.format == format && f.hash == hash)
+    }
+
+    pub fn record_forced(&mut self, rel: String, format: &str, hash: u64) {
+        self.forced.insert(
+            rel,
+            ForcedConversion {
+                format: format.to_string(),
+                hash,
+            },
+        );
+    }
+
+    pub fn record(&mut self, rel: String, file: &Path) {
+        if let Some(m) = mtime_nanos(file) {
+            self.files.insert(rel, m);
+        }
+    }
+}
+
+pub fn default_state_path(out_root: &Path) -> PathBuf {
+    out_root.join("_cache").join("tex_state.json")
+}
+
+fn mtime_nanos(file: &Path) -> Option<u64> {
+    let m = fs::metadata(file).ok()?.modified().ok()?;
+    u64::try_from(m.duration_since(UNIX_EPOCH).ok()?.as_nanos()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_hash_without_recorded_hash_is_false() {
+        let state = TexState::default();
+        assert!(!state.check_hash("a/b.bntx", 42));
+        assert!(state.hashes.is_empty());
+    }
+
+    #[test]
+    fn check_hash_matches_only_the_recorded_hash() {
+        let mut state = TexState::default();
+        state.record_hash("a/b.bntx".to_string(), 42);
+        assert!(state.check_hash("a/b.bntx", 42));
+        assert!(!state.check_hash("a/b.bntx", 43));
+        assert!(!state.check_hash("a/c.bntx", 42));
+    }
+
+    #[test]
+    fn unchanged_tracks_the_recorded_mtime() {
+        let td = tempfile::tempdir().unwrap();
+        let file = td.path().join("a.bntx");
+        fs::write(&file, b"old").unwrap();
+
+        let mut state = TexState::default();
+        assert!(!state.unchanged("a.bntx", &file));
+        state.record("a.bntx".to_string(), &file);
+        assert!(state.unchanged("a.bntx", &file));
+        assert!(!state.unchanged("b.bntx", &file));
+
+        fs::File::options()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(UNIX_EPOCH + std::time::Duration::from_secs(1))
+            .unwrap();
+        assert!(!state.unchanged("a.bntx", &file));
+        assert!(!state.unchanged("a.bntx", &td.path().join("missing.bntx")));
+    }
+
+    #[test]
+    fn forced_unchanged_needs_the_same_format_and_hash() {
+        let mut state = TexState::default();
+        assert!(!state.forced_unchanged("a.bntx", "BC7Unorm", 42));
+        state.record_forced("a.bntx".to_string(), "BC7Unorm", 42);
+        assert!(state.forced_unchanged("a.bntx", "BC7Unorm", 42));
+        assert!(!state.forced_unchanged("a.bntx", "BC7Unorm", 43));
+        assert!(!state.forced_unchanged("a.bntx", "BC1Unorm", 42));
+        assert!(!state.forced_unchanged("b.bntx", "BC7Unorm", 42));
+    }
+
+    #[test]
+    fn record_skips_missing_files() {
+        let td = tempfile::tempdir().unwrap();
+        let mut state = TexState::default();
+        state.record("missing.bntx".to_string(), &td.path().join("missing.bntx"));
+        assert!(state.files.is_empty());
+    }
+
+    #[test]
+    fn load_drops_state_from_other_settings() {
+        let td = tempfile::tempdir().unwrap();
+        let path = default_state_path(td.path());
+        let mut state = TexState {
+            settings: 7,
+            ..TexState::default()
+        };
+        state.files.insert("a.bntx".to_string(), 1);
+        state.record_hash("a.bntx".to_string(), 2);
+        state.save(&path).unwrap();
+
+        let same = TexState::load(&path, 7);
+        assert_eq!(same.files.len(), 1);
+        assert_eq!(same.hashes.len(), 1);
+
+        let other = TexState::load(&path, 8);
+        assert_eq!(other.settings, 8);
+        assert!(other.files.is_empty());
+        assert!(other.hashes.is_empty());
+
+        assert_eq!(TexState::load(&td.path().join("none.json"), 8).settings, 8);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::paths::{parse_pm_variant_name, pm_variant_dir_name};
 use crate::progress::ProgressSink;
 use std::{
     fs,
@@ -10,20 +11,17 @@ pub fn overlay_from_donor(
     out_pm_dir: &Path,
     progress: &ProgressSink,
 ) -> anyhow::Result<()> {
-    let target_pm_variant = out_pm_dir
-        .file_name()
-        .ok_or_else(|| anyhow::anyhow!("unexpected pm dir: {out_pm_dir:?}"))?
-        .to_string_lossy()
-        .to_string();
-    let donor_pm = donor_pm_variant
-        .split_once('_')
-        .map(|(a, _)| a)
-        .unwrap_or(donor_pm_variant);
+    let target_pm_variant = pm_variant_dir_name(out_pm_dir)?;
+    let donor_pm = parse_pm_variant_name(donor_pm_variant)
+        .map(|p| p.pm)
+        .ok_or_else(|| {
+            anyhow::anyhow!("expected a pmXXXX_YY_ZZ donor, got {donor_pm_variant:?}")
+        })?;
 
     let donor_dir = za_dump
         .join("ik_pokemon")
         .join("data")
-        .join(donor_pm)
+        .join(&donor_pm)
         .join(donor_pm_variant);
     if !donor_dir.is_dir() {
         anyhow::bail!("ZA base-config donor folder missing: {donor_dir:?}");
@@ -53,10 +51,9 @@ pub fn overlay_from_donor(
         let mut dst: Option<PathBuf> = None;
         if name == format!("{donor_pm_variant}.tracn") {
             dst = Some(out_pm_dir.join(format!("{target_pm_variant}.tracn")));
-        } else if name.starts_with(&format!("{donor_pm_variant}_base.")) {
-            let tail = &name[donor_pm_variant.len()..];
-            dst = Some(out_pm_dir.join(format!("{target_pm_variant}{tail}")));
-        } else if name.starts_with(&format!("{donor_pm_variant}_")) && name.ends_with(".trcrv") {
+        } else if name.starts_with(&format!("{donor_pm_variant}_base."))
+            || (name.starts_with(&format!("{donor_pm_variant}_")) && name.ends_with(".trcrv"))
+        {
             let tail = &name[donor_pm_variant.len()..];
             dst = Some(out_pm_dir.join(format!("{target_pm_variant}{tail}")));
         }
@@ -110,10 +107,13 @@ pub fn overlay_from_donor(
         let _ = fs::write(&p, replaced);
     }
 
-    progress.info(format!(
-        "za base overlay: donor={} -> {}",
-        donor_pm_variant, target_pm_variant
-    ));
+    progress.detail(
+        1,
+        format!(
+            "za base overlay: donor={} -> {}",
+            donor_pm_variant, target_pm_variant
+        ),
+    );
     Ok(())
 }
 
@@ -0,0 +1,132 @@
+use crate::{
+    backend,
+    cancel::CancelToken,
+    config::AppConfig,
+    headless_line,
+    pool::{TexLimits, WorkPool},
+    progress::{ProgressEvent, ProgressSink},
+};
+use serde::Deserialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// `--batch` manifest; relative paths resolve against the manifest's folder
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchManifest {
+    pub entries: Vec<BatchEntry>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchEntry {
+    pub template: PathBuf,
+    pub out_root: PathBuf,
+}
+
+/// Totals over every entry of a `--batch` run
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BatchOutcome {
+    pub failed: usize,
+    /// Warnings emitted by the entries that finished
+    pub warnings: usize,
+}
+
+/// Runs each manifest entry with its template/out_root over `base`, at most `pool.jobs()` at once
+/// Entries share one set of `--tex-jobs` slots.
+pub fn run_batch(
+    base: &AppConfig,
+    manifest_path: &Path,
+    pool: &WorkPool,
+) -> anyhow::Result<BatchOutcome> {
+    let text = fs::read_to_string(manifest_path)
+        .map_err(|e| anyhow::anyhow!("failed to read batch manifest {manifest_path:?}: {e}"))?;
+    let manifest: BatchManifest = serde_json::from_str(&text)
+        .map_err(|e| anyhow::anyhow!("failed to parse batch manifest {manifest_path:?}: {e}"))?;
+    let root = manifest_path.parent().unwrap_or(Path::new("."));
+    let entries = manifest
+        .entries
+        .into_iter()
+        .map(|e| BatchEntry {
+            template: root.join(e.template),
+            out_root: root.join(e.out_root),
+        })
+        .collect::<Vec<_>>();
+
+    let total = entries.len();
+    let tex_limits = TexLimits::for_batch(base);
+    let results = pool.map(&entries, |i, entry| {
+        run_entry(
+            base,
+            i,
+            entry,
+            &tex_limits,
+            &format!("[batch {}/{total}]", i + 1),
+        )
+    });
+
+    let mut out = BatchOutcome::default();
+    eprintln!("[batch] results:");
+    for (entry, res) in entries.iter().zip(results) {
+        match res {
+            Ok((summary, warns)) => {
+                out.warnings += warns;
+                eprintln!("  ok    {:?}: {summary}", entry.out_root);
+            }
+            Err(e) => {
+                out.failed += 1;
+                eprintln!("  FAIL  {:?}: {e}", entry.out_root);
+            }
+        }
+    }
+    eprintln!(
+        "[batch] {} ok, {} failed, {} warning(s)",
+        total - out.failed,
+        out.failed,
+        out.warnings
+    );
+    Ok(out)
+}
+
+fn run_entry(
+    base: &AppConfig,
+    index: usize,
+    entry: &BatchEntry,
+    tex_limits: &TexLimits,
+    prefix: &str,
+) -> Result<(String, usize), String> {
+    let mut cfg = base.clone();
+    cfg.template_path = Some(entry.template.clone());
+    cfg.out_root = Some(entry.out_root.clone());
+    cfg.legacy_mode = false;
+    // A shared --report-dir would have every entry overwrite (and diff against) the others' reports
+    if let Some(dir) = &base.report_dir {
+        let name = entry
+            .out_root
+            .file_name()
+            .map_or_else(|| "entry".into(), |n| n.to_string_lossy());
+        cfg.report_dir = Some(dir.join(format!("{:03}_{name}", index + 1)));
+    }
+    if let Err(e) = fs::create_dir_all(&entry.out_root) {
+        return Err(format!("create {:?}: {e}", entry.out_root));
+    }
+
+    let (sink, rx) = ProgressSink::new();
+    let prefix_owned = prefix.to_string();
+    let printer = std::thread::spawn(move || {
+        let mut warns = 0usize;
+        while let Ok(ev) = rx.recv() {
+            if matches!(ev, ProgressEvent::Warn { .. }) {
+                warns += 1;
+            }
+            if let Some(line) = headless_line(&ev) {
+                eprintln!("{prefix_owned} {line}");
+            }
+        }
+        warns
+    });
+    let res = backend::run_with_limits(&cfg, sink, CancelToken::new(), tex_limits);
+    let warns = printer.join().unwrap_or_default();
+    res.map(|s| (s.to_string(), warns))
+        .map_err(|e| format!("{e:#}"))
+}
@@ -7,7 +7,7 @@ use clap::Parser;
 #[derive(Debug, Parser)]
 struct Args {
     /// One or more `poke_resource_table.trpmcatalog` paths
-    #[arg(required = true)]
+    #[arg(required_unless_present = "from_json")]
     catalogs: Vec<PathBuf>,
 
     /// Optional root to validate that referenced files exist
@@ -18,11 +18,33 @@ struct Args {
     /// Print per-entry details (can be noisy)
     #[arg(long)]
     verbose: bool,
+
+    /// Write the full catalog as editable JSON to this path (single catalog only)
+    #[arg(long, conflicts_with = "from_json")]
+    to_json: Option<PathBuf>,
+
+    /// Read a JSON catalog (as written by `--to-json`) and write it back as a `.trpmcatalog` to `--out`
+    #[arg(long, requires = "out")]
+    from_json: Option<PathBuf>,
+
+    /// Output `.trpmcatalog` path for `--from-json`
+    #[arg(long)]
+    out: Option<PathBuf>,
 }
 
 fn main() -> anyhow::Result<()> {
     let args = Args::parse();
 
+    if let (Some(json), Some(out)) = (&args.from_json, &args.out) {
+        return import_json(json, out);
+    }
+    if let Some(json) = &args.to_json {
+        let [cat] = args.catalogs.as_slice() else {
+            anyhow::bail!("--to-json takes exactly one catalog");
+        };
+        return export_json(cat, json);
+    }
+
     for cat in &args.catalogs {
         let data_root = args.data_root.clone().or_else(|| default_data_root(cat));
 
@@ -39,6 +61,34 @@ fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+fn export_json(catalog: &Path, json: &Path) -> anyhow::Result<()> {
+    let b = std::fs::read(catalog).with_context(|| format!("read {}", catalog.display()))?;
+    let doc = svza::fb::trpmcatalog::read_doc(b).context("parse trpmcatalog")?;
+    let text = serde_json::to_string_pretty(&doc)?;
+    std::fs::write(json, text + "\n").with_context(|| format!("write {}", json.display()))?;
+    println!("wrote {} ({} entries)", json.display(), doc.entries.len());
+    Ok(())
+}
+
+fn import_json(json: &Path, out: &Path) -> anyhow::Result<()> {
+    let text = std::fs::read_to_string(json).with_context(|| format!("read {}", json.display()))?;
+    let doc: svza::fb::trpmcatalog::CatalogDoc =
+        serde_json::from_str(&text).context("parse catalog json")?;
+    let bin = svza::fb::trpmcatalog::write_doc(&doc)?;
+    // make sure what we wrote reads back before replacing anything
+    let back = svza::fb::trpmcatalog::read_doc(bin.clone()).context("re-read written catalog")?;
+    if back.entries.len() != doc.entries.len() {
+        anyhow::bail!(
+            "written catalog has {} entries, expected {}",
+            back.entries.len(),
+            doc.entries.len()
+        );
+    }
+    std::fs::write(out, bin).with_context(|| format!("write {}", out.display()))?;
+    println!("wrote {} ({} entries)", out.display(), doc.entries.len());
+    Ok(())
+}
+
 fn default_data_root(catalog: &Path) -> Option<PathBuf> {
     // .../ik_pokemon/catalog/catalog/poke_resource_table.trpmcatalog
     let p = catalog.parent()?.parent()?.parent()?;
@@ -0,0 +1,166 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use anyhow::Context as _;
+use clap::Parser;
+use serde::Serialize;
+use svza::{
+    config::{parse_look_at_groups, AppConfig},
+    fb::{raw::FbBuf, tralk::NoLookAtParams},
+};
+
+#[derive(Debug, Parser)]
+struct Args {
+    /// Input `*_base.tralk` path
+    #[arg(required = true)]
+    tralk: PathBuf,
+
+    /// Emit JSON instead of the text listing
+    #[arg(long, default_value_t = false)]
+    json: bool,
+
+    /// Only emit groups whose name contains this substring (case-insensitive)
+    #[arg(long)]
+    filter: Option<String>,
+
+    /// Look-at groups and the values the patch writes to them, as inline JSON or a `.json` file
+    /// (defaults to the saved config's groups, `head` alone when it has none)
+    #[arg(long, value_parser = parse_look_at_groups)]
+    look_at_groups: Option<BTreeMap<String, NoLookAtParams>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct TralkDoc {
+    /// Bytes 4..8 when the file carries a flatbuffers file_identifier
+    file_identifier: Option<String>,
+    group_count: usize,
+    groups: Vec<GroupDoc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct GroupDoc {
+    name: String,
+    look_at_type: u32,
+    /// Field 23
+    rotation_weights: Vec<f32>,
+    /// Fields 8..=13, the angle limits the no-head patch flattens
+    limits: Vec<Option<f32>>,
+    /// Field 14, meaning unknown; raw bits
+    field_14: Option<u32>,
+    /// Field 15 (enableTurningClamp)
+    enable_turning_clamp: Option<u8>,
+    /// Whether the group already carries the values the look-at patch writes to it; false for
+    /// groups the patch leaves alone
+    no_head: bool,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    let b = std::fs::read(&args.tralk).with_context(|| format!("read {}", args.tralk.display()))?;
+    let look_at = match args.look_at_groups {
+        Some(g) => g,
+        None => AppConfig::load_or_default()?.look_at_groups(),
+    };
+    let mut doc = read_tralk(b, &look_at).context("parse TRALK")?;
+
+    if let Some(f) = args
+        .filter
+        .as_ref()
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+    {
+        let f_l = f.to_ascii_lowercase();
+        doc.groups
+            .retain(|g| g.name.to_ascii_lowercase().contains(&f_l));
+        doc.group_count = doc.groups.len();
+    }
+
+    if args.json {
+        print!("{}", serde_json::to_string_pretty(&doc)? + "\n");
+        return Ok(());
+    }
+    if let Some(id) = &doc.file_identifier {
+        println!("file_identifier: {id}");
+    }
+    println!("groups: {}", doc.group_count);
+    for g in &doc.groups {
+        println!(
+            "{:?} look_at_type={} no_head={}",
+            g.name, g.look_at_type, g.no_head
+        );
+        println!("  rotation_weights: {:?}", g.rotation_weights);
+        println!("  limits[8..=13]:   {:?}", g.limits);
+        println!(
+            "  field_14: {:?}  enable_turning_clamp: {:?}",
+            g.field_14, g.enable_turning_clamp
+        );
+    }
+    Ok(())
+}
+
+fn read_tralk(
+    buf: Vec<u8>,
+    look_at: &BTreeMap<String, NoLookAtParams>,
+) -> anyhow::Result<TralkDoc> {
+    let fb = FbBuf::new(buf);
+    let root = fb.root_table_pos()?;
+    let root_vt = fb.vtable_pos(root)?;
+
+    let groups_pos = fb
+        .table_field_vec_of_tables(root, root_vt, 4)?
+        .unwrap_or_default();
+
+    let mut groups = Vec::with_capacity(groups_pos.len());
+    for gpos in groups_pos {
+        let gvt = fb.vtable_pos(gpos)?;
+        let name = fb.table_field_string(gpos, gvt, 0)?.unwrap_or_default();
+        let look_at_type = fb.table_field_scalar_u32(gpos, gvt, 4)?.unwrap_or(0);
+
+        let mut rotation_weights = Vec::new();
+        if let Some(vec_pos) = fb.table_field_vec_pos(gpos, gvt, 23)? {
+            let n = fb.read_u32(vec_pos)? as usize;
+            for i in 0..n {
+                rotation_weights.push(f32::from_bits(fb.read_u32(vec_pos + 4 + i * 4)?));
+            }
+        }
+
+        let mut limits = Vec::with_capacity(6);
+        for field in 8..=13 {
+            let v = match fb.table_field_loc(gpos, gvt, field)? {
+                Some(loc) => Some(f32::from_bits(fb.read_u32(loc)?)),
+                None => None,
+            };
+            limits.push(v);
+        }
+        let field_14 = match fb.table_field_loc(gpos, gvt, 14)? {
+            Some(loc) => Some(fb.read_u32(loc)?),
+            None => None,
+        };
+        let enable_turning_clamp = fb.table_field_scalar_u8(gpos, gvt, 15)?;
+
+        // Same test the patch uses to count a group as already patched
+        let no_head = look_at_type == 0
+            && look_at.get(&name).is_some_and(|p| {
+                rotation_weights.iter().all(|&w| w == p.rotation_weight)
+                    && limits.iter().flatten().all(|&v| v == p.limit)
+                    && enable_turning_clamp.is_none_or(|c| (c != 0) == p.turning_clamp)
+            });
+
+        groups.push(GroupDoc {
+            name,
+            look_at_type,
+            rotation_weights,
+            limits,
+            field_14,
+            enable_turning_clamp,
+            no_head,
+        });
+    }
+
+    Ok(TralkDoc {
+        file_identifier: fb
+            .file_identifier()
+            .map(|id| String::from_utf8_lossy(&id).to_string()),
+        group_count: groups.len(),
+        groups,
+    })
+}
@@ -3,7 +3,7 @@ use std::sync::{
     Arc,
 };
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct CancelToken {
     canceled: Arc<AtomicBool>,
 }
@@ -1,7 +1,44 @@
+use crate::fb::tralk::NoLookAtParams;
 use clap::Parser;
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
-use std::{fs, path::PathBuf};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Which `.bntx` files the texture pass collects
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+pub enum TextureScope {
+    #[default]
+    All,
+    /// Only menu icons (`/icon/`, `*_00.bntx`, `*_00_big.bntx`)
+    IconsOnly,
+}
+
+/// How the fallback icon donor is chosen when no index entry matches a converted icon
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+pub enum IconDonorStrategy {
+    /// The most common (size, format, mips) profile among ZA icons
+    #[default]
+    MostCommon,
+    /// The ZA icon with the most pixels
+    Largest,
+    /// The donor bntx given by `icon_donor_path`
+    PinnedPath,
+}
+
+/// Where game files go inside the output root
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+pub enum OutputLayout {
+    /// Straight under the output root (`ik_pokemon/`, `param_chr/`, `avalon/`)
+    #[default]
+    Raw,
+    /// Under `<title_id>/romfs/`, ready to drop into an emulator or Atmosphere mod folder
+    #[value(name = "layeredfs")]
+    LayeredFs,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
@@ -12,14 +49,81 @@ pub struct AppConfig {
     pub ultimate_tex_cli: Option<PathBuf>,
     pub flatc: Option<PathBuf>,
     pub pknx_personal_dir: Option<PathBuf>,
+    /// Extra `data/`-style roots (`<root>/pmXXXX/pmXXXX_YY_ZZ`) searched in order before the SV dump
+    /// A file present in an overlay wins over the dump's copy
+    pub overlay_data_roots: Vec<PathBuf>,
 
     pub language: String,
+    /// Display name for species without a name in the message tables
+    /// `{species}` is replaced by the number; `{species:05}` zero-pads it to 5 digits
+    pub species_name_format: String,
+    /// Extra languages for the names report; when set, each mon gets a `names: {lang: name}` map
+    pub report_languages: Vec<String>,
 
     pub texture_convert: bool,
     pub texture_allow_resize: bool,
+    /// Textures whose donor is wider or taller than this are failed instead of resized
+    pub max_resize_dim: u32,
+    /// Skip `.bntx` files whose mtime matches the one recorded after their last successful
+    /// conversion (`Output/_cache/tex_state.json`); the state is dropped whenever the format
+    /// overrides, format map, resize limits or donors differ from the run that wrote it
+    pub texture_incremental: bool,
+    /// Trust `already_converted` only when the texture data still hashes to what the last
+    /// conversion wrote; catches stale splices the structural check misses. Files with no
+    /// recorded hash yet are converted again so one gets recorded
+    pub verify_hash: bool,
+    /// Stop the texture phase on the first failed file instead of counting it and moving on
+    pub fail_fast: bool,
+    pub texture_scope: TextureScope,
+    pub icon_donor_strategy: IconDonorStrategy,
+    /// Donor bntx for `PinnedPath`; absolute, or relative to the ZA dump
+    pub icon_donor_path: Option<PathBuf>,
+    /// JSON map of extra BNTX format codes to ultimate_tex names (`{ "0x21": "BC7RgbaUnorm" }`)
+    pub tex_format_map: Option<PathBuf>,
+    /// Personal array location relative to both the ZA dump and the output root
+    pub personal_array_path: String,
+    /// Fail the param phase when a target's donor id has no entry, instead of warning
+    pub strict_param: bool,
+    /// Dump every built param/personal bin back through flatc and bail if it doesn't match
+    pub verify_flatc: bool,
+    /// Fail the run when the SV catalog references pm_variants whose assets aren't on disk
+    pub fail_on_missing_assets: bool,
+    /// Concurrency cap for parallel phases; 0 = one per CPU, 1 = strictly sequential
+    pub jobs: usize,
+    /// Max concurrent ultimate_tex_cli processes in the texture phase; 0 = one per `jobs` worker
+    /// Workers past the cap keep doing file IO and donor lookups while they wait for a slot.
+    /// Under `--batch` the cap is shared by every entry, each converting one file at a time.
+    pub tex_jobs: usize,
+    /// `species[:form[:gender]]` tokens; when set, only matching selected keys are converted
+    /// The species part may be a range (`25-30`)
+    pub only_species: Vec<String>,
+    /// `species[:form[:gender]]` tokens removed from the selection (applied after `only_species`)
+    pub exclude_species: Vec<String>,
+    /// `species[:form[:gender]]` tokens whose entries are deleted from the patched ZA catalog
+    /// Matching keys are also dropped from the selection, so nothing else is written for them
+    pub remove_species: Vec<String>,
+    /// Where reports are written; `None` keeps them in `<out_root>/_report`
+    pub report_dir: Option<PathBuf>,
+    /// `LayeredFs` nests the game files under `<layeredfs_title_id>/romfs/`; reports and
+    /// `_cache` stay at the output root either way
+    pub output_layout: OutputLayout,
+    /// 16-hex-digit title id used by `LayeredFs`
+    pub layeredfs_title_id: Option<String>,
+    /// `(pattern, format)` pairs forcing the ultimate_tex_cli `--format` for matching source textures
+    /// Patterns use `*` wildcards and match the file name, or the full path if they contain `/`
+    /// The donor still decides the splice geometry; first matching pattern wins. What each forced
+    /// conversion wrote is kept in `tex_state.json`, so unchanged files aren't re-encoded
+    pub format_overrides: Vec<(String, String)>,
     pub use_za_base_config: bool,
     pub za_base_donor_pm_variant: String,
     pub no_head_look_at: bool,
+    /// Also add a selected mon's other-gender SV catalog entries that share its model
+    pub mirror_genders: bool,
+    /// Per-group look-at params for `no_head_look_at`, keyed by JointRotation group name
+    /// Empty means just `head` with the default (fully disabled) params
+    pub look_at_groups: BTreeMap<String, NoLookAtParams>,
+    /// SV-style look-at disable: leave the SV `.tralk` on disk (tracn refs are still stripped)
+    pub keep_tralk: bool,
 
     /// When enabled, do not process mons whose (species,form,gender) key already exists in ZA's catalog
     /// When disabled, process them anyway (useful for ReZAifying an existing mon to debug animation/config issues)
@@ -28,9 +132,55 @@ pub struct AppConfig {
     /// Show legacy toggles/settings UI. New workflow uses templates + donor assignments instead
     pub legacy_mode: bool,
 
+    /// Donor template to run with instead of the GUI's autosave template
+    pub template_path: Option<PathBuf>,
+
     /// When enabled, write debugging reports under `Output/_report`
     pub generate_reports: bool,
 
+    /// Only compute the selection and re-patch the ZA catalog (plus the names report)
+    /// Skips copying pm packages, param/personal arrays and textures
+    pub catalog_only: bool,
+
+    /// Per-phase toggles; skip a phase while leaving its tool paths configured
+    pub skip_textures: bool,
+    pub skip_param: bool,
+    pub skip_personal: bool,
+    pub skip_catalog: bool,
+
+    /// After a run, bundle everything known about this species into `Output/_report/diag_<species>.json`
+    pub diagnostic_species: Option<u16>,
+
+    /// Keep the flatc JSON (dumped source + patched output) under `Output/_report/flatc/<name>`
+    pub keep_intermediates: bool,
+
+    /// Recopy pm package files whose source changed (size, mtime or winning root) since it was
+    /// copied, as recorded in `Output/_cache/copy_state.json`; files with no record are recopied
+    /// once. When disabled, any existing output file is kept as-is
+    pub refresh_changed: bool,
+
+    /// Follow symlinks when walking the dumps (pm copy, bntx index, catalog and hkx lookup)
+    /// Off by default: a link into another tree can pull in duplicate or unrelated files.
+    /// Symlink loops are detected by walkdir and skipped.
+    pub follow_symlinks: bool,
+
+    /// After patching the catalog, check each output `.trpokecfg` exists and only names its own pm_variant
+    pub validate_pokecfg: bool,
+
+    /// Decode monsname strings strictly: invalid UTF-16 fails the names report instead of
+    /// turning into U+FFFD. Meant for checking a new dump's decryption, not everyday runs
+    pub strict_names: bool,
+
+    /// Fail the run instead of warning when the SV and ZA dump paths look swapped; headless
+    /// runs also exit with code 2 when any warning was emitted
+    pub strict: bool,
+
+    /// Time every flatc / ultimate_tex_cli call and write the totals to `_report/profile.json`
+    pub profile: bool,
+
+    /// 0 = summary lines only, 1 = per-variant lines, 2 = per-file lines
+    pub verbosity: u8,
+
     pub donor_dev: u32,
 }
 
@@ -43,21 +193,104 @@ impl Default for AppConfig {
             ultimate_tex_cli: None,
             flatc: None,
             pknx_personal_dir: None,
+            overlay_data_roots: Vec::new(),
             language: "English".to_string(),
+            species_name_format: "#{species:05}".to_string(),
+            report_languages: Vec::new(),
             texture_convert: false,
             texture_allow_resize: true,
+            max_resize_dim: 4096,
+            texture_incremental: true,
+            verify_hash: false,
+            fail_fast: false,
+            texture_scope: TextureScope::All,
+            icon_donor_strategy: IconDonorStrategy::MostCommon,
+            icon_donor_path: None,
+            tex_format_map: None,
+            personal_array_path: "avalon/data/personal_array.bin".to_string(),
+            strict_param: false,
+            verify_flatc: false,
+            fail_on_missing_assets: false,
+            jobs: 1,
+            tex_jobs: 0,
+            only_species: Vec::new(),
+            exclude_species: Vec::new(),
+            remove_species: Vec::new(),
+            report_dir: None,
+            output_layout: OutputLayout::Raw,
+            layeredfs_title_id: None,
+            format_overrides: Vec::new(),
             use_za_base_config: false,
             za_base_donor_pm_variant: "pm0866_00_00".to_string(),
             no_head_look_at: false,
+            mirror_genders: false,
+            look_at_groups: BTreeMap::new(),
+            keep_tralk: false,
             skip_pokemon_already_in_za: true,
             legacy_mode: false,
+            template_path: None,
             generate_reports: true,
+            catalog_only: false,
+            skip_textures: false,
+            skip_param: false,
+            skip_personal: false,
+            skip_catalog: false,
+            diagnostic_species: None,
+            keep_intermediates: false,
+            refresh_changed: false,
+            follow_symlinks: false,
+            validate_pokecfg: false,
+            strict_names: false,
+            strict: false,
+            profile: false,
+            verbosity: 0,
             donor_dev: 866,
         }
     }
 }
 
 impl AppConfig {
+    /// Resolved report folder: `report_dir` (relative to the current directory) or `<out_root>/_report`
+    pub fn report_dir(&self, out_root: &Path) -> PathBuf {
+        match &self.report_dir {
+            Some(dir) => crate::paths::resolve_out_root(dir).unwrap_or_else(|_| dir.clone()),
+            None => out_root.join("_report"),
+        }
+    }
+
+    /// Root the game files are written under: `out_root` itself, or `<out_root>/<title_id>/romfs`
+    pub fn game_root(&self, out_root: &Path) -> anyhow::Result<PathBuf> {
+        match self.output_layout {
+            OutputLayout::Raw => Ok(out_root.to_path_buf()),
+            OutputLayout::LayeredFs => {
+                let id = self.layeredfs_title_id.as_deref().map(str::trim);
+                let Some(id) = id.filter(|id| !id.is_empty()) else {
+                    anyhow::bail!("layeredfs output needs a title id (pass --layeredfs-title-id)");
+                };
+                if id.len() != 16 || !id.chars().all(|c| c.is_ascii_hexdigit()) {
+                    anyhow::bail!("layeredfs title id must be 16 hex digits, got {id:?}");
+                }
+                Ok(out_root.join(id.to_ascii_uppercase()).join("romfs"))
+            }
+        }
+    }
+
+    /// `rel` (a `/`-separated game path like `ik_pokemon/data`) under [`Self::game_root`]
+    pub fn out_path(&self, out_root: &Path, rel: &str) -> anyhow::Result<PathBuf> {
+        let mut p = self.game_root(out_root)?;
+        p.extend(rel.split('/').filter(|s| !s.is_empty()));
+        Ok(p)
+    }
+
+    /// Groups the no-head look-at patch applies to; `head` alone when none are configured
+    pub fn look_at_groups(&self) -> BTreeMap<String, NoLookAtParams> {
+        if self.look_at_groups.is_empty() {
+            BTreeMap::from([("head".to_string(), NoLookAtParams::default())])
+        } else {
+            self.look_at_groups.clone()
+        }
+    }
+
     pub fn load_or_default() -> anyhow::Result<Self> {
         let path = config_path()?;
         if !path.exists() {
@@ -97,12 +330,84 @@ impl AppConfig {
         if let Some(p) = &args.pknx_personal_dir {
             self.pknx_personal_dir = Some(p.clone());
         }
+        if !args.overlay_data_root.is_empty() {
+            self.overlay_data_roots = args.overlay_data_root.clone();
+        }
         if args.texture_convert {
             self.texture_convert = true;
         }
         if args.no_texture_resize {
             self.texture_allow_resize = false;
         }
+        if let Some(v) = args.max_resize_dim {
+            self.max_resize_dim = v;
+        }
+        if let Some(v) = args.texture_incremental {
+            self.texture_incremental = v;
+        }
+        if args.verify_hash {
+            self.verify_hash = true;
+        }
+        if args.fail_fast {
+            self.fail_fast = true;
+        }
+        if let Some(v) = args.texture_scope {
+            self.texture_scope = v;
+        }
+        if let Some(v) = args.icon_donor_strategy {
+            self.icon_donor_strategy = v;
+        }
+        if let Some(p) = &args.icon_donor {
+            self.icon_donor_path = Some(p.clone());
+            if args.icon_donor_strategy.is_none() {
+                self.icon_donor_strategy = IconDonorStrategy::PinnedPath;
+            }
+        }
+        if let Some(p) = &args.tex_format_map {
+            self.tex_format_map = Some(p.clone());
+        }
+        if let Some(rel) = &args.personal_array_path {
+            self.personal_array_path = rel.clone();
+        }
+        if args.strict_param {
+            self.strict_param = true;
+        }
+        if args.verify_flatc {
+            self.verify_flatc = true;
+        }
+        if args.fail_on_missing_assets {
+            self.fail_on_missing_assets = true;
+        }
+        if let Some(n) = args.jobs {
+            self.jobs = n;
+        }
+        if let Some(n) = args.tex_jobs {
+            self.tex_jobs = n;
+        }
+        if !args.only_species.is_empty() {
+            self.only_species = args.only_species.clone();
+        }
+        if !args.exclude_species.is_empty() {
+            self.exclude_species = args.exclude_species.clone();
+        }
+        if !args.remove_species.is_empty() {
+            self.remove_species = args.remove_species.clone();
+        }
+        if let Some(p) = &args.report_dir {
+            self.report_dir = Some(p.clone());
+        }
+        if let Some(v) = args.output_layout {
+            self.output_layout = v;
+        }
+        if let Some(id) = &args.layeredfs_title_id {
+            self.layeredfs_title_id = Some(id.clone());
+            if args.output_layout.is_none() {
+                self.output_layout = OutputLayout::LayeredFs;
+            }
+        }
+        if !args.format_override.is_empty() {
+            self.format_overrides = args.format_override.clone();
+        }
         if args.use_za_base_config {
             self.use_za_base_config = true;
         }
@@ -112,11 +417,67 @@ impl AppConfig {
         if args.no_head_look_at {
             self.no_head_look_at = true;
         }
+        if args.mirror_genders {
+            self.mirror_genders = true;
+        }
+        if let Some(groups) = &args.look_at_groups {
+            self.look_at_groups = groups.clone();
+        }
+        if args.keep_tralk {
+            self.keep_tralk = true;
+        }
         // This is an explicit toggle (defaults true); apply unconditionally so passing `--skip-pokemon-already-in-za false`
         // works as expected
         self.skip_pokemon_already_in_za = args.skip_pokemon_already_in_za;
         self.legacy_mode = args.legacy_mode;
+        if let Some(p) = &args.template {
+            // an explicit template only makes sense in the template flow
+            self.template_path = Some(p.clone());
+            self.legacy_mode = false;
+        }
         self.generate_reports = args.generate_reports;
+        if args.catalog_only {
+            self.catalog_only = true;
+        }
+        if args.skip_textures {
+            self.skip_textures = true;
+        }
+        if args.skip_param {
+            self.skip_param = true;
+        }
+        if args.skip_personal {
+            self.skip_personal = true;
+        }
+        if args.skip_catalog {
+            self.skip_catalog = true;
+        }
+        if args.diagnostic_bundle.is_some() {
+            self.diagnostic_species = args.diagnostic_bundle;
+        }
+        if args.keep_intermediates {
+            self.keep_intermediates = true;
+        }
+        if args.refresh_changed {
+            self.refresh_changed = true;
+        }
+        if args.follow_symlinks {
+            self.follow_symlinks = true;
+        }
+        if args.validate_pokecfg {
+            self.validate_pokecfg = true;
+        }
+        if args.strict_names {
+            self.strict_names = true;
+        }
+        if args.strict {
+            self.strict = true;
+        }
+        if args.profile {
+            self.profile = true;
+        }
+        if args.verbose > 0 {
+            self.verbosity = args.verbose;
+        }
         if let Some(v) = args.donor_dev {
             self.donor_dev = v;
         }
@@ -125,6 +486,12 @@ impl AppConfig {
                 self.language = s.trim().to_string();
             }
         }
+        if !args.report_lang.is_empty() {
+            self.report_languages = args.report_lang.clone();
+        }
+        if let Some(s) = &args.species_name_format {
+            self.species_name_format = s.clone();
+        }
     }
 }
 
@@ -140,6 +507,37 @@ pub struct HeadlessArgs {
     #[arg(long, default_value_t = false)]
     pub headless: bool,
 
+    /// Print the effective config (saved config merged with CLI flags) as JSON and exit
+    #[arg(long, default_value_t = false)]
+    pub print_config: bool,
+
+    /// Print the phases a run would go through with this config, each enabled or skipped, and exit
+    #[arg(long, default_value_t = false)]
+    pub list_phases: bool,
+
+    /// Write `_report/tex_plan.json` (the donor each output `.bntx` would get) without converting, and exit
+    #[arg(long, default_value_t = false)]
+    pub tex_plan: bool,
+
+    /// Print ZA donor candidates (`species  name  pm_variant`) and exit
+    #[arg(long, default_value_t = false)]
+    pub list_donors: bool,
+
+    /// Filter for `--list-donors`: substring of name, pm_variant or species number
+    #[arg(long, requires = "list_donors")]
+    pub donor_search: Option<String>,
+
+    /// Headless: exit with code 2 if any warnings were emitted (errors always exit with 1)
+    /// Also turns the swapped SV/ZA dump check into a hard error
+    #[arg(long, default_value_t = false)]
+    pub strict: bool,
+
+    /// Headless: open the output folder in the file manager after a successful run (no errors,
+    /// and no warnings under --strict)
+    /// Skipped on CI and on Linux/BSD sessions without a display
+    #[arg(long, default_value_t = false)]
+    pub open_output: bool,
+
     #[arg(long)]
     pub sv_root: Option<PathBuf>,
 
@@ -158,12 +556,102 @@ pub struct HeadlessArgs {
     #[arg(long)]
     pub pknx_personal_dir: Option<PathBuf>,
 
+    /// Mod folder laid out like the dump's `data/`, preferred over it; repeat to stack (first wins)
+    #[arg(long)]
+    pub overlay_data_root: Vec<PathBuf>,
+
     #[arg(long, default_value_t = false)]
     pub texture_convert: bool,
 
     #[arg(long, default_value_t = false)]
     pub no_texture_resize: bool,
 
+    /// Largest donor width/height a texture may be resized to (default 4096)
+    #[arg(long)]
+    pub max_resize_dim: Option<u32>,
+
+    /// Only convert textures changed since their last successful conversion (default true)
+    /// Pass `--texture-incremental false` to reprocess every `.bntx`
+    #[arg(long, action = clap::ArgAction::Set)]
+    pub texture_incremental: Option<bool>,
+
+    /// Before skipping an already-converted texture, compare its data region against the hash
+    /// recorded at its last conversion; slower, but reconverts stale splices
+    #[arg(long, default_value_t = false)]
+    pub verify_hash: bool,
+
+    /// Abort the texture phase on the first file that fails to convert
+    #[arg(long, default_value_t = false)]
+    pub fail_fast: bool,
+
+    /// Restrict the texture pass to menu icons (`icons-only`) instead of every `.bntx`
+    #[arg(long, value_enum)]
+    pub texture_scope: Option<TextureScope>,
+
+    /// Fallback icon donor choice: `most-common` (default), `largest` or `pinned-path`
+    #[arg(long, value_enum)]
+    pub icon_donor_strategy: Option<IconDonorStrategy>,
+
+    /// Donor bntx to pin as the fallback icon donor (implies `--icon-donor-strategy pinned-path`)
+    #[arg(long)]
+    pub icon_donor: Option<PathBuf>,
+
+    /// JSON file mapping BNTX format codes to ultimate_tex names, merged over the built-in table
+    /// e.g. `{ "0x21": "BC7RgbaUnorm", "0x20/6": "BC7RgbaUnormSrgb" }`
+    #[arg(long)]
+    pub tex_format_map: Option<PathBuf>,
+
+    /// Personal array path relative to the ZA dump and output root
+    /// (default `avalon/data/personal_array.bin`)
+    #[arg(long, value_parser = parse_relative_path)]
+    pub personal_array_path: Option<String>,
+
+    /// Fail instead of warning when a donor id is missing from a param array (per-species donors)
+    #[arg(long, default_value_t = false)]
+    pub strict_param: bool,
+
+    /// Dump each built param/personal bin back through flatc and bail if it doesn't match
+    /// what was written (doubles the flatc work)
+    #[arg(long, default_value_t = false)]
+    pub verify_flatc: bool,
+
+    /// Exit with an error listing SV catalog pm_variants whose assets are missing, instead of
+    /// skipping them with a warning
+    #[arg(long, default_value_t = false)]
+    pub fail_on_missing_assets: bool,
+
+    /// Only convert these `species[:form[:gender]]` keys, e.g. `--only-species 25,133:1,150-151`
+    /// A bare species matches every form and gender
+    #[arg(long, value_delimiter = ',')]
+    pub only_species: Vec<String>,
+
+    /// Leave out these `species[:form[:gender]]` keys, e.g. `--only-species 25 --exclude-species 25:0`
+    #[arg(long, value_delimiter = ',')]
+    pub exclude_species: Vec<String>,
+
+    /// Delete these `species[:form[:gender]]` entries from the patched ZA catalog, e.g. `--remove-species 25:1`
+    /// Applied after the selection is added, so a removed key stays out even if selected
+    #[arg(long, value_delimiter = ',')]
+    pub remove_species: Vec<String>,
+
+    /// Write reports here instead of `<out-root>/_report` (kept out of the packaged output)
+    /// With `--batch`, each entry gets its own `NNN_<out-root name>` subfolder
+    #[arg(long)]
+    pub report_dir: Option<PathBuf>,
+
+    /// Output layout: `raw` (default) or `layeredfs` (`<title-id>/romfs/...`)
+    #[arg(long, value_enum)]
+    pub output_layout: Option<OutputLayout>,
+
+    /// Title id for the layeredfs layout (implies `--output-layout layeredfs`)
+    #[arg(long)]
+    pub layeredfs_title_id: Option<String>,
+
+    /// Force an output texture format for matching sources, as `PATTERN=FORMAT` (repeatable)
+    /// e.g. `--format-override '*_00.bntx=BC7RgbaUnormSrgb'`
+    #[arg(long, value_parser = parse_format_override)]
+    pub format_override: Vec<(String, String)>,
+
     #[arg(long, default_value_t = false)]
     pub use_za_base_config: bool,
 
@@ -173,6 +661,19 @@ pub struct HeadlessArgs {
     #[arg(long, default_value_t = false)]
     pub no_head_look_at: bool,
 
+    /// Add the other genders' SV catalog entries of each selected mon when they use the same model
+    #[arg(long, default_value_t = false)]
+    pub mirror_genders: bool,
+
+    /// Look-at groups to patch with `--no-head-look-at`, as inline JSON or a `.json` file
+    /// e.g. `{"head":{},"eye":{"rotation_weight":0.5,"limit":0.3,"turning_clamp":true}}`
+    #[arg(long, value_parser = parse_look_at_groups)]
+    pub look_at_groups: Option<BTreeMap<String, NoLookAtParams>>,
+
+    /// Keep the SV `.tralk` instead of deleting it when look-at is disabled SV-style
+    #[arg(long, default_value_t = false)]
+    pub keep_tralk: bool,
+
     /// If true (default), skip mons already present in ZA's catalog
     /// Pass `--skip-pokemon-already-in-za false` to process them anyway
     #[arg(long, default_value_t = true, action = clap::ArgAction::Set)]
@@ -181,14 +682,187 @@ pub struct HeadlessArgs {
     #[arg(long, default_value_t = true, action = clap::ArgAction::Set)]
     pub legacy_mode: bool,
 
+    /// Donor template JSON to use instead of the autosave template (implies `--legacy-mode false`)
+    #[arg(long)]
+    pub template: Option<PathBuf>,
+
+    /// Run every `{template, out_root}` entry of this manifest JSON and report per-entry results
+    #[arg(long)]
+    pub batch: Option<PathBuf>,
+
+    /// Max work items run at once by parallel phases such as `--batch` and texture conversion (0 = one per CPU; default 1)
+    #[arg(long)]
+    pub jobs: Option<usize>,
+
+    /// Max ultimate_tex_cli processes run at once by the texture phase (0 = up to --jobs);
+    /// shared by all --batch entries
+    #[arg(long)]
+    pub tex_jobs: Option<usize>,
+
     /// If true (default), write debugging reports under `Output/_report`
     /// Pass `--generate-reports false` to disable
     #[arg(long, default_value_t = true, action = clap::ArgAction::Set)]
     pub generate_reports: bool,
 
+    /// Only re-patch the ZA catalog (and names report) from the current selection
+    /// Assumes pm packages were already copied into the output by a previous run
+    #[arg(long, default_value_t = false)]
+    pub catalog_only: bool,
+
+    /// Skip the texture convert phase
+    #[arg(long, default_value_t = false)]
+    pub skip_textures: bool,
+
+    /// Skip the param array patch phase
+    #[arg(long, default_value_t = false)]
+    pub skip_param: bool,
+
+    /// Skip the personal array patch phase
+    #[arg(long, default_value_t = false)]
+    pub skip_personal: bool,
+
+    /// Skip re-patching the ZA catalog
+    #[arg(long, default_value_t = false)]
+    pub skip_catalog: bool,
+
+    /// Write `_report/diag_<species>.json` (selection, donor, files, anim sync, catalog before/after)
+    #[arg(long, value_name = "SPECIES")]
+    pub diagnostic_bundle: Option<u16>,
+
+    /// Keep flatc's intermediate JSON under `Output/_report/flatc/` for inspection
+    #[arg(long, default_value_t = false)]
+    pub keep_intermediates: bool,
+
+    /// Recopy pm package files whose source changed since they were copied instead of keeping the old output
+    #[arg(long, default_value_t = false)]
+    pub refresh_changed: bool,
+
+    /// Follow symlinks when scanning the SV and ZA dumps (loops are skipped)
+    #[arg(long, default_value_t = false)]
+    pub follow_symlinks: bool,
+
+    /// Warn when a converted `.trpokecfg` is missing or still references a donor pm_variant
+    #[arg(long, default_value_t = false)]
+    pub validate_pokecfg: bool,
+
+    /// Fail on monsname strings that aren't valid UTF-16 instead of decoding them lossily
+    #[arg(long, default_value_t = false)]
+    pub strict_names: bool,
+
+    /// Time each external tool call and report calls plus total/average wall time per tool
+    #[arg(long, default_value_t = false)]
+    pub profile: bool,
+
+    /// More backend log detail: `-v` per-variant lines, `-vv` per-file lines
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
     #[arg(long)]
     pub donor_dev: Option<u32>,
 
+    /// Delete the tool-owned subtrees (ik_pokemon, param_chr, avalon, _report, _cache) under the
+    /// output root before running; anything else in the output folder is left alone
+    #[arg(long, default_value_t = false)]
+    pub clean: bool,
+
+    /// List the `.bak` files earlier runs left under the output root, delete them, and exit
+    #[arg(long, default_value_t = false)]
+    pub prune_backups: bool,
+
+    /// With `--prune-backups`: only list what would be removed
+    #[arg(long, requires = "prune_backups", default_value_t = false)]
+    pub dry_run: bool,
+
+    /// Run only the per-variant copy pipeline for this `pmXXXX_YY_ZZ`, bypassing catalog selection
+    #[arg(long)]
+    pub pm_variant: Option<String>,
+
+    /// ZA donor pm_variant to overlay onto `--pm-variant`
+    #[arg(long, requires = "pm_variant")]
+    pub donor: Option<String>,
+
     #[arg(long)]
     pub lang: Option<String>,
+
+    /// Extra language for the converted names report's `names` map; repeat for more
+    #[arg(long)]
+    pub report_lang: Vec<String>,
+
+    /// Placeholder for unnamed species, e.g. `[{species}]` or `SPECIES_{species}` (default `#{species:05}`)
+    #[arg(long)]
+    pub species_name_format: Option<String>,
+}
+
+fn parse_format_override(s: &str) -> Result<(String, String), String> {
+    let Some((pattern, format)) = s.split_once('=') else {
+        return Err(format!("expected PATTERN=FORMAT, got {s:?}"));
+    };
+    let (pattern, format) = (pattern.trim(), format.trim());
+    if pattern.is_empty() || format.is_empty() {
+        return Err(format!("expected PATTERN=FORMAT, got {s:?}"));
+    }
+    Ok((pattern.to_string(), format.to_string()))
+}
+
+fn parse_relative_path(s: &str) -> Result<String, String> {
+    crate::paths::check_relative_path(s)?;
+    Ok(s.to_string())
+}
+
+/// `--look-at-groups` value: inline JSON, or the path of a `.json` file holding it
+pub fn parse_look_at_groups(s: &str) -> Result<BTreeMap<String, NoLookAtParams>, String> {
+    let text = if s.trim_start().starts_with('{') {
+        s.to_string()
+    } else {
+        fs::read_to_string(s).map_err(|e| format!("read {s:?}: {e}"))?
+    };
+    serde_json::from_str(&text).map_err(|e| format!("invalid look-at groups JSON: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_look_at_groups_inline_and_file() {
+        let json = r#"{"head":{},"eye":{"rotation_weight":0.5,"limit":0.3,"turning_clamp":true}}"#;
+        let want = BTreeMap::from([
+            ("head".to_string(), NoLookAtParams::default()),
+            (
+                "eye".to_string(),
+                NoLookAtParams {
+                    rotation_weight: 0.5,
+                    limit: 0.3,
+                    turning_clamp: true,
+                },
+            ),
+        ]);
+        assert_eq!(parse_look_at_groups(json).unwrap(), want);
+        assert_eq!(parse_look_at_groups(&format!("  {json}")).unwrap(), want);
+
+        let td = tempfile::tempdir().unwrap();
+        let path = td.path().join("groups.json");
+        fs::write(&path, json).unwrap();
+        assert_eq!(parse_look_at_groups(path.to_str().unwrap()).unwrap(), want);
+
+        // fields left out keep their defaults
+        let partial = parse_look_at_groups(r#"{"eye":{"limit":0.3}}"#).unwrap();
+        assert_eq!(
+            partial["eye"],
+            NoLookAtParams {
+                limit: 0.3,
+                ..NoLookAtParams::default()
+            }
+        );
+    }
+
+    #[test]
+    fn parse_look_at_groups_errors() {
+        assert!(parse_look_at_groups("{not json").is_err());
+        assert!(parse_look_at_groups(r#"{"eye":{"limit":"x"}}"#).is_err());
+        let td = tempfile::tempdir().unwrap();
+        let missing = td.path().join("missing.json");
+        let err = parse_look_at_groups(missing.to_str().unwrap()).unwrap_err();
+        assert!(err.starts_with("read "), "{err}");
+    }
 }
@@ -0,0 +1,44 @@
+use std::path::PathBuf;
+
+/// Failure kinds an embedder or the UI may want to tell apart
+/// Raised inside `anyhow::Error`; recover it with `err.downcast_ref::<SvzaError>()`.
+#[derive(Debug, thiserror::Error)]
+pub enum SvzaError {
+    #[error("{tool} not found: {path:?}")]
+    MissingTool { tool: &'static str, path: PathBuf },
+    #[error("{tool} not set")]
+    ToolNotSet { tool: &'static str },
+    #[error("{what} is not a directory: {path:?}")]
+    PathNotFound { what: &'static str, path: PathBuf },
+    #[error("failed to parse catalog {path:?}: {reason}")]
+    CatalogParse { path: PathBuf, reason: String },
+    #[error("flatc {step} failed: {status}\n{output}")]
+    FlatcFailed {
+        step: &'static str,
+        status: String,
+        output: String,
+    },
+    #[error("ultimate_tex_cli failed: {status}\n{output}")]
+    TextureToolFailed { status: String, output: String },
+}
+
+impl SvzaError {
+    /// One-line suggestion shown next to the error in the UI
+    pub fn hint(&self) -> &'static str {
+        match self {
+            SvzaError::MissingTool { .. } | SvzaError::ToolNotSet { .. } => {
+                "check the tool path under Settings (or disable the step that needs it)"
+            }
+            SvzaError::PathNotFound { .. } => {
+                "re-pick the folder; it may have moved or be unmounted"
+            }
+            SvzaError::CatalogParse { .. } => {
+                "the catalog is not a valid trpmcatalog; re-extract it from the dump"
+            }
+            SvzaError::FlatcFailed { .. } => "make sure flatc matches the bundled schemas",
+            SvzaError::TextureToolFailed { .. } => {
+                "run ultimate_tex_cli by hand on the file to see the full error"
+            }
+        }
+    }
+}
@@ -1,4 +1,7 @@
 pub mod raw;
+#[cfg(test)]
+pub(crate) mod testbuf;
 pub mod tracn;
 pub mod tralk;
 pub mod trpmcatalog;
+pub mod trpokecfg;
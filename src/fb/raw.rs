@@ -41,14 +41,41 @@ impl FbBuf {
         Ok(self.read_u32(pos)? as i32)
     }
 
+    /// Resolves the root table and checks its vtable lies inside the buffer
+    /// A `file_identifier` (bytes 4..8) needs no special handling: the root uoffset already skips it.
     pub fn root_table_pos(&self) -> anyhow::Result<usize> {
         let off = self.read_u32(0)? as usize;
         if off >= self.b.len() {
             anyhow::bail!("fb: root table offset out of bounds: {off}");
         }
+        if off < 4 {
+            anyhow::bail!(
+                "fb: root table offset {off} overlaps the root uoffset (corrupt or padded file?)"
+            );
+        }
+        let vt = self
+            .vtable_pos(off)
+            .map_err(|e| anyhow::anyhow!("fb: root table at {off} has no valid vtable: {e}"))?;
+        let vt_len = self
+            .read_u16(vt)
+            .map_err(|_| anyhow::anyhow!("fb: root vtable out of bounds at {vt} (table {off})"))?
+            as usize;
+        if vt_len < 4 || !vt_len.is_multiple_of(2) || vt + vt_len > self.b.len() {
+            anyhow::bail!("fb: root vtable at {vt} has invalid length {vt_len} (table {off})");
+        }
         Ok(off)
     }
 
+    /// The 4-byte `file_identifier` after the root uoffset, if the file appears to carry one
+    pub fn file_identifier(&self) -> Option<[u8; 4]> {
+        let off = self.read_u32(0).ok()? as usize;
+        if off < 8 {
+            return None;
+        }
+        let id: [u8; 4] = self.b.get(4..8)?.try_into().ok()?;
+        id.iter().all(u8::is_ascii_alphanumeric).then_some(id)
+    }
+
     pub fn vtable_pos(&self, table_pos: usize) -> anyhow::Result<usize> {
         // In FlatBuffers this is a signed offset from the table start to the vtable
         // (usually negative, since vtables are stored before tables)
@@ -193,6 +220,18 @@ impl FbBuf {
         Ok(Some(self.read_u8(table_pos + fo)?))
     }
 
+    /// A `bool` field is stored as one byte; anything non-zero reads as true
+    pub fn table_field_scalar_bool(
+        &self,
+        table_pos: usize,
+        vtable_pos: usize,
+        field_index: usize,
+    ) -> anyhow::Result<Option<bool>> {
+        Ok(self
+            .table_field_scalar_u8(table_pos, vtable_pos, field_index)?
+            .map(|v| v != 0))
+    }
+
     pub fn table_field_vec_of_tables(
         &self,
         table_pos: usize,
@@ -258,3 +297,105 @@ impl FbBuf {
 
     // keep this small; add helpers as we need them
 }
+
+/// Overwrites the byte at `pos` with a flatbuffers bool (0 or 1)
+/// Only valid for fields present in the buffer; an absent field has no slot to write.
+pub fn write_bool_at(buf: &mut [u8], pos: usize, v: bool) -> anyhow::Result<()> {
+    let slot = buf
+        .get_mut(pos)
+        .ok_or_else(|| anyhow::anyhow!("fb: out of bounds write_bool at {pos}"))?;
+    *slot = u8::from(v);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fb::testbuf::{self, Field};
+
+    fn root_u32(b: Vec<u8>) -> anyhow::Result<Option<u32>> {
+        let fb = FbBuf::new(b);
+        let root = fb.root_table_pos()?;
+        let vt = fb.vtable_pos(root)?;
+        fb.table_field_scalar_u32(root, vt, 1)
+    }
+
+    #[test]
+    fn root_without_file_identifier() {
+        let b = testbuf::table(vec![(1, Field::U32(42))], None);
+        assert_eq!(FbBuf::new(b.clone()).file_identifier(), None);
+        assert_eq!(root_u32(b).unwrap(), Some(42));
+    }
+
+    #[test]
+    fn root_with_file_identifier() {
+        let b = testbuf::table(vec![(1, Field::U32(42))], Some(b"TRPM"));
+        assert_eq!(FbBuf::new(b.clone()).file_identifier(), Some(*b"TRPM"));
+        assert_eq!(root_u32(b).unwrap(), Some(42));
+    }
+
+    #[test]
+    fn root_vtable_out_of_bounds_is_an_error() {
+        // root at 4, soffset -64 puts the vtable past the end of the buffer
+        let mut b = 4u32.to_le_bytes().to_vec();
+        b.extend_from_slice(&(-64i32).to_le_bytes());
+        let err = FbBuf::new(b).root_table_pos().unwrap_err();
+        assert!(err.to_string().contains("vtable"), "{err}");
+
+        // vtable inside the buffer but claiming to extend past it
+        let mut b = 8u32.to_le_bytes().to_vec();
+        b.extend_from_slice(&[64, 0, 8, 0]);
+        b.extend_from_slice(&4i32.to_le_bytes());
+        let err = FbBuf::new(b).root_table_pos().unwrap_err();
+        assert!(err.to_string().contains("invalid length"), "{err}");
+    }
+
+    #[test]
+    fn bool_fields_read_any_non_zero_byte_as_true() {
+        let b = testbuf::table(
+            vec![(0, Field::U8(0)), (1, Field::U8(1)), (2, Field::U8(7))],
+            None,
+        );
+        let fb = FbBuf::new(b);
+        let root = fb.root_table_pos().unwrap();
+        let vt = fb.vtable_pos(root).unwrap();
+        assert_eq!(
+            fb.table_field_scalar_bool(root, vt, 0).unwrap(),
+            Some(false)
+        );
+        assert_eq!(fb.table_field_scalar_bool(root, vt, 1).unwrap(), Some(true));
+        assert_eq!(fb.table_field_scalar_bool(root, vt, 2).unwrap(), Some(true));
+        // past the end of the vtable
+        assert_eq!(fb.table_field_scalar_bool(root, vt, 3).unwrap(), None);
+    }
+
+    #[test]
+    fn absent_bool_field_is_none() {
+        // field 1 set, so the vtable has a zero slot for field 0
+        let b = testbuf::table(vec![(1, Field::U8(1))], None);
+        let fb = FbBuf::new(b);
+        let root = fb.root_table_pos().unwrap();
+        let vt = fb.vtable_pos(root).unwrap();
+        assert_eq!(fb.table_field_scalar_bool(root, vt, 0).unwrap(), None);
+    }
+
+    #[test]
+    fn write_bool_at_is_bounds_checked() {
+        let mut b = vec![0xFF; 4];
+        write_bool_at(&mut b, 3, false).unwrap();
+        write_bool_at(&mut b, 0, true).unwrap();
+        assert_eq!(b, [1, 0xFF, 0xFF, 0]);
+        assert!(write_bool_at(&mut b, 4, true).is_err());
+        assert!(write_bool_at(&mut b, usize::MAX, true).is_err());
+        assert_eq!(b, [1, 0xFF, 0xFF, 0]);
+    }
+
+    #[test]
+    fn root_offset_must_point_past_itself_and_inside_the_buffer() {
+        assert!(FbBuf::new(vec![0, 0, 0, 0, 0, 0, 0, 0])
+            .root_table_pos()
+            .is_err());
+        assert!(FbBuf::new(vec![200, 0, 0, 0]).root_table_pos().is_err());
+        assert!(FbBuf::new(vec![4, 0]).root_table_pos().is_err());
+    }
+}
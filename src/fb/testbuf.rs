@@ -0,0 +1,102 @@
+//! Hand-assembled flatbuffers for unit tests
+//!
+//! Layout is deliberately simple: the root uoffset (plus an optional file identifier), then each
+//! table as its vtable followed by the table itself with one 4-byte slot per field. Strings,
+//! vectors and sub-tables are appended after their parent and linked with forward uoffsets.
+
+/// One field value; `index` in [`table`] is the flatbuffers field index, not the vtable slot
+pub(crate) enum Field {
+    U8(u8),
+    U32(u32),
+    F32(f32),
+    Str(&'static str),
+    F32s(Vec<f32>),
+    Tables(Vec<Vec<(usize, Field)>>),
+}
+
+/// A finished buffer whose root table has `fields`
+pub(crate) fn table(fields: Vec<(usize, Field)>, file_id: Option<&[u8; 4]>) -> Vec<u8> {
+    let mut b = vec![0u8; 4];
+    if let Some(id) = file_id {
+        b.extend_from_slice(id);
+    }
+    let root = write_table(&mut b, &fields);
+    patch_u32(&mut b, 0, root as u32);
+    b
+}
+
+fn align4(b: &mut Vec<u8>) {
+    while !b.len().is_multiple_of(4) {
+        b.push(0);
+    }
+}
+
+fn patch_u32(b: &mut [u8], at: usize, v: u32) {
+    b[at..at + 4].copy_from_slice(&v.to_le_bytes());
+}
+
+fn write_table(b: &mut Vec<u8>, fields: &[(usize, Field)]) -> usize {
+    align4(b);
+    let slots = fields.iter().map(|(i, _)| i + 1).max().unwrap_or(0);
+    let vt_len = 4 + 2 * slots;
+    let vt_pos = b.len();
+    b.extend_from_slice(&(vt_len as u16).to_le_bytes());
+    b.extend_from_slice(&((4 + 4 * fields.len()) as u16).to_le_bytes());
+    for i in 0..slots {
+        let fo = fields
+            .iter()
+            .position(|(fi, _)| *fi == i)
+            .map_or(0, |n| 4 + 4 * n);
+        b.extend_from_slice(&(fo as u16).to_le_bytes());
+    }
+    align4(b);
+
+    let table_pos = b.len();
+    b.extend_from_slice(&((table_pos - vt_pos) as i32).to_le_bytes());
+    for (_, f) in fields {
+        match f {
+            Field::U8(v) => b.extend_from_slice(&[*v, 0, 0, 0]),
+            Field::U32(v) => b.extend_from_slice(&v.to_le_bytes()),
+            Field::F32(v) => b.extend_from_slice(&v.to_le_bytes()),
+            Field::Str(_) | Field::F32s(_) | Field::Tables(_) => b.extend_from_slice(&[0; 4]),
+        }
+    }
+
+    for (n, (_, f)) in fields.iter().enumerate() {
+        let slot = table_pos + 4 + 4 * n;
+        let child = match f {
+            Field::Str(s) => {
+                align4(b);
+                let at = b.len();
+                b.extend_from_slice(&(s.len() as u32).to_le_bytes());
+                b.extend_from_slice(s.as_bytes());
+                b.push(0);
+                at
+            }
+            Field::F32s(v) => {
+                align4(b);
+                let at = b.len();
+                b.extend_from_slice(&(v.len() as u32).to_le_bytes());
+                for x in v {
+                    b.extend_from_slice(&x.to_le_bytes());
+                }
+                at
+            }
+            Field::Tables(tables) => {
+                align4(b);
+                let at = b.len();
+                b.extend_from_slice(&(tables.len() as u32).to_le_bytes());
+                b.extend(std::iter::repeat_n(0, 4 * tables.len()));
+                for (i, t) in tables.iter().enumerate() {
+                    let elem = at + 4 + 4 * i;
+                    let tpos = write_table(b, t);
+                    patch_u32(b, elem, (tpos - elem) as u32);
+                }
+                at
+            }
+            Field::U8(_) | Field::U32(_) | Field::F32(_) => continue,
+        };
+        patch_u32(b, slot, (child - slot) as u32);
+    }
+    table_pos
+}
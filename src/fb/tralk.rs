@@ -1,28 +1,74 @@
-use crate::fb::raw::FbBuf;
+use crate::fb::raw::{write_bool_at, FbBuf};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Outcome of [`patch_look_at_groups_in_place`] for one configured group name
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NoHeadPatch {
+    /// Groups whose values were rewritten
+    pub changed: usize,
+    /// Groups that already carried the requested values and were left untouched
+    pub already: usize,
+}
+
+/// Value the no-head patch writes into the head group's angle limits (fields 8..=13)
+pub const NO_HEAD_LIMIT: f32 = 0.001;
+
+/// Values written into one JointRotation look-at group; the default fully kills it
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NoLookAtParams {
+    /// Written to every rotationWeights entry (field 23)
+    pub rotation_weight: f32,
+    /// Written to the angle limits (fields 8..=13)
+    pub limit: f32,
+    /// enableTurningClamp (field 15)
+    pub turning_clamp: bool,
+}
+
+impl Default for NoLookAtParams {
+    fn default() -> Self {
+        Self {
+            rotation_weight: 0.0,
+            limit: NO_HEAD_LIMIT,
+            turning_clamp: false,
+        }
+    }
+}
+
+/// Applies each group's params to the JointRotation groups of that name
+/// Every configured name gets an entry in the result, zeroed when the file has no such group.
+pub fn patch_look_at_groups_in_place(
+    buf: &mut [u8],
+    groups: &BTreeMap<String, NoLookAtParams>,
+) -> anyhow::Result<BTreeMap<String, NoHeadPatch>> {
+    let mut out: BTreeMap<String, NoHeadPatch> = groups
+        .keys()
+        .map(|k| (k.clone(), NoHeadPatch::default()))
+        .collect();
 
-pub fn patch_no_head_joint_rotation_in_place(buf: &mut [u8]) -> anyhow::Result<usize> {
     let fb = FbBuf::new(buf.to_vec());
     let root = fb.root_table_pos()?;
     let root_vt = fb.vtable_pos(root)?;
 
-    let Some(groups) = fb.table_field_vec_of_tables(root, root_vt, 4)? else {
-        return Ok(0);
+    let Some(group_list) = fb.table_field_vec_of_tables(root, root_vt, 4)? else {
+        return Ok(out);
     };
 
-    let mut changed = 0usize;
-    for gpos in groups {
+    for gpos in group_list {
         let gvt = fb.vtable_pos(gpos)?;
 
         let name = fb.table_field_string(gpos, gvt, 0)?.unwrap_or_default();
-        if name != "head" {
+        let Some(params) = groups.get(&name) else {
             continue;
-        }
+        };
         let look_at_type = fb.table_field_scalar_u32(gpos, gvt, 4)?.unwrap_or(0);
         if look_at_type != 0 {
             continue;
         }
 
-        // a3 rotationWeights -> [0.0, 0.0]
+        // a3 rotationWeights
+        let mut weight_locs = Vec::new();
         if let Some(vec_pos) = fb.table_field_vec_pos(gpos, gvt, 23)? {
             if vec_pos + 4 <= buf.len() {
                 let Ok(hdr) = <[u8; 4]>::try_from(&buf[vec_pos..vec_pos + 4]) else {
@@ -34,28 +80,58 @@ pub fn patch_no_head_joint_rotation_in_place(buf: &mut [u8]) -> anyhow::Result<u
                     if at + 4 > buf.len() {
                         break;
                     }
-                    write_f32(buf, at, 0.0)?;
+                    weight_locs.push(at);
                 }
             }
         }
-
+        let mut limit_locs = Vec::new();
         for field in 8..=13 {
             if let Some(loc) = fb.table_field_loc(gpos, gvt, field)? {
-                write_f32(buf, loc, 0.001)?;
+                limit_locs.push(loc);
             }
         }
+        // b2 enableTurningClamp
+        let clamp_loc = fb.table_field_loc(gpos, gvt, 15)?;
+        let clamp = fb.table_field_scalar_bool(gpos, gvt, 15)?;
 
-        // b2 enableTurningClamp -> false
-        if let Some(loc) = fb.table_field_loc(gpos, gvt, 15)? {
-            if loc < buf.len() {
-                buf[loc] = 0;
-            }
+        // Nothing to compare or write; counting it as already patched would hide a schema change
+        if weight_locs.is_empty() && limit_locs.is_empty() && clamp_loc.is_none() {
+            continue;
+        }
+        let entry = out.entry(name).or_default();
+        let already = weight_locs
+            .iter()
+            .all(|&at| read_f32(buf, at).is_ok_and(|v| v == params.rotation_weight))
+            && limit_locs
+                .iter()
+                .all(|&at| read_f32(buf, at).is_ok_and(|v| v == params.limit))
+            && clamp.is_none_or(|v| v == params.turning_clamp);
+        if already {
+            entry.already += 1;
+            continue;
+        }
+
+        for at in weight_locs {
+            write_f32(buf, at, params.rotation_weight)?;
+        }
+        for loc in limit_locs {
+            write_f32(buf, loc, params.limit)?;
+        }
+        if let Some(loc) = clamp_loc {
+            write_bool_at(buf, loc, params.turning_clamp)?;
         }
 
-        changed += 1;
+        entry.changed += 1;
     }
 
-    Ok(changed)
+    Ok(out)
+}
+
+fn read_f32(buf: &[u8], pos: usize) -> anyhow::Result<f32> {
+    let b = buf
+        .get(pos..pos + 4)
+        .ok_or_else(|| anyhow::anyhow!("fb: out of bounds read_f32 at {pos}"))?;
+    Ok(f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
 }
 
 fn write_f32(buf: &mut [u8], pos: usize, v: f32) -> anyhow::Result<()> {
@@ -65,3 +141,132 @@ fn write_f32(buf: &mut [u8], pos: usize, v: f32) -> anyhow::Result<()> {
     buf[pos..pos + 4].copy_from_slice(&v.to_bits().to_le_bytes());
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fb::testbuf::{self, Field};
+
+    fn tralk(groups: Vec<Vec<(usize, Field)>>) -> Vec<u8> {
+        testbuf::table(vec![(4, Field::Tables(groups))], None)
+    }
+
+    fn head_group() -> Vec<(usize, Field)> {
+        named_group("head")
+    }
+
+    fn named_group(name: &'static str) -> Vec<(usize, Field)> {
+        let mut g = vec![(0, Field::Str(name)), (4, Field::U32(0))];
+        g.extend((8..=13).map(|i| (i, Field::F32(45.0))));
+        g.push((15, Field::U8(1)));
+        g.push((23, Field::F32s(vec![1.0, 0.5])));
+        g
+    }
+
+    fn groups(name: &str) -> BTreeMap<String, NoLookAtParams> {
+        BTreeMap::from([(name.to_string(), NoLookAtParams::default())])
+    }
+
+    #[test]
+    fn second_run_reports_already_patched() {
+        let mut buf = tralk(vec![head_group()]);
+        let first = patch_look_at_groups_in_place(&mut buf, &groups("head")).unwrap();
+        assert_eq!(
+            first["head"],
+            NoHeadPatch {
+                changed: 1,
+                already: 0
+            }
+        );
+
+        let fb = FbBuf::new(buf.clone());
+        let root = fb.root_table_pos().unwrap();
+        let root_vt = fb.vtable_pos(root).unwrap();
+        let g = fb
+            .table_field_vec_of_tables(root, root_vt, 4)
+            .unwrap()
+            .unwrap()[0];
+        let gvt = fb.vtable_pos(g).unwrap();
+        assert_eq!(fb.table_field_scalar_bool(g, gvt, 15).unwrap(), Some(false));
+        let limit = fb.table_field_loc(g, gvt, 8).unwrap().unwrap();
+        assert_eq!(read_f32(&buf, limit).unwrap(), NO_HEAD_LIMIT);
+
+        let second = patch_look_at_groups_in_place(&mut buf, &groups("head")).unwrap();
+        assert_eq!(
+            second["head"],
+            NoHeadPatch {
+                changed: 0,
+                already: 1
+            }
+        );
+    }
+
+    #[test]
+    fn group_without_patchable_fields_is_not_counted() {
+        let mut buf = tralk(vec![vec![(0, Field::Str("head")), (4, Field::U32(0))]]);
+        let res = patch_look_at_groups_in_place(&mut buf, &groups("head")).unwrap();
+        assert_eq!(res["head"], NoHeadPatch::default());
+    }
+
+    #[test]
+    fn unconfigured_and_non_rotation_groups_are_left_alone() {
+        let mut other_type = head_group();
+        other_type[1] = (4, Field::U32(1));
+        let mut buf = tralk(vec![other_type]);
+        let before = buf.clone();
+        let res = patch_look_at_groups_in_place(&mut buf, &groups("head")).unwrap();
+        assert_eq!(res["head"], NoHeadPatch::default());
+        assert_eq!(buf, before);
+
+        let res = patch_look_at_groups_in_place(&mut buf, &groups("eye")).unwrap();
+        assert_eq!(res["eye"], NoHeadPatch::default());
+        assert_eq!(buf, before);
+    }
+
+    /// (first rotation weight, first limit, clamp) of group `i`
+    fn group_values(buf: &[u8], i: usize) -> (f32, f32, Option<bool>) {
+        let fb = FbBuf::new(buf.to_vec());
+        let root = fb.root_table_pos().unwrap();
+        let root_vt = fb.vtable_pos(root).unwrap();
+        let g = fb
+            .table_field_vec_of_tables(root, root_vt, 4)
+            .unwrap()
+            .unwrap()[i];
+        let gvt = fb.vtable_pos(g).unwrap();
+        let weights = fb.table_field_vec_pos(g, gvt, 23).unwrap().unwrap();
+        let limit = fb.table_field_loc(g, gvt, 8).unwrap().unwrap();
+        (
+            read_f32(buf, weights + 4).unwrap(),
+            read_f32(buf, limit).unwrap(),
+            fb.table_field_scalar_bool(g, gvt, 15).unwrap(),
+        )
+    }
+
+    #[test]
+    fn each_group_gets_its_own_params() {
+        let mut buf = tralk(vec![named_group("head"), named_group("eye")]);
+        let eye = NoLookAtParams {
+            rotation_weight: 0.5,
+            limit: 0.3,
+            turning_clamp: true,
+        };
+        let groups = BTreeMap::from([
+            ("head".to_string(), NoLookAtParams::default()),
+            ("eye".to_string(), eye),
+        ]);
+        let res = patch_look_at_groups_in_place(&mut buf, &groups).unwrap();
+        let changed = NoHeadPatch {
+            changed: 1,
+            already: 0,
+        };
+        assert_eq!(res["head"], changed);
+        assert_eq!(res["eye"], changed);
+
+        assert_eq!(group_values(&buf, 0), (0.0, NO_HEAD_LIMIT, Some(false)));
+        assert_eq!(group_values(&buf, 1), (0.5, 0.3, Some(true)));
+
+        let again = patch_look_at_groups_in_place(&mut buf, &groups).unwrap();
+        assert_eq!(again["head"].already, 1);
+        assert_eq!(again["eye"].already, 1);
+    }
+}
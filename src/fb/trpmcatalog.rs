@@ -1,6 +1,7 @@
 use crate::fb::raw::FbBuf;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct SpeciesKey {
     pub species: u16,
     pub form: u16,
@@ -13,20 +14,20 @@ pub struct CatalogEntryLite {
     pub model_path: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AnimationInfo {
     pub form_number: i16,
     pub path: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LocatorInfo {
     pub form_number: i16,
     pub loc_index: u8,
     pub loc_path: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CatalogEntryFull {
     pub key: SpeciesKey,
     pub model_path: String,
@@ -39,7 +40,7 @@ pub struct CatalogEntryFull {
     pub defence_path: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CatalogDoc {
     pub version: u32,
     pub entries: Vec<CatalogEntryFull>,
@@ -189,6 +190,81 @@ pub fn read_doc(buf: Vec<u8>) -> anyhow::Result<CatalogDoc> {
     Ok(CatalogDoc { version, entries })
 }
 
+/// Assembles a `CatalogDoc` entry by entry
+#[derive(Debug, Clone)]
+pub struct CatalogDocBuilder {
+    doc: CatalogDoc,
+}
+
+impl CatalogDocBuilder {
+    pub fn new(version: u32) -> Self {
+        Self {
+            doc: CatalogDoc {
+                version,
+                entries: Vec::new(),
+            },
+        }
+    }
+
+    /// Adds an entry for `key` with every path derived from `{pm}/{pm_variant}` the way ZA lays
+    /// them out; `f` fills in the rest, e.g. `|e| e.animation(0, path)`
+    pub fn entry(
+        mut self,
+        key: SpeciesKey,
+        pm: &str,
+        pm_variant: &str,
+        f: impl FnOnce(CatalogEntryFull) -> CatalogEntryFull,
+    ) -> Self {
+        let base = format!("{pm}/{pm_variant}");
+        self.doc.entries.push(f(CatalogEntryFull {
+            key,
+            model_path: format!("{base}/{pm_variant}.trmdl"),
+            material_table_path: format!("{base}/{pm_variant}.trmmt"),
+            config_path: format!("{base}/{pm_variant}.trpokecfg"),
+            animations: Vec::new(),
+            locators: Vec::new(),
+            icon_path: format!("{base}/{pm_variant}_00.bntx"),
+            unk_id: 0,
+            defence_path: format!("{base}/{pm_variant}_defence.hkx"),
+        }));
+        self
+    }
+
+    pub fn push(mut self, entry: CatalogEntryFull) -> Self {
+        self.doc.entries.push(entry);
+        self
+    }
+
+    pub fn build(self) -> CatalogDoc {
+        self.doc
+    }
+}
+
+/// Setters for [`CatalogDocBuilder::entry`]
+impl CatalogEntryFull {
+    pub fn animation(mut self, form_number: i16, path: impl Into<String>) -> Self {
+        self.animations.push(AnimationInfo {
+            form_number,
+            path: path.into(),
+        });
+        self
+    }
+
+    pub fn locator(mut self, form_number: i16, loc_index: u8, path: impl Into<String>) -> Self {
+        self.locators.push(LocatorInfo {
+            form_number,
+            loc_index,
+            loc_path: path.into(),
+        });
+        self
+    }
+
+    pub fn unk_id(mut self, unk_id: u32) -> Self {
+        self.unk_id = unk_id;
+        self
+    }
+}
+
 pub fn write_doc(doc: &CatalogDoc) -> anyhow::Result<Vec<u8>> {
     let mut w = Writer::new();
     w.write_catalog(doc)
@@ -205,7 +281,7 @@ impl Writer {
 
     fn align(&mut self, n: usize) {
         let pad = (n - (self.b.len() % n)) % n;
-        self.b.extend(std::iter::repeat(0).take(pad));
+        self.b.extend(std::iter::repeat_n(0, pad));
     }
 
     fn pos(&self) -> usize {
@@ -251,9 +327,9 @@ impl Writer {
     fn write_table_header(&mut self, vtable_len: usize, obj_align: usize) -> (usize, usize) {
         self.align(2);
         let vtable_pos = self.pos();
-        self.b.extend(std::iter::repeat(0).take(vtable_len));
+        self.b.extend(std::iter::repeat_n(0, vtable_len));
         let pad = (obj_align - (self.pos() % obj_align)) % obj_align;
-        self.b.extend(std::iter::repeat(0).take(pad));
+        self.b.extend(std::iter::repeat_n(0, pad));
         let obj_pos = self.pos();
         (vtable_pos, obj_pos)
     }
@@ -462,3 +538,67 @@ impl Writer {
         Ok(std::mem::take(&mut self.b))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(species: u16, form: u16, gender: u8) -> SpeciesKey {
+        SpeciesKey {
+            species,
+            form,
+            gender,
+        }
+    }
+
+    #[test]
+    fn write_then_read_round_trips() {
+        let doc = CatalogDocBuilder::new(3)
+            .entry(key(25, 0, 0), "pm0025", "pm0025_00_00", |e| {
+                e.animation(0, "pm0025/pm0025_00_00/pm0025_00_00.tracn")
+                    .animation(1, "pm0025/pm0025_00_00/pm0025_01_00.tracn")
+                    .locator(0, 0, "pm0025/pm0025_00_00/pm0025_00_00_00000.trskl")
+                    .locator(0, 1, "pm0025/pm0025_00_00/pm0025_00_00_20000.trskl")
+                    .unk_id(0xDEAD_BEEF)
+            })
+            .entry(key(26, 1, 1), "pm0026", "pm0026_01_00", |e| {
+                e.locator(1, 0, "pm0026/pm0026_01_00/pm0026_01_00_00000.trskl")
+            })
+            .entry(key(1000, 0, 2), "pm1000", "pm1000_00_00", |e| e)
+            .build();
+
+        let back = read_doc(write_doc(&doc).unwrap()).unwrap();
+        assert_eq!(back.version, doc.version);
+        assert_eq!(back.entries.len(), doc.entries.len());
+        for (got, want) in back.entries.iter().zip(&doc.entries) {
+            assert_eq!(got.key, want.key);
+            assert_eq!(got.model_path, want.model_path);
+            assert_eq!(got.material_table_path, want.material_table_path);
+            assert_eq!(got.config_path, want.config_path);
+            assert_eq!(got.animations, want.animations);
+            assert_eq!(got.locators, want.locators);
+            assert_eq!(got.icon_path, want.icon_path);
+            assert_eq!(got.unk_id, want.unk_id);
+            assert_eq!(got.defence_path, want.defence_path);
+        }
+    }
+
+    #[test]
+    fn read_entries_matches_read_doc() {
+        let doc = CatalogDocBuilder::new(1)
+            .entry(key(25, 0, 0), "pm0025", "pm0025_00_00", |e| e)
+            .entry(key(26, 0, 0), "pm0026", "pm0026_00_00", |e| e)
+            .build();
+        let lite = read_entries(write_doc(&doc).unwrap()).unwrap();
+        let got: Vec<_> = lite
+            .iter()
+            .map(|e| (e.key, e.model_path.as_str()))
+            .collect();
+        let want: Vec<_> = doc
+            .entries
+            .iter()
+            .map(|e| (e.key, e.model_path.as_str()))
+            .collect();
+        assert_eq!(got, want);
+    }
+}
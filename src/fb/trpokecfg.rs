@@ -0,0 +1,117 @@
+use crate::fb::raw::FbBuf;
+use crate::paths::parse_pm_variant_name;
+
+/// Root table fields naming the files a config belongs to
+const FIELD_MODEL: usize = 0;
+const FIELD_MATERIAL: usize = 1;
+const FIELD_SKELETON: usize = 2;
+
+/// `pmXXXX_YY_ZZ`
+const PM_VARIANT_LEN: usize = 12;
+
+/// File names a `.trpokecfg` points at; a field the config leaves out reads as empty
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PokeCfgDoc {
+    /// `.trmdl` file name
+    pub model_name: String,
+    /// `.trmmt` material table file name
+    pub material_name: String,
+    /// `.trskl` skeleton file name
+    pub skeleton_name: String,
+}
+
+impl PokeCfgDoc {
+    /// The `pmXXXX_YY_ZZ` prefix of each referenced name, deduplicated in field order
+    /// Names without one (shared assets) don't belong to any pm_variant and are left out.
+    pub fn pm_variant_refs(&self) -> Vec<String> {
+        let mut out = Vec::new();
+        for name in [&self.model_name, &self.material_name, &self.skeleton_name] {
+            let Some(prefix) = name.get(..PM_VARIANT_LEN) else {
+                continue;
+            };
+            if parse_pm_variant_name(prefix).is_some() && !out.iter().any(|r| r == prefix) {
+                out.push(prefix.to_string());
+            }
+        }
+        out
+    }
+}
+
+pub fn read_doc(buf: Vec<u8>) -> anyhow::Result<PokeCfgDoc> {
+    let fb = FbBuf::new(buf);
+    let root = fb.root_table_pos()?;
+    let root_vt = fb.vtable_pos(root)?;
+
+    let name = |field| -> anyhow::Result<String> {
+        let s = fb
+            .table_field_string(root, root_vt, field)?
+            .unwrap_or_default();
+        Ok(s.rsplit(['/', '\\']).next().unwrap_or_default().to_string())
+    };
+    Ok(PokeCfgDoc {
+        model_name: name(FIELD_MODEL)?,
+        material_name: name(FIELD_MATERIAL)?,
+        skeleton_name: name(FIELD_SKELETON)?,
+    })
+}
+
+/// Returns the pm_variants the config's model, material and skeleton names belong to
+pub fn read_pm_variant_refs(buf: &[u8]) -> anyhow::Result<Vec<String>> {
+    Ok(read_doc(buf.to_vec())?.pm_variant_refs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fb::testbuf::{self, Field};
+
+    #[test]
+    fn reads_the_mapped_fields() {
+        let buf = testbuf::table(
+            vec![
+                (
+                    FIELD_MODEL,
+                    Field::Str("pm0025/pm0025_00_00/pm0025_00_00.trmdl"),
+                ),
+                (FIELD_MATERIAL, Field::Str("pm0025_00_00.trmmt")),
+                (FIELD_SKELETON, Field::Str("pm0025_00_00.trskl")),
+                // not a reference field, however much it looks like one
+                (5, Field::Str("pm0133_00_00.trmdl")),
+                (6, Field::U32(7)),
+            ],
+            None,
+        );
+        let doc = read_doc(buf.clone()).unwrap();
+        assert_eq!(
+            doc,
+            PokeCfgDoc {
+                model_name: "pm0025_00_00.trmdl".into(),
+                material_name: "pm0025_00_00.trmmt".into(),
+                skeleton_name: "pm0025_00_00.trskl".into(),
+            }
+        );
+        assert_eq!(read_pm_variant_refs(&buf).unwrap(), ["pm0025_00_00"]);
+    }
+
+    #[test]
+    fn missing_fields_read_empty() {
+        let buf = testbuf::table(
+            vec![(FIELD_SKELETON, Field::Str("pm0025_00_00.trskl"))],
+            None,
+        );
+        let doc = read_doc(buf).unwrap();
+        assert_eq!(doc.model_name, "");
+        assert_eq!(doc.material_name, "");
+        assert_eq!(doc.pm_variant_refs(), ["pm0025_00_00"]);
+    }
+
+    #[test]
+    fn pm_variant_refs_skips_shared_names_and_keeps_donors() {
+        let doc = PokeCfgDoc {
+            model_name: "pm0025_00_00.trmdl".into(),
+            material_name: "common_eye.trmmt".into(),
+            skeleton_name: "pm0133_00_00.trskl".into(),
+        };
+        assert_eq!(doc.pm_variant_refs(), ["pm0025_00_00", "pm0133_00_00"]);
+    }
+}
@@ -1,2 +1,39 @@
+pub mod backend;
+pub mod cancel;
+pub mod config;
+pub mod error;
 pub mod fb;
+pub mod paths;
+pub mod pool;
+pub mod progress;
 pub mod template;
+
+use cancel::CancelToken;
+use config::AppConfig;
+use progress::{ProgressEvent, ProgressSink, RunSummary};
+
+/// Runs the conversion and calls `on_event` on the caller's thread for every progress event
+/// The backend runs on a scoped worker; events are forwarded as they arrive, and the final
+/// `Finished` event is delivered before this returns.
+pub fn convert_with(
+    cfg: &AppConfig,
+    cancel: CancelToken,
+    mut on_event: impl FnMut(ProgressEvent),
+) -> anyhow::Result<RunSummary> {
+    let (sink, rx) = ProgressSink::new();
+    let reporter = sink.clone();
+    std::thread::scope(|s| {
+        let worker = s.spawn(move || {
+            let res = backend::run(cfg, sink, cancel);
+            reporter.finished(res.as_ref().cloned().map_err(|e| format!("{e:#}")));
+            res
+        });
+        // the channel closes once the worker drops both sinks
+        while let Ok(ev) = rx.recv() {
+            on_event(ev);
+        }
+        worker
+            .join()
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("conversion thread panicked")))
+    })
+}
@@ -1,10 +1,4 @@
-mod backend;
-mod cancel;
-mod config;
-mod fb;
-mod paths;
-mod progress;
-mod template;
+mod batch;
 mod ui;
 
 use anyhow::Context as _;
@@ -12,10 +6,103 @@ use clap::Parser;
 use config::{AppConfig, HeadlessArgs};
 use eframe::egui;
 use progress::{ProgressEvent, ProgressSink};
+use std::process::ExitCode;
+use svza::{backend, cancel, config, error, fb, paths, pool, progress, template};
 
-fn main() -> anyhow::Result<()> {
+/// Headless exit code when `--strict` is set and the run emitted warnings (hard errors exit with 1)
+const EXIT_WARNINGS: u8 = 2;
+
+fn main() -> anyhow::Result<ExitCode> {
     let args = HeadlessArgs::parse();
 
+    if args.print_config {
+        let mut cfg = AppConfig::load_or_default()?;
+        cfg.apply_headless(&args);
+        println!("{}", serde_json::to_string_pretty(&cfg)?);
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if args.list_phases {
+        let mut cfg = AppConfig::load_or_default()?;
+        cfg.apply_headless(&args);
+        for (i, p) in backend::plan::phase_plan(&cfg).iter().enumerate() {
+            match p.skip {
+                None => println!("{:>2}. {:<22} enabled", i + 1, p.name),
+                Some(why) => println!("{:>2}. {:<22} skipped ({why})", i + 1, p.name),
+            }
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if args.list_donors {
+        let mut cfg = AppConfig::load_or_default()?;
+        cfg.apply_headless(&args);
+        let za_dump = cfg
+            .za_dump
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("ZA dump not set (pass --za-dump)"))?;
+        let donors = backend::catalog::list_za_donors(
+            za_dump,
+            &cfg.language,
+            &cfg.species_name_format,
+            args.donor_search.as_deref(),
+            cfg.follow_symlinks,
+        )?;
+        for d in donors {
+            println!("{:>4}  {}  {}", d.key.species, d.name, d.pm_variant);
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if args.tex_plan {
+        let mut cfg = AppConfig::load_or_default()?;
+        cfg.apply_headless(&args);
+        let za_dump = cfg
+            .za_dump
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("ZA dump not set (pass --za-dump)"))?;
+        let out_root = paths::resolve_out_root(
+            cfg.out_root
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("Output root not set (pass --out-root)"))?,
+        )?;
+        let (sink, rx) = ProgressSink::new();
+        let res = backend::textures::write_tex_plan(&cfg, &za_dump, &out_root, &sink);
+        drop(sink);
+        while let Ok(ev) = rx.recv() {
+            print_headless_event(&ev);
+        }
+        res.context("texture plan failed")?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if args.prune_backups {
+        let mut cfg = AppConfig::load_or_default()?;
+        cfg.apply_headless(&args);
+        let (sink, rx) = ProgressSink::new();
+        let res = backend::clean::prune_backups(&cfg, args.dry_run, &sink);
+        drop(sink);
+        while let Ok(ev) = rx.recv() {
+            print_headless_event(&ev);
+        }
+        res.context("prune failed")?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(manifest) = &args.batch {
+        let mut cfg = AppConfig::load_or_default()?;
+        cfg.apply_headless(&args);
+        let outcome = batch::run_batch(&cfg, manifest, &pool::build_thread_pool(&cfg))?;
+        if outcome.failed > 0 {
+            return Ok(ExitCode::FAILURE);
+        }
+        if outcome.warnings > 0 && cfg.strict {
+            eprintln!("[exit] {} warning(s) with --strict", outcome.warnings);
+            return Ok(ExitCode::from(EXIT_WARNINGS));
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+
     if args.headless {
         let mut cfg = AppConfig::load_or_default()?;
         cfg.apply_headless(&args);
@@ -23,14 +110,70 @@ fn main() -> anyhow::Result<()> {
         let (sink, rx) = ProgressSink::new();
         let cancel = cancel::CancelToken::new();
 
-        std::thread::spawn(move || {
+        let reporter = sink.clone();
+        let printer = std::thread::spawn(move || {
+            let (mut warns, mut errors) = (0usize, 0usize);
             while let Ok(ev) = rx.recv() {
+                match &ev {
+                    ProgressEvent::Warn { .. } => warns += 1,
+                    ProgressEvent::Error { .. } => errors += 1,
+                    _ => {}
+                }
                 print_headless_event(&ev);
             }
+            (warns, errors)
         });
 
-        backend::run(&cfg, sink, cancel).context("backend run failed")?;
-        return Ok(());
+        let cleaned = if args.clean {
+            backend::clean::clean_output(&cfg, &sink)
+                .map(|_| ())
+                .context("clean failed")
+        } else {
+            Ok(())
+        };
+        let res = cleaned.and_then(|()| {
+            if let Some(pm_variant) = &args.pm_variant {
+                backend::run_single_pm_variant(
+                    &cfg,
+                    pm_variant,
+                    args.donor.as_deref(),
+                    sink,
+                    cancel,
+                )
+                .context("single pm_variant run failed")
+            } else {
+                backend::run(&cfg, sink, cancel).context("backend run failed")
+            }
+        });
+        reporter.finished(res.as_ref().cloned().map_err(|e| format!("{e:#}")));
+        // all senders gone -> printer drains the channel and exits
+        drop(reporter);
+        let (warns, errors) = printer.join().unwrap_or_default();
+        if let Some(kind) = res
+            .as_ref()
+            .err()
+            .and_then(|e| e.downcast_ref::<error::SvzaError>())
+        {
+            eprintln!("[hint] {}", kind.hint());
+        }
+        res?;
+        if errors > 0 {
+            eprintln!("[exit] {errors} error(s), {warns} warning(s)");
+            return Ok(ExitCode::FAILURE);
+        }
+        if warns > 0 && cfg.strict {
+            eprintln!("[exit] {warns} warning(s) with --strict");
+            return Ok(ExitCode::from(EXIT_WARNINGS));
+        }
+        // only a successful run opens the output; --strict warnings count as a failure
+        if let Some(out) = cfg.out_root.as_deref().filter(|_| args.open_output) {
+            if ui::can_open_folders() {
+                ui::open_folder(out);
+            } else {
+                eprintln!("[open-output] no display available; not opening {out:?}");
+            }
+        }
+        return Ok(ExitCode::SUCCESS);
     }
 
     let icon = load_app_icon();
@@ -48,7 +191,7 @@ fn main() -> anyhow::Result<()> {
     )
     .map_err(|e| anyhow::anyhow!("{e}"))?;
 
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
 
 fn load_app_icon() -> egui::IconData {
@@ -57,18 +200,30 @@ fn load_app_icon() -> egui::IconData {
 }
 
 fn print_headless_event(ev: &ProgressEvent) {
-    match ev {
-        ProgressEvent::PhaseStart { name } => eprintln!("[phase] {name}"),
-        ProgressEvent::Info { msg } => eprintln!("{msg}"),
-        ProgressEvent::Warn { msg } => eprintln!("[warn] {msg}"),
-        ProgressEvent::Error { msg } => eprintln!("[error] {msg}"),
+    if let Some(line) = headless_line(ev) {
+        eprintln!("{line}");
+    }
+}
+
+fn headless_line(ev: &ProgressEvent) -> Option<String> {
+    Some(match ev {
+        ProgressEvent::PhaseStart { name } => format!("[phase] {name}"),
+        ProgressEvent::Info { msg } => msg.clone(),
+        ProgressEvent::Warn { msg } => format!("[warn] {msg}"),
+        ProgressEvent::Error { msg } => format!("[error] {msg}"),
         ProgressEvent::Progress { done, total } => {
-            if *total > 0 {
-                let pct = (*done as f32) * 100.0 / (*total as f32);
-                eprintln!("[progress] {done}/{total} ({pct:.1}%)");
+            if *total == 0 {
+                return None;
             }
+            let pct = (*done as f32) * 100.0 / (*total as f32);
+            format!("[progress] {done}/{total} ({pct:.1}%)")
         }
-        ProgressEvent::PhaseEnd { name } => eprintln!("[done] {name}"),
-        ProgressEvent::Finished { ok } => eprintln!("[finished] ok={ok}"),
-    }
+        ProgressEvent::Metric { .. } => return None,
+        ProgressEvent::PhaseEnd { name } => format!("[done] {name}"),
+        ProgressEvent::PhaseSkipped { name, reason } => {
+            format!("[phase] {name} skipped ({reason})")
+        }
+        ProgressEvent::Finished(Ok(s)) => format!("[finished] ok=true {s}"),
+        ProgressEvent::Finished(Err(e)) => format!("[finished] ok=false error: {e}"),
+    })
 }
@@ -6,6 +6,39 @@ pub enum SvLayout {
     IkPokemon,
 }
 
+/// Writes `data` to a uniquely named temp file next to `dst`, then renames it over `dst`
+/// Readers (and a crash mid-write) see either the old file or the new one, never a truncated one.
+pub fn atomic_write(dst: &Path, data: &[u8]) -> anyhow::Result<()> {
+    let parent = match dst.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    };
+    std::fs::create_dir_all(parent)?;
+    let mut tmp = temp_file_in(parent)?;
+    // Temp files are created owner-only; keep the mode a plain rewrite would have
+    if let Ok(meta) = std::fs::metadata(dst) {
+        tmp.as_file().set_permissions(meta.permissions())?;
+    }
+    std::io::Write::write_all(&mut tmp, data)?;
+    tmp.as_file().sync_all()?;
+    tmp.persist(dst).map_err(|e| e.error)?;
+    Ok(())
+}
+
+/// New files get 0o644 less the umask, like `File::create`
+#[cfg(unix)]
+fn temp_file_in(dir: &Path) -> std::io::Result<tempfile::NamedTempFile> {
+    use std::os::unix::fs::PermissionsExt;
+    tempfile::Builder::new()
+        .permissions(std::fs::Permissions::from_mode(0o644))
+        .tempfile_in(dir)
+}
+
+#[cfg(not(unix))]
+fn temp_file_in(dir: &Path) -> std::io::Result<tempfile::NamedTempFile> {
+    tempfile::NamedTempFile::new_in(dir)
+}
+
 pub fn canonicalish(path: &Path) -> PathBuf {
     if path.as_os_str().is_empty() {
         return PathBuf::new();
@@ -13,26 +46,115 @@ pub fn canonicalish(path: &Path) -> PathBuf {
     path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
 }
 
+/// Output roots may not exist yet, so `canonicalize` can't be relied on; relative paths are
+/// anchored to the current directory instead of being left to whatever CWD later joins see.
+pub fn resolve_out_root(path: &Path) -> anyhow::Result<PathBuf> {
+    if path.as_os_str().is_empty() {
+        anyhow::bail!("Output root not set");
+    }
+    if let Ok(p) = path.canonicalize() {
+        return Ok(p);
+    }
+    if path.is_absolute() {
+        return Ok(path.to_path_buf());
+    }
+    Ok(std::env::current_dir()?.join(path))
+}
+
+/// A `/` or `\\` separated path meant to be joined under some root; rejects anything that
+/// would leave it (`..`, a leading separator, a drive prefix) so it can't point elsewhere
+pub fn check_relative_path(rel: &str) -> Result<(), String> {
+    if rel.trim().is_empty() {
+        return Err("empty path".to_string());
+    }
+    if rel.starts_with(['/', '\\']) {
+        return Err(format!("{rel:?} must be relative"));
+    }
+    if rel
+        .split(['/', '\\'])
+        .next()
+        .is_some_and(|s| s.contains(':'))
+    {
+        return Err(format!("{rel:?} must not start with a drive prefix"));
+    }
+    if rel.split(['/', '\\']).any(|s| s == "..") {
+        return Err(format!("{rel:?} must not contain `..`"));
+    }
+    Ok(())
+}
+
 pub fn detect_sv_layout(sv_root: &Path) -> Option<(SvLayout, PathBuf)> {
-    let ik = sv_root.join("ik_pokemon");
-    if ik.is_dir() {
-        return Some((SvLayout::IkPokemon, ik));
+    if looks_like_output_root(sv_root) {
+        return None;
     }
-    let p = sv_root.join("pokemon");
-    if p.is_dir() {
-        return Some((SvLayout::Pokemon, p));
+    for (layout, dir) in [
+        (SvLayout::IkPokemon, sv_root.join("ik_pokemon")),
+        (SvLayout::Pokemon, sv_root.join("pokemon")),
+    ] {
+        if dir.is_dir() && looks_like_poke_root(&dir) {
+            return Some((layout, dir));
+        }
     }
     None
 }
 
-pub fn find_under(root: &Path, rel: &str, file_name: &str) -> anyhow::Result<PathBuf> {
+/// File every run drops into its output root, whether or not it writes reports there
+pub const OUTPUT_MARKER: &str = ".svza_output";
+
+/// Marks `out_root` as written by this tool; see [`looks_like_output_root`]
+pub fn write_output_marker(out_root: &Path) -> anyhow::Result<()> {
+    std::fs::create_dir_all(out_root)?;
+    std::fs::write(out_root.join(OUTPUT_MARKER), b"")?;
+    Ok(())
+}
+
+/// True when `root` has the marker file this tool writes into an output folder
+/// `_report`/`_cache` still count for outputs written before the marker existed.
+pub fn looks_like_output_root(root: &Path) -> bool {
+    root.join(OUTPUT_MARKER).is_file()
+        || root.join("_report").is_dir()
+        || root.join("_cache").is_dir()
+}
+
+fn looks_like_poke_root(poke_root: &Path) -> bool {
+    if !poke_root.join("catalog").is_dir() {
+        return false;
+    }
+    let Ok(rd) = std::fs::read_dir(poke_root.join("data")) else {
+        return false;
+    };
+    rd.flatten().any(|e| {
+        e.file_type().map(|t| t.is_dir()).unwrap_or(false)
+            && e.file_name().to_string_lossy().starts_with("pm")
+    })
+}
+
+/// Walks `root`, optionally following symlinks. Symlink loops are dropped via walkdir's
+/// ancestor check instead of surfacing as errors; a link into a sibling tree is still
+/// walked, so followed links can yield the same file twice.
+pub fn walk_dir(
+    root: &Path,
+    follow_links: bool,
+) -> impl Iterator<Item = walkdir::Result<walkdir::DirEntry>> {
+    walkdir::WalkDir::new(root)
+        .follow_links(follow_links)
+        .into_iter()
+        .filter(|e| !matches!(e, Err(err) if err.loop_ancestor().is_some()))
+}
+
+pub fn find_under(
+    root: &Path,
+    rel: &str,
+    file_name: &str,
+    follow_links: bool,
+) -> anyhow::Result<PathBuf> {
     let candidate = root.join(rel);
     if candidate.exists() {
         return Ok(candidate);
     }
 
     let mut matches = Vec::new();
-    for entry in walkdir::WalkDir::new(root).follow_links(false) {
+    for entry in walk_dir(root, follow_links) {
         let entry = entry?;
         if !entry.file_type().is_file() {
             continue;
@@ -49,3 +171,247 @@ pub fn find_under(root: &Path, rel: &str, file_name: &str) -> anyhow::Result<Pat
     matches.sort_by_key(|p| (p.to_string_lossy().len(), p.to_string_lossy().to_string()));
     Ok(matches[0].clone())
 }
+
+/// A `pmXXXX_YY_ZZ` folder name split into species, form and variant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PmVariantName {
+    pub pm: String,
+    pub species: u16,
+    pub form: u16,
+    pub variant: u16,
+}
+
+/// `pmXXXX`: the per-species folder under `data/`.
+pub fn is_pm_dir(name: &str) -> bool {
+    let b = name.as_bytes();
+    b.len() == 6 && b.starts_with(b"pm") && b[2..].iter().all(u8::is_ascii_digit)
+}
+
+pub fn parse_pm_variant_name(name: &str) -> Option<PmVariantName> {
+    let b = name.as_bytes();
+    // ASCII-only up front so the byte-offset slicing below can't split a character
+    let ok = b.len() == 12
+        && name.is_ascii()
+        && is_pm_dir(&name[..6])
+        && b[6] == b'_'
+        && b[7..9].iter().all(u8::is_ascii_digit)
+        && b[9] == b'_'
+        && b[10..12].iter().all(u8::is_ascii_digit);
+    if !ok {
+        return None;
+    }
+    Some(PmVariantName {
+        pm: name[..6].to_string(),
+        species: name[2..6].parse().ok()?,
+        form: name[7..9].parse().ok()?,
+        variant: name[10..12].parse().ok()?,
+    })
+}
+
+/// Splits a catalog model path (`pmXXXX/pmXXXX_YY_ZZ/...`) into its pm and pm_variant folders.
+pub fn parse_model_path(model_path: &str) -> Option<(String, String)> {
+    let mp = model_path.replace('\\', "/");
+    let mut parts = mp.split('/').filter(|s| !s.is_empty());
+    let pm = parts.next()?;
+    let pm_variant = parts.next()?;
+    let parsed = parse_pm_variant_name(pm_variant)?;
+    if parsed.pm != pm {
+        return None;
+    }
+    Some((parsed.pm, pm_variant.to_string()))
+}
+
+/// Folder name of a pm_variant directory, rejecting anything that is not `pmXXXX_YY_ZZ`
+/// before it gets used to build `{pm_variant}_*` file names.
+pub fn pm_variant_dir_name(dir: &Path) -> anyhow::Result<String> {
+    let name = dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    if parse_pm_variant_name(&name).is_none() {
+        anyhow::bail!("expected a pmXXXX_YY_ZZ folder, got {dir:?}");
+    }
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn check_relative_path_cases() {
+        for ok in [
+            "avalon/data/personal_array.bin",
+            "avalon\\data\\personal_array.bin",
+            "./personal_array.bin",
+            "a..b/c",
+        ] {
+            assert_eq!(check_relative_path(ok), Ok(()), "{ok}");
+        }
+        for bad in [
+            "",
+            " ",
+            "/avalon/data/personal_array.bin",
+            "\\avalon\\personal_array.bin",
+            "C:\\dump\\personal_array.bin",
+            "C:personal_array.bin",
+            "../personal_array.bin",
+            "avalon/../../personal_array.bin",
+            "avalon\\..\\personal_array.bin",
+        ] {
+            assert!(check_relative_path(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn atomic_write_keeps_the_destination_mode() {
+        use std::os::unix::fs::PermissionsExt;
+        let td = tempfile::tempdir().unwrap();
+        let mode = |p: &Path| fs::metadata(p).unwrap().permissions().mode() & 0o777;
+
+        // File::create asks for 0o666, so this is what the umask lets through
+        let probe = td.path().join("probe");
+        fs::File::create(&probe).unwrap();
+        let fresh = td.path().join("fresh.json");
+        atomic_write(&fresh, b"a").unwrap();
+        assert_eq!(mode(&fresh), mode(&probe) & 0o644);
+
+        let existing = td.path().join("existing.json");
+        fs::write(&existing, b"old").unwrap();
+        fs::set_permissions(&existing, fs::Permissions::from_mode(0o664)).unwrap();
+        atomic_write(&existing, b"new").unwrap();
+        assert_eq!(mode(&existing), 0o664);
+        assert_eq!(fs::read(&existing).unwrap(), b"new");
+    }
+
+    /// `<root>/<poke_dir>/catalog` plus one `data/pmXXXX` folder
+    fn dump_fixture(poke_dir: &str) -> tempfile::TempDir {
+        let td = tempfile::tempdir().unwrap();
+        let poke_root = td.path().join(poke_dir);
+        fs::create_dir_all(poke_root.join("catalog")).unwrap();
+        fs::create_dir_all(poke_root.join("data").join("pm0025")).unwrap();
+        td
+    }
+
+    #[test]
+    fn detect_sv_layout_ik_pokemon() {
+        let td = dump_fixture("ik_pokemon");
+        let (layout, poke_root) = detect_sv_layout(td.path()).unwrap();
+        assert_eq!(layout, SvLayout::IkPokemon);
+        assert_eq!(poke_root, td.path().join("ik_pokemon"));
+    }
+
+    #[test]
+    fn detect_sv_layout_pokemon() {
+        let td = dump_fixture("pokemon");
+        let (layout, poke_root) = detect_sv_layout(td.path()).unwrap();
+        assert_eq!(layout, SvLayout::Pokemon);
+        assert_eq!(poke_root, td.path().join("pokemon"));
+    }
+
+    #[test]
+    fn detect_sv_layout_rejects_output_root() {
+        for marker in ["_report", "_cache"] {
+            let td = dump_fixture("ik_pokemon");
+            fs::create_dir_all(td.path().join(marker)).unwrap();
+            assert!(looks_like_output_root(td.path()));
+            assert_eq!(detect_sv_layout(td.path()), None, "{marker}");
+        }
+    }
+
+    #[test]
+    fn output_marker_alone_marks_an_output_root() {
+        // e.g. written with generate_reports=false and --report-dir elsewhere
+        let td = dump_fixture("ik_pokemon");
+        assert!(!looks_like_output_root(td.path()));
+        write_output_marker(td.path()).unwrap();
+        assert!(looks_like_output_root(td.path()));
+        assert_eq!(detect_sv_layout(td.path()), None);
+    }
+
+    #[test]
+    fn parse_pm_variant_name_cases() {
+        let ok = parse_pm_variant_name("pm0025_00_00").unwrap();
+        assert_eq!(
+            ok,
+            PmVariantName {
+                pm: "pm0025".to_string(),
+                species: 25,
+                form: 0,
+                variant: 0,
+            }
+        );
+        let ok = parse_pm_variant_name("pm1017_03_41").unwrap();
+        assert_eq!((ok.species, ok.form, ok.variant), (1017, 3, 41));
+
+        for bad in [
+            "",
+            "pk0025_00_00",
+            "PM0025_00_00",
+            "pm025_00_00",
+            "pm00025_00_00",
+            "pm0025_0_00",
+            "pm0025_000_00",
+            "pm0025_00_0",
+            "pm0025_00_000",
+            "pm0025_00_00_01",
+            "pm0025-00-00",
+            "pm0025_00_0a",
+            "pm0025_0０_00",
+            "pm000é00_00",
+            "pm0025/00_00",
+        ] {
+            assert_eq!(parse_pm_variant_name(bad), None, "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn parse_model_path_cases() {
+        let want = Some(("pm0025".to_string(), "pm0025_00_00".to_string()));
+        assert_eq!(
+            parse_model_path("pm0025/pm0025_00_00/pm0025_00_00.trmdl"),
+            want
+        );
+        assert_eq!(
+            parse_model_path("pm0025\\pm0025_00_00\\pm0025_00_00.trmdl"),
+            want
+        );
+        assert_eq!(parse_model_path("/pm0025//pm0025_00_00/x.trmdl"), want);
+        assert_eq!(parse_model_path("pm0025/pm0025_00_00"), want);
+
+        for bad in [
+            "",
+            "pm0025",
+            "pm025/pm025_00_00/x.trmdl",
+            "0025/0025_00_00/x.trmdl",
+            "data/pm0025/pm0025_00_00/x.trmdl",
+            "pm0025/pm0026_00_00/pm0026_00_00.trmdl",
+            "pm0025/pm0025_00/x.trmdl",
+        ] {
+            assert_eq!(parse_model_path(bad), None, "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn resolve_out_root_anchors_relative_paths() {
+        let rel = Path::new("svza_test_missing_out_root/Output");
+        assert!(!rel.exists());
+        let cwd = std::env::current_dir().unwrap();
+        assert_eq!(resolve_out_root(rel).unwrap(), cwd.join(rel));
+
+        let abs = cwd.join("svza_test_missing_out_root");
+        assert_eq!(resolve_out_root(&abs).unwrap(), abs);
+
+        assert!(resolve_out_root(Path::new("")).is_err());
+    }
+
+    #[test]
+    fn detect_sv_layout_needs_pm_data() {
+        let td = tempfile::tempdir().unwrap();
+        fs::create_dir_all(td.path().join("ik_pokemon").join("catalog")).unwrap();
+        fs::create_dir_all(td.path().join("ik_pokemon").join("data").join("common")).unwrap();
+        assert_eq!(detect_sv_layout(td.path()), None);
+    }
+}
@@ -0,0 +1,145 @@
+use crate::config::AppConfig;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Condvar, Mutex,
+};
+
+/// Concurrency cap for a parallel phase, built from `AppConfig::jobs`
+/// With one job, work runs in order on the calling thread with no threads spawned.
+#[derive(Debug, Clone, Copy)]
+pub struct WorkPool {
+    jobs: usize,
+}
+
+pub fn build_thread_pool(cfg: &AppConfig) -> WorkPool {
+    WorkPool::new(cfg.jobs)
+}
+
+impl WorkPool {
+    /// `jobs == 0` means one worker per available CPU
+    pub fn new(jobs: usize) -> Self {
+        let jobs = match jobs {
+            0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+            n => n,
+        };
+        Self { jobs }
+    }
+
+    pub fn jobs(&self) -> usize {
+        self.jobs
+    }
+
+    /// Calls `f(index, item)` for every item on at most `jobs` workers; results keep input order
+    pub fn map<T: Sync, R: Send>(&self, items: &[T], f: impl Fn(usize, &T) -> R + Sync) -> Vec<R> {
+        let workers = self.jobs.min(items.len());
+        if workers <= 1 {
+            return items.iter().enumerate().map(|(i, t)| f(i, t)).collect();
+        }
+
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new((0..items.len()).map(|_| None).collect::<Vec<Option<R>>>());
+        std::thread::scope(|s| {
+            for _ in 0..workers {
+                s.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(item) = items.get(i) else {
+                        break;
+                    };
+                    let r = f(i, item);
+                    if let Ok(mut slots) = results.lock() {
+                        slots[i] = Some(r);
+                    }
+                });
+            }
+        });
+        // scope() re-raises a worker panic, so every slot is filled here
+        results
+            .into_inner()
+            .unwrap_or_default()
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+}
+
+/// Counting semaphore; caps how many holders run at once (e.g. child processes)
+/// independently of how many `WorkPool` workers are alive.
+#[derive(Debug)]
+pub struct Semaphore {
+    permits: Mutex<usize>,
+    freed: Condvar,
+    total: usize,
+}
+
+impl Semaphore {
+    /// At least one permit, so `acquire` can never block forever
+    pub fn new(permits: usize) -> Self {
+        let total = permits.max(1);
+        Self {
+            permits: Mutex::new(total),
+            freed: Condvar::new(),
+            total,
+        }
+    }
+
+    pub fn permits(&self) -> usize {
+        self.total
+    }
+
+    /// Blocks until a permit is free; it is returned when the guard drops
+    pub fn acquire(&self) -> SemaphorePermit<'_> {
+        let mut n = self.permits.lock().unwrap_or_else(|e| e.into_inner());
+        while *n == 0 {
+            n = self.freed.wait(n).unwrap_or_else(|e| e.into_inner());
+        }
+        *n -= 1;
+        SemaphorePermit { sem: self }
+    }
+}
+
+pub struct SemaphorePermit<'a> {
+    sem: &'a Semaphore,
+}
+
+impl Drop for SemaphorePermit<'_> {
+    fn drop(&mut self) {
+        *self.sem.permits.lock().unwrap_or_else(|e| e.into_inner()) += 1;
+        self.sem.freed.notify_one();
+    }
+}
+
+/// Texture-phase concurrency: workers per run plus the ultimate_tex_cli slots
+/// One value can back several runs at once (`--batch`), which then share the `--tex-jobs` slots.
+#[derive(Debug)]
+pub struct TexLimits {
+    /// Workers converting files side by side within one run
+    pub pool: WorkPool,
+    /// Caps concurrent ultimate_tex_cli processes across every run using this value
+    pub tool_slots: Semaphore,
+}
+
+impl TexLimits {
+    /// `jobs` workers and `tex_jobs` slots (0 = one per worker)
+    pub fn for_run(cfg: &AppConfig) -> Self {
+        Self {
+            pool: build_thread_pool(cfg),
+            tool_slots: Semaphore::new(tex_slots(cfg)),
+        }
+    }
+
+    /// `--batch` already runs `jobs` entries at once, so each entry converts its textures in
+    /// order and every entry draws from the same slots
+    pub fn for_batch(cfg: &AppConfig) -> Self {
+        Self {
+            pool: WorkPool::new(1),
+            tool_slots: Semaphore::new(tex_slots(cfg)),
+        }
+    }
+}
+
+fn tex_slots(cfg: &AppConfig) -> usize {
+    match cfg.tex_jobs {
+        0 => build_thread_pool(cfg).jobs(),
+        n => n,
+    }
+}
@@ -1,25 +1,154 @@
-use std::sync::mpsc;
+use serde::Serialize;
+use std::{
+    fmt,
+    sync::{mpsc, Arc, Mutex},
+};
+
+/// End-of-run counts carried by `ProgressEvent::Finished` on success
+#[derive(Debug, Clone, Default)]
+pub struct RunSummary {
+    pub species_entries: usize,
+    pub pm_variants: usize,
+    pub copied: usize,
+    pub anim_filled: usize,
+    pub anim_missing_after: usize,
+    pub canceled: bool,
+    /// Every warning the run reported, in order; one per `ProgressEvent::Warn`
+    pub warnings: Vec<RunWarning>,
+    /// `(name, reason)` of each phase that did not run; a skipped phase is not a warning
+    pub skipped_phases: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WarningKind {
+    /// A file the catalog or a phase expects is not in the dump or output
+    MissingAsset,
+    /// A donor (template assignment or param entry) could not be resolved
+    MissingDonor,
+    Texture,
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RunWarning {
+    pub kind: WarningKind,
+    pub msg: String,
+}
+
+impl RunSummary {
+    pub fn canceled(self) -> Self {
+        Self {
+            canceled: true,
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for RunSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "entries={} pm_variants={} copied={} anim_filled={} anim_missing_after={}",
+            self.species_entries,
+            self.pm_variants,
+            self.copied,
+            self.anim_filled,
+            self.anim_missing_after
+        )?;
+        if !self.warnings.is_empty() {
+            write!(f, " warnings={}", self.warnings.len())?;
+        }
+        if self.canceled {
+            write!(f, " (canceled)")?;
+        }
+        Ok(())
+    }
+}
 
 #[derive(Debug, Clone)]
 pub enum ProgressEvent {
-    PhaseStart { name: String },
-    Progress { done: u64, total: u64 },
-    Info { msg: String },
-    Warn { msg: String },
-    Error { msg: String },
-    PhaseEnd { name: String },
-    Finished { ok: bool },
+    PhaseStart {
+        name: String,
+    },
+    Progress {
+        done: u64,
+        total: u64,
+    },
+    Info {
+        msg: String,
+    },
+    Warn {
+        msg: String,
+    },
+    Error {
+        msg: String,
+    },
+    Metric {
+        key: String,
+        value: i64,
+    },
+    PhaseEnd {
+        name: String,
+    },
+    /// The phase did not run (disabled, or a prerequisite is missing); sent instead of `PhaseEnd`
+    PhaseSkipped {
+        name: String,
+        reason: String,
+    },
+    /// The run's summary, or the error that ended it
+    Finished(Result<RunSummary, String>),
 }
 
 #[derive(Clone)]
 pub struct ProgressSink {
     tx: mpsc::Sender<ProgressEvent>,
+    verbosity: u8,
+    /// Shared by every clone so the run can hand the full list back in its summary
+    warnings: Arc<Mutex<Vec<RunWarning>>>,
+    skipped_phases: Arc<Mutex<Vec<(String, String)>>>,
 }
 
 impl ProgressSink {
     pub fn new() -> (Self, mpsc::Receiver<ProgressEvent>) {
         let (tx, rx) = mpsc::channel();
-        (Self { tx }, rx)
+        (
+            Self {
+                tx,
+                verbosity: 0,
+                warnings: Arc::default(),
+                skipped_phases: Arc::default(),
+            },
+            rx,
+        )
+    }
+
+    /// Warnings recorded so far by this sink or any of its clones
+    pub fn warnings(&self) -> Vec<RunWarning> {
+        self.warnings.lock().map(|w| w.clone()).unwrap_or_default()
+    }
+
+    /// Phases skipped so far by this sink or any of its clones
+    pub fn skipped_phases(&self) -> Vec<(String, String)> {
+        self.skipped_phases
+            .lock()
+            .map(|s| s.clone())
+            .unwrap_or_default()
+    }
+
+    fn record(&self, kind: WarningKind, msg: &str) {
+        if let Ok(mut w) = self.warnings.lock() {
+            w.push(RunWarning {
+                kind,
+                msg: msg.to_string(),
+            });
+        }
+    }
+
+    /// Sets the threshold for `detail` lines; events above it are dropped before the channel
+    pub fn with_verbosity(mut self, verbosity: u8) -> Self {
+        self.verbosity = verbosity;
+        self
     }
 
     pub fn send(&self, ev: ProgressEvent) {
@@ -34,6 +163,14 @@ impl ProgressSink {
         self.send(ProgressEvent::PhaseEnd { name: name.into() });
     }
 
+    pub fn phase_skipped(&self, name: impl Into<String>, reason: impl Into<String>) {
+        let (name, reason) = (name.into(), reason.into());
+        if let Ok(mut s) = self.skipped_phases.lock() {
+            s.push((name.clone(), reason.clone()));
+        }
+        self.send(ProgressEvent::PhaseSkipped { name, reason });
+    }
+
     pub fn progress(&self, done: u64, total: u64) {
         self.send(ProgressEvent::Progress { done, total });
     }
@@ -42,15 +179,35 @@ impl ProgressSink {
         self.send(ProgressEvent::Info { msg: msg.into() });
     }
 
+    /// Info line only shown at `-v` (level 1, per-variant) or `-vv` (level 2, per-file)
+    pub fn detail(&self, level: u8, msg: impl Into<String>) {
+        if level <= self.verbosity {
+            self.info(msg);
+        }
+    }
+
     pub fn warn(&self, msg: impl Into<String>) {
-        self.send(ProgressEvent::Warn { msg: msg.into() });
+        self.warn_kind(WarningKind::Other, msg);
+    }
+
+    pub fn warn_kind(&self, kind: WarningKind, msg: impl Into<String>) {
+        let msg = msg.into();
+        self.record(kind, &msg);
+        self.send(ProgressEvent::Warn { msg });
     }
 
     pub fn error(&self, msg: impl Into<String>) {
         self.send(ProgressEvent::Error { msg: msg.into() });
     }
 
-    pub fn finished(&self, ok: bool) {
-        self.send(ProgressEvent::Finished { ok });
+    pub fn metric(&self, key: impl Into<String>, value: i64) {
+        self.send(ProgressEvent::Metric {
+            key: key.into(),
+            value,
+        });
+    }
+
+    pub fn finished(&self, result: Result<RunSummary, String>) {
+        self.send(ProgressEvent::Finished(result));
     }
 }
@@ -1,8 +1,10 @@
 use serde::{Deserialize, Serialize};
 use std::{
     collections::{BTreeMap, BTreeSet},
-    fs,
+    fmt, fs,
+    ops::RangeInclusive,
     path::{Path, PathBuf},
+    str::FromStr,
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
@@ -32,6 +34,125 @@ impl From<Key> for crate::fb::trpmcatalog::SpeciesKey {
     }
 }
 
+/// `species[:form[:gender]]`; a missing form or gender is 0
+impl FromStr for Key {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let bad = || anyhow::anyhow!("bad key {s:?} (expected `species[:form[:gender]]`)");
+        let mut parts = s.trim().split(':').map(str::trim);
+        let species = parts.next().unwrap_or("").parse().map_err(|_| bad())?;
+        let form = parts.next().map_or(Ok(0), str::parse).map_err(|_| bad())?;
+        let gender = parts.next().map_or(Ok(0), str::parse).map_err(|_| bad())?;
+        if parts.next().is_some() {
+            return Err(bad());
+        }
+        Ok(Self {
+            species,
+            form,
+            gender,
+        })
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.species, self.form, self.gender)
+    }
+}
+
+/// A set of keys written `species[:form[:gender]]`, where species may be a range (`25-30`)
+/// Omitted parts match anything, so `25` is every form and gender of 25, `25:1` every gender of form 1.
+/// An empty form before a gender (`25::1`) matches gender 1 of every form.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyRange {
+    pub species: RangeInclusive<u16>,
+    pub form: Option<u16>,
+    pub gender: Option<u8>,
+}
+
+impl KeyRange {
+    pub fn matches(&self, key: &Key) -> bool {
+        self.species.contains(&key.species)
+            && self.form.is_none_or(|f| f == key.form)
+            && self.gender.is_none_or(|g| g == key.gender)
+    }
+
+    /// Comma-separated ranges from each token, skipping empty items (`"25, 26:1"` is two)
+    pub fn parse_list(tokens: &[String]) -> anyhow::Result<Vec<Self>> {
+        tokens
+            .iter()
+            .flat_map(|t| t.split(','))
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(str::parse)
+            .collect()
+    }
+}
+
+impl FromStr for KeyRange {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let bad = || {
+            anyhow::anyhow!("bad key range {s:?} (expected `species[:form[:gender]]`, e.g. `25`, `25:1`, `25-30`)")
+        };
+        let mut parts = s.trim().split(':').map(str::trim);
+        let species = parts.next().unwrap_or("");
+        let species = match species.split_once('-') {
+            Some((lo, hi)) => {
+                let lo: u16 = lo.trim().parse().map_err(|_| bad())?;
+                let hi: u16 = hi.trim().parse().map_err(|_| bad())?;
+                if lo > hi {
+                    return Err(bad());
+                }
+                lo..=hi
+            }
+            None => {
+                let n: u16 = species.parse().map_err(|_| bad())?;
+                n..=n
+            }
+        };
+        let form = parts.next();
+        let gender = parts.next();
+        if parts.next().is_some() {
+            return Err(bad());
+        }
+        let form = match (form, gender) {
+            (Some(""), Some(_)) => None,
+            _ => form.map(str::parse).transpose().map_err(|_| bad())?,
+        };
+        let gender = gender.map(str::parse).transpose().map_err(|_| bad())?;
+        Ok(Self {
+            species,
+            form,
+            gender,
+        })
+    }
+}
+
+impl fmt::Display for KeyRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (lo, hi) = (self.species.start(), self.species.end());
+        if lo == hi {
+            write!(f, "{lo}")?;
+        } else {
+            write!(f, "{lo}-{hi}")?;
+        }
+        match (self.form, self.gender) {
+            (None, None) => Ok(()),
+            (Some(form), None) => write!(f, ":{form}"),
+            (form, Some(gender)) => {
+                write!(f, ":")?;
+                if let Some(form) = form {
+                    write!(f, "{form}")?;
+                }
+                write!(f, ":{gender}")
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Assignment {
     pub target: Key,
@@ -72,6 +193,34 @@ impl DonorTemplate {
         self.selected_targets.iter().copied().collect()
     }
 
+    /// Orders `selected_targets` by (species, form, gender); returns true if anything moved
+    pub fn sort_selected_targets(&mut self) -> bool {
+        if self.selected_targets.windows(2).all(|w| w[0] <= w[1]) {
+            return false;
+        }
+        self.selected_targets.sort();
+        true
+    }
+
+    /// Selected or assigned targets not in `known`, e.g. from a template made against another dump
+    pub fn unknown_targets(&self, known: &BTreeSet<Key>) -> Vec<Key> {
+        let all: BTreeSet<Key> = self
+            .selected_targets
+            .iter()
+            .copied()
+            .chain(self.assignments.iter().map(|a| a.target))
+            .collect();
+        all.into_iter().filter(|k| !known.contains(k)).collect()
+    }
+
+    /// Removes `targets` from the selection and their assignments; returns how many entries went
+    pub fn drop_targets(&mut self, targets: &BTreeSet<Key>) -> usize {
+        let before = self.selected_targets.len() + self.assignments.len();
+        self.selected_targets.retain(|k| !targets.contains(k));
+        self.assignments.retain(|a| !targets.contains(&a.target));
+        before - self.selected_targets.len() - self.assignments.len()
+    }
+
     pub fn assignment_map(&self) -> BTreeMap<Key, Key> {
         let mut out = BTreeMap::new();
         for a in &self.assignments {
@@ -141,3 +290,121 @@ pub fn preferred_template_dirs() -> Vec<PathBuf> {
 
     out
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(species: u16, form: u16, gender: u8) -> Key {
+        Key {
+            species,
+            form,
+            gender,
+        }
+    }
+
+    #[test]
+    fn key_parse_and_display() {
+        assert_eq!("25".parse::<Key>().unwrap(), key(25, 0, 0));
+        assert_eq!(" 25:1 ".parse::<Key>().unwrap(), key(25, 1, 0));
+        assert_eq!("25 : 1 : 1".parse::<Key>().unwrap(), key(25, 1, 1));
+        for k in [key(25, 0, 0), key(1017, 3, 2)] {
+            assert_eq!(k.to_string().parse::<Key>().unwrap(), k);
+        }
+        assert_eq!(key(25, 1, 1).to_string(), "25:1:1");
+        for bad in ["", "x", "25:", "25:1:1:1", "25-30", "70000", "25:1:256"] {
+            assert!(bad.parse::<Key>().is_err(), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn key_range_parse() {
+        let r: KeyRange = "25-30:1".parse().unwrap();
+        assert_eq!(r.species, 25..=30);
+        assert_eq!((r.form, r.gender), (Some(1), None));
+        let r: KeyRange = "25::1".parse().unwrap();
+        assert_eq!((r.form, r.gender), (None, Some(1)));
+        for bad in ["", "25:", "25::", "30-25", "25-", "25:1:1:1", "a-b", "25:x"] {
+            assert!(bad.parse::<KeyRange>().is_err(), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn key_range_display_round_trips() {
+        for (species, form, gender) in [
+            (25..=25, None, None),
+            (25..=30, None, None),
+            (25..=25, Some(1), None),
+            (25..=25, None, Some(1)),
+            (25..=30, Some(0), Some(1)),
+        ] {
+            let r = KeyRange {
+                species,
+                form,
+                gender,
+            };
+            let text = r.to_string();
+            assert_eq!(text.parse::<KeyRange>().unwrap(), r, "{text}");
+        }
+        assert_eq!(
+            "25 - 30 : 1".parse::<KeyRange>().unwrap().to_string(),
+            "25-30:1"
+        );
+    }
+
+    #[test]
+    fn key_range_matches() {
+        let any_form_female: KeyRange = "25::1".parse().unwrap();
+        assert!(any_form_female.matches(&key(25, 3, 1)));
+        assert!(!any_form_female.matches(&key(25, 3, 0)));
+
+        let range: KeyRange = "25-27".parse().unwrap();
+        assert!(range.matches(&key(25, 0, 0)));
+        assert!(range.matches(&key(27, 9, 2)));
+        assert!(!range.matches(&key(24, 0, 0)));
+        assert!(!range.matches(&key(28, 0, 0)));
+
+        let exact: KeyRange = "25:1:0".parse().unwrap();
+        assert!(exact.matches(&key(25, 1, 0)));
+        assert!(!exact.matches(&key(25, 1, 1)));
+        assert!(!exact.matches(&key(25, 0, 0)));
+    }
+
+    #[test]
+    fn parse_list_splits_commas_and_skips_blanks() {
+        let tokens = vec![
+            "25, 26:1".to_string(),
+            " ".to_string(),
+            "30-31,".to_string(),
+        ];
+        let list = KeyRange::parse_list(&tokens).unwrap();
+        let text: Vec<_> = list.iter().map(ToString::to_string).collect();
+        assert_eq!(text, ["25", "26:1", "30-31"]);
+        assert!(KeyRange::parse_list(&["25,x".to_string()]).is_err());
+    }
+
+    #[test]
+    fn unknown_targets_are_reported_and_dropped() {
+        let mut tpl = DonorTemplate {
+            selected_targets: vec![key(25, 0, 0), key(26, 0, 0), key(9999, 0, 0)],
+            ..DonorTemplate::default()
+        };
+        tpl.set_assignment(key(25, 0, 0), key(133, 0, 0));
+        tpl.set_assignment(key(9999, 0, 0), key(133, 0, 0));
+        // assigned but never selected
+        tpl.set_assignment(key(8888, 1, 0), key(133, 0, 0));
+        let known = BTreeSet::from([key(25, 0, 0), key(26, 0, 0)]);
+
+        let unknown = tpl.unknown_targets(&known);
+        assert_eq!(unknown, [key(8888, 1, 0), key(9999, 0, 0)]);
+
+        let dropped = tpl.drop_targets(&unknown.into_iter().collect());
+        assert_eq!(dropped, 3);
+        assert_eq!(tpl.selected_targets, [key(25, 0, 0), key(26, 0, 0)]);
+        assert_eq!(
+            tpl.assignment_map(),
+            BTreeMap::from([(key(25, 0, 0), key(133, 0, 0))])
+        );
+        assert!(tpl.unknown_targets(&known).is_empty());
+    }
+}
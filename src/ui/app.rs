@@ -1,14 +1,19 @@
 use crate::{
     backend,
     cancel::CancelToken,
-    config::AppConfig,
-    progress::{ProgressEvent, ProgressSink},
-    ui::donors::DonorsUi,
+    config::{AppConfig, IconDonorStrategy, TextureScope},
+    error::SvzaError,
+    progress::{ProgressEvent, ProgressSink, RunSummary},
+    ui::{donors::DonorsUi, util::open_folder},
 };
 use eframe::egui;
+use std::collections::{BTreeMap, VecDeque};
 use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
+/// Lines kept in the Progress tab; older ones are dropped and counted in `logs_trimmed`.
+const MAX_LOG_LINES: usize = 5000;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Tab {
     Donors,
@@ -28,12 +33,26 @@ pub struct SvZaApp {
     progress_rx: Option<std::sync::mpsc::Receiver<ProgressEvent>>,
 
     phase: String,
+    /// Phases reported as skipped this run, with the reason
+    skipped_phases: Vec<(String, String)>,
     done: u64,
     total: u64,
-    logs: Vec<String>,
+    logs: VecDeque<String>,
+    logs_trimmed: usize,
+    /// Progress tab log filter: case-insensitive substring, plus `[warn]` / `[error]` chips
+    log_search: String,
+    log_only_warn: bool,
+    log_only_error: bool,
+    metrics: BTreeMap<String, i64>,
+    last_result: Option<Result<RunSummary, String>>,
 
     tab: Tab,
     donors_ui: DonorsUi,
+    /// Set when Run was pressed on a non-empty output folder; holds the summary shown in the confirm dialog.
+    confirm_overwrite: Option<String>,
+    /// Set when "Clean output…" was pressed; holds the subtrees the confirm dialog will delete.
+    confirm_clean: Option<Vec<PathBuf>>,
+    confirm_prune: Option<Vec<PathBuf>>,
 }
 
 impl SvZaApp {
@@ -49,10 +68,20 @@ impl SvZaApp {
             cancel: None,
             progress_rx: None,
             phase: String::new(),
+            skipped_phases: Vec::new(),
             done: 0,
             total: 0,
-            logs: Vec::new(),
+            logs: VecDeque::new(),
+            logs_trimmed: 0,
+            log_search: String::new(),
+            log_only_warn: false,
+            log_only_error: false,
+            metrics: BTreeMap::new(),
+            last_result: None,
             tab: Tab::Donors,
+            confirm_overwrite: None,
+            confirm_clean: None,
+            confirm_prune: None,
         }
     }
 
@@ -69,30 +98,220 @@ impl SvZaApp {
             match ev {
                 ProgressEvent::PhaseStart { name } => {
                     self.phase = name.clone();
-                    self.logs.push(format!("[phase] {name}"));
+                    self.push_log(format!("[phase] {name}"));
                     self.done = 0;
                     self.total = 0;
                 }
                 ProgressEvent::PhaseEnd { name } => {
-                    self.logs.push(format!("[done] {name}"));
+                    self.push_log(format!("[done] {name}"));
+                }
+                ProgressEvent::PhaseSkipped { name, reason } => {
+                    self.push_log(format!("[skipped] {name} ({reason})"));
+                    self.skipped_phases.push((name, reason));
                 }
                 ProgressEvent::Progress { done, total } => {
                     self.done = done;
                     self.total = total;
                 }
-                ProgressEvent::Info { msg } => self.logs.push(msg),
-                ProgressEvent::Warn { msg } => self.logs.push(format!("[warn] {msg}")),
-                ProgressEvent::Error { msg } => self.logs.push(format!("[error] {msg}")),
-                ProgressEvent::Finished { ok } => {
+                ProgressEvent::Info { msg } => self.push_log(msg),
+                ProgressEvent::Warn { msg } => self.push_log(format!("[warn] {msg}")),
+                ProgressEvent::Error { msg } => self.push_log(format!("[error] {msg}")),
+                ProgressEvent::Metric { key, value } => {
+                    self.metrics.insert(key, value);
+                }
+                ProgressEvent::Finished(result) => {
                     self.running = false;
                     self.cancel = None;
                     self.progress_rx = None;
-                    self.logs.push(format!("[run] finished ok={ok}"));
+                    self.push_log(format!("[run] finished ok={}", result.is_ok()));
+                    self.last_result = Some(result);
                 }
             }
         }
     }
 
+    fn push_log(&mut self, line: String) {
+        if self.logs.len() >= MAX_LOG_LINES {
+            self.logs.pop_front();
+            self.logs_trimmed += 1;
+        }
+        self.logs.push_back(line);
+    }
+
+    /// Whether `line` passes the Progress tab filter; with no chip on, every level shows
+    fn log_line_visible(&self, line: &str, needle: &str) -> bool {
+        let level_ok = (!self.log_only_warn && !self.log_only_error)
+            || (self.log_only_warn && line.starts_with("[warn]"))
+            || (self.log_only_error && line.starts_with("[error]"));
+        level_ok && (needle.is_empty() || line.to_lowercase().contains(needle))
+    }
+
+    fn logs_text(&self) -> String {
+        let mut out = String::new();
+        if self.logs_trimmed > 0 {
+            out.push_str(&format!(
+                "[log] {} earlier lines trimmed (buffer keeps last {MAX_LOG_LINES})\n",
+                self.logs_trimmed
+            ));
+        }
+        for line in &self.logs {
+            out.push_str(line);
+            out.push('\n');
+        }
+        out
+    }
+
+    fn request_run(&mut self) {
+        match self.cfg.out_root.as_deref().and_then(overwrite_summary) {
+            Some(summary) => self.confirm_overwrite = Some(summary),
+            None => {
+                self.start_run();
+                self.tab = Tab::Progress;
+            }
+        }
+    }
+
+    fn show_confirm_overwrite(&mut self, ctx: &egui::Context) {
+        let Some(summary) = &self.confirm_overwrite else {
+            return;
+        };
+        let mut proceed = false;
+        let mut cancel = false;
+        egui::Window::new("Output folder is not empty")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.label(summary);
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Overwrite and run").clicked() {
+                        proceed = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+        if proceed {
+            self.confirm_overwrite = None;
+            self.start_run();
+            self.tab = Tab::Progress;
+        } else if cancel {
+            self.confirm_overwrite = None;
+        }
+    }
+
+    fn show_confirm_clean(&mut self, ctx: &egui::Context) {
+        let Some(subtrees) = &self.confirm_clean else {
+            return;
+        };
+        let mut proceed = false;
+        let mut cancel = false;
+        egui::Window::new("Clean output folder")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.label("These folders will be deleted (other files are left alone):");
+                for p in subtrees {
+                    ui.label(format!("  {}", p.display()));
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Delete").clicked() {
+                        proceed = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+        if proceed {
+            self.confirm_clean = None;
+            self.clean_output();
+        } else if cancel {
+            self.confirm_clean = None;
+        }
+    }
+
+    fn show_confirm_prune(&mut self, ctx: &egui::Context) {
+        let Some(backups) = &self.confirm_prune else {
+            return;
+        };
+        let mut proceed = false;
+        let mut cancel = false;
+        egui::Window::new("Prune backups")
+            .collapsible(false)
+            .resizable(true)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                if backups.is_empty() {
+                    ui.label("No backup files under the output folder.");
+                } else {
+                    ui.label(format!("{} backup file(s) will be deleted:", backups.len()));
+                    egui::ScrollArea::vertical()
+                        .max_height(240.0)
+                        .show(ui, |ui| {
+                            for p in backups {
+                                ui.label(format!("  {}", p.display()));
+                            }
+                        });
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(!backups.is_empty(), egui::Button::new("Delete"))
+                        .clicked()
+                    {
+                        proceed = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+        if proceed {
+            self.confirm_prune = None;
+            self.drain_maintenance(|cfg, sink| {
+                backend::clean::prune_backups(cfg, false, sink)
+                    .map(|_| ())
+                    .map_err(|e| anyhow::anyhow!("prune failed: {e:#}"))
+            });
+        } else if cancel {
+            self.confirm_prune = None;
+        }
+    }
+
+    fn clean_output(&mut self) {
+        self.drain_maintenance(|cfg, sink| {
+            backend::clean::clean_output(cfg, sink)
+                .map(|_| ())
+                .map_err(|e| anyhow::anyhow!("clean failed: {e:#}"))
+        });
+    }
+
+    /// Runs a quick output-folder operation on the UI thread and copies its messages into the log
+    fn drain_maintenance(
+        &mut self,
+        op: impl FnOnce(&AppConfig, &ProgressSink) -> anyhow::Result<()>,
+    ) {
+        let (sink, rx) = ProgressSink::new();
+        if let Err(e) = op(&self.cfg, &sink) {
+            sink.error(format!("{e:#}"));
+        }
+        drop(sink);
+        while let Ok(ev) = rx.recv() {
+            match ev {
+                ProgressEvent::Info { msg } => self.push_log(msg),
+                ProgressEvent::Warn { msg } => self.push_log(format!("[warn] {msg}")),
+                ProgressEvent::Error { msg } => self.push_log(format!("[error] {msg}")),
+                _ => {}
+            }
+        }
+        self.tab = Tab::Progress;
+    }
+
     fn start_run(&mut self) {
         if self.running {
             return;
@@ -108,16 +327,20 @@ impl SvZaApp {
         let reporter = sink.clone();
 
         self.running = true;
+        self.metrics.clear();
+        self.skipped_phases.clear();
+        self.last_result = None;
         self.cancel = Some(cancel.clone());
         self.progress_rx = Some(rx);
 
-        std::thread::spawn(move || {
-            let res = backend::run(&cfg, sink, cancel);
-            if let Err(e) = res {
+        std::thread::spawn(move || match backend::run(&cfg, sink, cancel) {
+            Ok(summary) => reporter.finished(Ok(summary)),
+            Err(e) => {
                 reporter.error(format!("run failed: {e:#}"));
-                reporter.finished(false);
-            } else {
-                reporter.finished(true);
+                if let Some(kind) = e.downcast_ref::<SvzaError>() {
+                    reporter.info(format!("[hint] {}", kind.hint()));
+                }
+                reporter.finished(Err(format!("{e:#}")));
             }
         });
     }
@@ -216,13 +439,19 @@ impl eframe::App for SvZaApp {
                             c.cancel();
                         }
                     }
-                } else if ui.button("Run").clicked() {
-                    self.start_run();
-                    self.tab = Tab::Progress;
+                } else if ui
+                    .add_enabled(self.confirm_overwrite.is_none(), egui::Button::new("Run"))
+                    .clicked()
+                {
+                    self.request_run();
                 }
             });
         });
 
+        self.show_confirm_overwrite(ctx);
+        self.show_confirm_clean(ctx);
+        self.show_confirm_prune(ctx);
+
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.separator();
 
@@ -233,36 +462,123 @@ impl eframe::App for SvZaApp {
             });
 
             ui.separator();
+            let locked = self.running;
+            if locked {
+                ui.colored_label(
+                    egui::Color32::YELLOW,
+                    "Run in progress: paths, settings and donor assignments are locked until it \
+                     finishes or is canceled.",
+                );
+            }
             ui.label("Paths");
-            cfg_changed |= Self::dir_picker_row(ui, "ZA dump", &mut self.cfg.za_dump);
-            cfg_changed |= Self::dir_picker_row(ui, "SV dump", &mut self.cfg.sv_root);
-            cfg_changed |= Self::dir_picker_row(ui, "Output", &mut self.cfg.out_root);
+            ui.add_enabled_ui(!locked, |ui| {
+                cfg_changed |= Self::dir_picker_row(ui, "ZA dump", &mut self.cfg.za_dump);
+                cfg_changed |= Self::dir_picker_row(ui, "SV dump", &mut self.cfg.sv_root);
+                cfg_changed |= Self::dir_picker_row(ui, "Output", &mut self.cfg.out_root);
+            });
+            let out_dir = self.cfg.out_root.clone().filter(|p| p.is_dir());
+            // a layeredfs layout without a valid title id falls back to the plain layout here;
+            // the run itself reports the error
+            let game_dir = out_dir
+                .as_deref()
+                .map(|p| self.cfg.game_root(p).unwrap_or_else(|_| p.to_path_buf()));
             ui.horizontal(|ui| {
-                cfg_changed |= ui
-                    .checkbox(&mut self.cfg.texture_convert, "Convert textures")
-                    .changed();
+                if ui
+                    .add_enabled(out_dir.is_some(), egui::Button::new("Open output folder"))
+                    .clicked()
+                {
+                    if let Some(p) = &out_dir {
+                        open_folder(p);
+                    }
+                }
+                let subtrees = out_dir
+                    .as_deref()
+                    .zip(game_dir.as_deref())
+                    .map(|(o, g)| backend::clean::existing_subtrees(o, g))
+                    .unwrap_or_default();
+                if ui
+                    .add_enabled(
+                        !self.running && !subtrees.is_empty(),
+                        egui::Button::new("Clean output…"),
+                    )
+                    .clicked()
+                {
+                    self.confirm_clean = Some(subtrees);
+                }
+                if ui
+                    .add_enabled(
+                        !self.running && out_dir.as_deref().is_some_and(|p| p.is_dir()),
+                        egui::Button::new("Prune backups…"),
+                    )
+                    .on_hover_text("Delete the .bak files earlier runs left in the output folder")
+                    .clicked()
+                {
+                    if let (Some(o), Some(g)) = (out_dir.as_deref(), game_dir.as_deref()) {
+                        self.confirm_prune = Some(backend::clean::find_backups(o, g));
+                    }
+                }
+            });
+            ui.add_enabled_ui(!locked, |ui| {
+                ui.horizontal(|ui| {
+                    cfg_changed |= ui
+                        .checkbox(&mut self.cfg.texture_convert, "Convert textures")
+                        .changed();
+                    ui.add_enabled_ui(self.cfg.texture_convert, |ui| {
+                        cfg_changed |= Self::file_picker_row(
+                            ui,
+                            "ultimate_tex_cli",
+                            &mut self.cfg.ultimate_tex_cli,
+                        );
+                    });
+                });
                 ui.add_enabled_ui(self.cfg.texture_convert, |ui| {
+                    cfg_changed |= ui
+                        .checkbox(
+                            &mut self.cfg.texture_allow_resize,
+                            "Allow texture resize (icons)",
+                        )
+                        .changed();
                     cfg_changed |= Self::file_picker_row(
                         ui,
-                        "ultimate_tex_cli",
-                        &mut self.cfg.ultimate_tex_cli,
+                        "tex format map (json)",
+                        &mut self.cfg.tex_format_map,
                     );
+                    let mut icons_only = self.cfg.texture_scope == TextureScope::IconsOnly;
+                    if ui.checkbox(&mut icons_only, "Icons only").changed() {
+                        self.cfg.texture_scope = if icons_only {
+                            TextureScope::IconsOnly
+                        } else {
+                            TextureScope::All
+                        };
+                        cfg_changed = true;
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label("Fallback icon donor");
+                        for (v, label) in [
+                            (IconDonorStrategy::MostCommon, "Most common"),
+                            (IconDonorStrategy::Largest, "Largest"),
+                            (IconDonorStrategy::PinnedPath, "Pinned"),
+                        ] {
+                            cfg_changed |= ui
+                                .radio_value(&mut self.cfg.icon_donor_strategy, v, label)
+                                .changed();
+                        }
+                    });
+                    if self.cfg.icon_donor_strategy == IconDonorStrategy::PinnedPath {
+                        cfg_changed |= Self::file_picker_row(
+                            ui,
+                            "icon donor (bntx)",
+                            &mut self.cfg.icon_donor_path,
+                        );
+                    }
+                });
+                cfg_changed |= Self::file_picker_row(ui, "flatc", &mut self.cfg.flatc);
+                cfg_changed |=
+                    Self::dir_picker_row(ui, "pkNX personal dir", &mut self.cfg.pknx_personal_dir);
+                ui.horizontal(|ui| {
+                    ui.label("Language (ik_message/dat/...)");
+                    cfg_changed |= ui.text_edit_singleline(&mut self.cfg.language).changed();
                 });
-            });
-            ui.add_enabled_ui(self.cfg.texture_convert, |ui| {
-                cfg_changed |= ui
-                    .checkbox(
-                        &mut self.cfg.texture_allow_resize,
-                        "Allow texture resize (icons)",
-                    )
-                    .changed();
-            });
-            cfg_changed |= Self::file_picker_row(ui, "flatc", &mut self.cfg.flatc);
-            cfg_changed |=
-                Self::dir_picker_row(ui, "pkNX personal dir", &mut self.cfg.pknx_personal_dir);
-            ui.horizontal(|ui| {
-                ui.label("Language (ik_message/dat/...)");
-                cfg_changed |= ui.text_edit_singleline(&mut self.cfg.language).changed();
             });
 
             if cfg_changed {
@@ -272,10 +588,12 @@ impl eframe::App for SvZaApp {
             ui.separator();
             match self.tab {
                 Tab::Donors => {
-                    self.donors_ui.ui(ui, &mut self.cfg);
+                    ui.add_enabled_ui(!locked, |ui| {
+                        self.donors_ui.ui(ui, &mut self.cfg);
+                    });
                 }
                 Tab::Legacy => {
-                    ui.add_enabled_ui(self.cfg.legacy_mode, |ui| {
+                    ui.add_enabled_ui(self.cfg.legacy_mode && !locked, |ui| {
                         ui.label("Toggles");
                         cfg_changed |= ui
                             .checkbox(&mut self.cfg.use_za_base_config, "Use ZA base-config donor")
@@ -333,18 +651,95 @@ impl eframe::App for SvZaApp {
                         }
                     ));
                     ui.add(egui::ProgressBar::new(pct / 100.0).text(format!("{pct:.1}%")));
+                    for (name, reason) in &self.skipped_phases {
+                        ui.colored_label(
+                            egui::Color32::GRAY,
+                            format!("Skipped: {name} ({reason})"),
+                        );
+                    }
+
+                    match &self.last_result {
+                        Some(Ok(s)) if s.canceled => {
+                            ui.colored_label(egui::Color32::YELLOW, format!("Run canceled: {s}"));
+                        }
+                        Some(Ok(s)) => {
+                            ui.colored_label(egui::Color32::GREEN, format!("Run succeeded: {s}"));
+                        }
+                        Some(Err(e)) => {
+                            ui.colored_label(egui::Color32::RED, format!("Run failed: {e}"));
+                        }
+                        None => {}
+                    }
 
                     if let Some(e) = &self.last_save_err {
                         ui.colored_label(egui::Color32::YELLOW, format!("config save failed: {e}"));
                     }
 
+                    if !self.metrics.is_empty() {
+                        ui.separator();
+                        ui.label("Metrics");
+                        egui::Grid::new("metrics_grid")
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for (k, v) in &self.metrics {
+                                    ui.label(k);
+                                    ui.label(v.to_string());
+                                    ui.end_row();
+                                }
+                            });
+                    }
+
                     ui.separator();
-                    ui.label("Logs");
+                    ui.horizontal(|ui| {
+                        ui.label("Logs");
+                        if ui.button("Copy logs").clicked() {
+                            let text = self.logs_text();
+                            ui.output_mut(|o| o.copied_text = text);
+                        }
+                        if ui.button("Save logs…").clicked() {
+                            if let Some(p) = rfd::FileDialog::new()
+                                .add_filter("txt", &["txt", "log"])
+                                .set_file_name("svza.log")
+                                .save_file()
+                            {
+                                if let Err(e) = std::fs::write(&p, self.logs_text()) {
+                                    self.push_log(format!("[error] save logs failed: {e}"));
+                                }
+                            }
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Filter");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.log_search)
+                                .hint_text("search logs")
+                                .desired_width(220.0),
+                        );
+                        ui.toggle_value(&mut self.log_only_warn, "[warn]");
+                        ui.toggle_value(&mut self.log_only_error, "[error]");
+                        if ui.small_button("Clear").clicked() {
+                            self.log_search.clear();
+                            self.log_only_warn = false;
+                            self.log_only_error = false;
+                        }
+                    });
+                    let needle = self.log_search.trim().to_lowercase();
+                    let filtering = !needle.is_empty() || self.log_only_warn || self.log_only_error;
                     egui::ScrollArea::vertical()
                         .stick_to_bottom(true)
                         .show(ui, |ui| {
+                            if self.logs_trimmed > 0 {
+                                ui.weak(format!("… {} earlier lines trimmed", self.logs_trimmed));
+                            }
+                            let mut shown = 0usize;
                             for line in &self.logs {
-                                ui.label(line);
+                                if self.log_line_visible(line, &needle) {
+                                    ui.label(line);
+                                    shown += 1;
+                                }
+                            }
+                            if filtering {
+                                ui.weak(format!("{shown} of {} lines match", self.logs.len()));
                             }
                         });
                 }
@@ -358,3 +753,34 @@ impl eframe::App for SvZaApp {
         ctx.request_repaint();
     }
 }
+
+/// Describes what a run would touch in `out_root`, or None when the folder is missing or empty.
+fn overwrite_summary(out_root: &std::path::Path) -> Option<String> {
+    let mut names: Vec<String> = std::fs::read_dir(out_root)
+        .ok()?
+        .flatten()
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .collect();
+    if names.is_empty() {
+        return None;
+    }
+    names.sort();
+
+    let mut out = format!(
+        "{} already contains {} entries:\n",
+        out_root.display(),
+        names.len()
+    );
+    const SHOWN: usize = 12;
+    for n in names.iter().take(SHOWN) {
+        out.push_str(&format!("  {n}\n"));
+    }
+    if names.len() > SHOWN {
+        out.push_str(&format!("  … and {} more\n", names.len() - SHOWN));
+    }
+    out.push_str(
+        "\nRunning will overwrite converted pm folders and patch the catalog, \
+         param arrays and personal data in place (originals are kept as .bak).",
+    );
+    Some(out)
+}
@@ -1,9 +1,13 @@
 use crate::{
-    backend::names,
+    backend::{
+        catalog::{read_catalog_doc, scan_existing_pm_variants},
+        names, textures,
+    },
     config::AppConfig,
     fb::trpmcatalog::CatalogDoc,
-    paths::find_under,
+    paths::{detect_sv_layout, find_under, parse_model_path},
     template::{preferred_template_dirs, DonorTemplate, Key, TemplateStore},
+    ui::util::open_folder,
 };
 use eframe::egui;
 use std::{
@@ -19,6 +23,7 @@ struct Row {
     name: String,
     pm_variant: String,
     in_za: bool,
+    has_assets: bool,
 }
 
 pub struct DonorsUi {
@@ -33,6 +38,8 @@ pub struct DonorsUi {
     donors: Vec<Row>,
     targets: Vec<Row>,
     donor_by_key: BTreeMap<Key, Row>,
+    /// (species, form) pairs whose SV targets are split by gender
+    gendered_targets: BTreeSet<(u16, u16)>,
 
     current_donor: Option<Key>,
     target_selected: BTreeSet<usize>,
@@ -41,6 +48,18 @@ pub struct DonorsUi {
     donor_search: String,
     target_search: String,
     show_in_za: bool,
+    allow_missing_assets: bool,
+    /// Keep "Set Pokemon" ordered by species after every edit instead of click order
+    auto_sort_selected: bool,
+    /// Assigning a donor also assigns it to the other genders of the same species/form
+    assign_all_genders: bool,
+    notice: Option<String>,
+    /// Template targets with no row in the loaded SV catalog
+    unknown_targets: Vec<Key>,
+
+    poke_root: Option<PathBuf>,
+    /// SV icon of the single selected target, decoded in-process; keyed by pm_variant
+    icon_preview: Option<(String, Result<egui::TextureHandle, String>)>,
 }
 
 impl DonorsUi {
@@ -66,16 +85,27 @@ impl DonorsUi {
             donors: Vec::new(),
             targets: Vec::new(),
             donor_by_key: BTreeMap::new(),
+            gendered_targets: BTreeSet::new(),
             current_donor: None,
             target_selected: BTreeSet::new(),
             last_clicked_target: None,
             donor_search: String::new(),
             target_search: String::new(),
             show_in_za: false,
+            allow_missing_assets: false,
+            auto_sort_selected: false,
+            assign_all_genders: false,
+            notice: None,
+            unknown_targets: Vec::new(),
+            poke_root: None,
+            icon_preview: None,
         }
     }
 
     fn mark_dirty(&mut self) {
+        if self.auto_sort_selected {
+            self.tpl.sort_selected_targets();
+        }
         self.dirty = true;
         self.last_edit = Instant::now();
     }
@@ -111,11 +141,13 @@ impl DonorsUi {
             za_dump,
             "ik_pokemon/catalog/catalog/poke_resource_table.trpmcatalog",
             "poke_resource_table.trpmcatalog",
+            cfg.follow_symlinks,
         );
         let sv_cat = find_under(
             sv_root,
             "catalog/catalog/poke_resource_table.trpmcatalog",
             "poke_resource_table.trpmcatalog",
+            cfg.follow_symlinks,
         );
         let (Ok(za_cat), Ok(sv_cat)) = (za_cat, sv_cat) else {
             return;
@@ -125,16 +157,40 @@ impl DonorsUi {
             return;
         };
 
-        let name_map = names::load_monsname_map(za_dump, &self.tpl.language).unwrap_or_default();
+        let name_map =
+            names::load_monsname_map(za_dump, &self.tpl.language, false).unwrap_or_default();
         let za_keys: BTreeSet<Key> = za_doc.entries.iter().map(|e| Key::from(e.key)).collect();
-
-        self.donors = build_rows(&za_doc, &name_map, &za_keys, true);
-        self.targets = build_rows(&sv_doc, &name_map, &za_keys, false);
+        self.poke_root = detect_sv_layout(sv_root).map(|(_, poke_root)| poke_root);
+        let existing = self.poke_root.as_deref().map(scan_existing_pm_variants);
+        self.icon_preview = None;
+
+        let name_format = cfg.species_name_format.as_str();
+        self.donors = build_rows(&za_doc, &name_map, name_format, &za_keys, true, None);
+        self.targets = build_rows(
+            &sv_doc,
+            &name_map,
+            name_format,
+            &za_keys,
+            false,
+            existing.as_ref(),
+        );
         self.donor_by_key = self.donors.iter().cloned().map(|r| (r.key, r)).collect();
+        self.gendered_targets = gendered_groups(&self.targets);
 
         if self.current_donor.is_none() {
             self.current_donor = self.tpl.default_donor;
         }
+        self.validate_targets();
+    }
+
+    /// Re-checks the template's targets against the SV catalog; a no-op until one is loaded
+    fn validate_targets(&mut self) {
+        if self.targets.is_empty() {
+            self.unknown_targets.clear();
+            return;
+        }
+        let known: BTreeSet<Key> = self.targets.iter().map(|r| r.key).collect();
+        self.unknown_targets = self.tpl.unknown_targets(&known);
     }
 
     pub fn ui(&mut self, ui: &mut egui::Ui, cfg: &mut AppConfig) {
@@ -158,6 +214,7 @@ impl DonorsUi {
                             self.current_donor = self.tpl.default_donor;
                             self.target_selected.clear();
                             self.last_clicked_target = None;
+                            self.validate_targets();
                         }
                     }
                 }
@@ -178,6 +235,34 @@ impl DonorsUi {
             }
         });
 
+        if !self.unknown_targets.is_empty() {
+            ui.horizontal(|ui| {
+                let show = self
+                    .unknown_targets
+                    .iter()
+                    .take(10)
+                    .map(|k| format!("{}:{}:{}", k.species, k.form, k.gender))
+                    .collect::<Vec<_>>();
+                ui.colored_label(
+                    egui::Color32::YELLOW,
+                    format!(
+                        "{} template target(s) not in the SV catalog (first 10): {}",
+                        self.unknown_targets.len(),
+                        show.join(", ")
+                    ),
+                );
+                if ui.button("Drop unknown targets").clicked() {
+                    let unknown: BTreeSet<Key> = self.unknown_targets.drain(..).collect();
+                    let dropped = self.tpl.drop_targets(&unknown);
+                    self.notice = Some(format!(
+                        "dropped {} unknown target(s) ({dropped} template entries)",
+                        unknown.len()
+                    ));
+                    self.mark_dirty();
+                }
+            });
+        }
+
         ui.separator();
 
         ui.horizontal(|ui| {
@@ -191,6 +276,11 @@ impl DonorsUi {
             ui.checkbox(&mut cfg.generate_reports, "Generate reports");
             ui.add_space(8.0);
             ui.checkbox(&mut cfg.no_head_look_at, "No head look-at (tralk)");
+            ui.add_space(8.0);
+            ui.checkbox(&mut cfg.mirror_genders, "Mirror genders")
+                .on_hover_text(
+                    "Also add the other genders' SV entries that share a selected mon's model",
+                );
             if ui.button("Clear assignments").clicked() {
                 self.tpl.assignments.clear();
                 self.mark_dirty();
@@ -311,23 +401,78 @@ impl DonorsUi {
             right.horizontal(|ui| {
                 if ui.button("Assign donor to selected").clicked() {
                     if let Some(dk) = self.current_donor {
-                        for &idx in &self.target_selected {
-                            if let Some(t) = self.targets.get(idx) {
-                                self.tpl.set_assignment(t.key, dk);
-                            }
-                        }
-                        self.mark_dirty();
+                        self.assign_to_selected(dk);
                     }
                 }
+                let mut copy_from = None;
+                ui.add_enabled_ui(
+                    !assignments.is_empty() && !self.target_selected.is_empty(),
+                    |ui| {
+                        ui.menu_button("Copy donor from…", |ui| {
+                            egui::ScrollArea::vertical()
+                                .max_height(320.0)
+                                .show(ui, |ui| {
+                                    for (target, donor) in &assignments {
+                                        let target_s = self
+                                            .targets
+                                            .iter()
+                                            .find(|r| r.key == *target)
+                                            .map_or_else(
+                                                || format!("#{}", target.species),
+                                                |r| r.name.clone(),
+                                            );
+                                        let donor_s = self.donor_by_key.get(donor).map_or_else(
+                                            || format!("#{}", donor.species),
+                                            |r| r.name.clone(),
+                                        );
+                                        let label =
+                                            format!("{target_s} ({target})  donor: {donor_s}");
+                                        if ui.button(label).clicked() {
+                                            copy_from = Some(*donor);
+                                            ui.close_menu();
+                                        }
+                                    }
+                                });
+                        })
+                        .response
+                        .on_hover_text(
+                            "Give the selected targets the donor another target already has",
+                        );
+                    },
+                );
+                if let Some(dk) = copy_from {
+                    self.assign_to_selected(dk);
+                }
                 if ui.button("Toggle selected as convert").clicked() {
+                    let mut blocked = 0usize;
                     for &idx in &self.target_selected {
                         if let Some(t) = self.targets.get(idx) {
+                            if !t.has_assets
+                                && !self.allow_missing_assets
+                                && !self.tpl.selected_targets.contains(&t.key)
+                            {
+                                blocked += 1;
+                                continue;
+                            }
                             toggle_selected(&mut self.tpl.selected_targets, t.key);
                         }
                     }
+                    self.notice = (blocked > 0)
+                        .then(|| format!("{blocked} target(s) have no assets on disk; not added"));
                     self.mark_dirty();
                 }
             });
+            right.horizontal(|ui| {
+                ui.checkbox(
+                    &mut self.allow_missing_assets,
+                    "Allow targets without assets",
+                );
+                ui.checkbox(&mut self.assign_all_genders, "Assign to all genders");
+                if let Some(n) = &self.notice {
+                    ui.colored_label(egui::Color32::YELLOW, n);
+                }
+            });
+            self.show_icon_preview(right);
 
             let avail_h = right.available_height();
             let min_set_h = 160.0;
@@ -342,6 +487,7 @@ impl DonorsUi {
                     .id_source("targets_list")
                     .auto_shrink([false, false])
                     .show(ui, |ui| {
+                        let mut prev_group: Option<(u16, u16)> = None;
                         for (idx, r) in self
                             .targets
                             .iter()
@@ -363,15 +509,41 @@ impl DonorsUi {
                                 .and_then(|k| self.donor_by_key.get(&k).map(|r| r.name.clone()))
                                 .unwrap_or_else(|| "-".to_string());
 
+                            // gender-split species/forms: first gender carries the name,
+                            // the rest are indented sub-rows
+                            let group = (r.key.species, r.key.form);
+                            let gendered = self.gendered_targets.contains(&group);
+                            let sub_row = gendered && prev_group == Some(group);
+                            prev_group = Some(group);
+                            let head = if sub_row {
+                                format!("gender {}", r.key.gender)
+                            } else if gendered {
+                                format!("{}  (#{})  gender {}", r.name, r.key.species, r.key.gender)
+                            } else {
+                                format!("{}  (#{})", r.name, r.key.species)
+                            };
+
                             let label = format!(
-                                "{}  (#{})  [{}]  donor: {}{}",
-                                r.name,
-                                r.key.species,
+                                "{}  [{}]  donor: {}{}{}",
+                                head,
                                 if is_enabled { "convert" } else { "skip" },
                                 donor_s,
-                                if r.in_za { "  (in ZA)" } else { "" }
+                                if r.in_za { "  (in ZA)" } else { "" },
+                                if r.has_assets { "" } else { "  (no assets)" }
                             );
-                            let resp = ui.selectable_label(is_sel, label);
+                            let mut text = egui::RichText::new(label);
+                            if !r.has_assets {
+                                text = text.strikethrough();
+                            }
+                            let resp = if sub_row {
+                                ui.horizontal(|ui| {
+                                    ui.add_space(24.0);
+                                    ui.selectable_label(is_sel, text)
+                                })
+                                .inner
+                            } else {
+                                ui.selectable_label(is_sel, text)
+                            };
                             if resp.clicked() {
                                 apply_selection_click(
                                     idx,
@@ -394,6 +566,17 @@ impl DonorsUi {
                     self.last_clicked_target = None;
                     self.mark_dirty();
                 }
+                if ui.button("Sort by species").clicked() && self.tpl.sort_selected_targets() {
+                    self.mark_dirty();
+                }
+                if ui
+                    .checkbox(&mut self.auto_sort_selected, "Keep sorted")
+                    .changed()
+                    && self.auto_sort_selected
+                    && self.tpl.sort_selected_targets()
+                {
+                    self.mark_dirty();
+                }
             });
 
             right.allocate_ui(
@@ -464,6 +647,84 @@ impl DonorsUi {
     }
 }
 
+impl DonorsUi {
+    /// Assigns `donor` to the selected targets (and their other genders with "Assign to all genders")
+    fn assign_to_selected(&mut self, donor: Key) {
+        let picked = self
+            .target_selected
+            .iter()
+            .filter_map(|&idx| self.targets.get(idx))
+            .map(|t| t.key)
+            .collect::<BTreeSet<_>>();
+        let groups = picked
+            .iter()
+            .map(|k| (k.species, k.form))
+            .collect::<BTreeSet<_>>();
+        for t in &self.targets {
+            let k = t.key;
+            if picked.contains(&k)
+                || (self.assign_all_genders && groups.contains(&(k.species, k.form)))
+            {
+                self.tpl.set_assignment(k, donor);
+            }
+        }
+        self.mark_dirty();
+    }
+
+    fn show_icon_preview(&mut self, ui: &mut egui::Ui) {
+        let single = (self.target_selected.len() == 1)
+            .then(|| self.target_selected.first())
+            .flatten()
+            .and_then(|&i| self.targets.get(i));
+        let Some(pm_variant) = single.map(|r| r.pm_variant.clone()) else {
+            return;
+        };
+        if self.icon_preview.as_ref().map(|(k, _)| k) != Some(&pm_variant) {
+            let loaded = self
+                .load_icon(ui.ctx(), &pm_variant)
+                .map_err(|e| format!("{e:#}"));
+            self.icon_preview = Some((pm_variant, loaded));
+        }
+        let Some((_, preview)) = &self.icon_preview else {
+            return;
+        };
+        ui.horizontal(|ui| match preview {
+            Ok(tex) => {
+                ui.add(egui::Image::new((tex.id(), egui::vec2(64.0, 64.0))));
+                ui.label(format!("icon {}x{}", tex.size()[0], tex.size()[1]));
+            }
+            Err(e) => {
+                ui.weak(format!("no icon preview: {e}"));
+            }
+        });
+    }
+
+    fn load_icon(
+        &self,
+        ctx: &egui::Context,
+        pm_variant: &str,
+    ) -> anyhow::Result<egui::TextureHandle> {
+        let poke_root = self
+            .poke_root
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("SV layout not detected"))?;
+        let pm = pm_variant.get(..6).unwrap_or(pm_variant);
+        let dir = poke_root.join("data").join(pm).join(pm_variant);
+        let name = format!("{pm_variant}_00.bntx");
+        let path = [dir.join(&name), dir.join("icon").join(&name)]
+            .into_iter()
+            .find(|p| p.is_file())
+            .ok_or_else(|| anyhow::anyhow!("{name} not found"))?;
+        let (w, h, rgba) = textures::decode_bntx_rgba(&path)?;
+        let img = egui::ColorImage::from_rgba_unmultiplied([w as usize, h as usize], &rgba);
+        Ok(ctx.load_texture(
+            format!("icon_{pm_variant}"),
+            img,
+            egui::TextureOptions::LINEAR,
+        ))
+    }
+}
+
 fn toggle_selected(list: &mut Vec<Key>, k: Key) {
     if let Some(i) = list.iter().position(|x| *x == k) {
         list.remove(i);
@@ -506,6 +767,21 @@ fn apply_selection_click(
     }
 }
 
+fn gendered_groups(rows: &[Row]) -> BTreeSet<(u16, u16)> {
+    let mut genders = BTreeMap::<(u16, u16), BTreeSet<u8>>::new();
+    for r in rows {
+        genders
+            .entry((r.key.species, r.key.form))
+            .or_default()
+            .insert(r.key.gender);
+    }
+    genders
+        .into_iter()
+        .filter(|(_, g)| g.len() > 1)
+        .map(|(k, _)| k)
+        .collect()
+}
+
 fn row_match(r: &Row, q: &str) -> bool {
     let q = q.trim();
     if q.is_empty() {
@@ -517,16 +793,13 @@ fn row_match(r: &Row, q: &str) -> bool {
         || format!("{}", r.key.species).contains(&ql)
 }
 
-fn read_catalog_doc(path: &Path) -> anyhow::Result<CatalogDoc> {
-    let b = fs::read(path)?;
-    crate::fb::trpmcatalog::read_doc(b)
-}
-
 fn build_rows(
     doc: &CatalogDoc,
     name_map: &BTreeMap<u16, String>,
+    name_format: &str,
     za_keys: &BTreeSet<Key>,
     is_za: bool,
+    existing_pm_variants: Option<&BTreeSet<(String, String)>>,
 ) -> Vec<Row> {
     let mut out = Vec::with_capacity(doc.entries.len());
     for e in &doc.entries {
@@ -535,34 +808,19 @@ fn build_rows(
             .get(&key.species)
             .cloned()
             .filter(|s| !s.is_empty())
-            .unwrap_or_else(|| format!("#{:#05}", key.species));
-        let (_pm, pm_variant) = parse_pm_variant(&e.model_path).unwrap_or_default();
+            .unwrap_or_else(|| names::placeholder_name(name_format, key.species));
+        let (pm, pm_variant) = parse_model_path(&e.model_path).unwrap_or_default();
+        let has_assets = existing_pm_variants
+            .map(|set| set.contains(&(pm, pm_variant.clone())))
+            .unwrap_or(true);
         out.push(Row {
             key,
             name,
             pm_variant,
             in_za: if is_za { true } else { za_keys.contains(&key) },
+            has_assets,
         });
     }
     out.sort_by_key(|r| (r.key.species, r.key.form, r.key.gender));
     out
 }
-
-fn parse_pm_variant(model_path: &str) -> Option<(String, String)> {
-    let mp = model_path.replace('\\', "/");
-    let mut parts = mp.split('/').filter(|s| !s.is_empty());
-    let pm = parts.next()?.to_string();
-    let pm_variant = parts.next()?.to_string();
-    Some((pm, pm_variant))
-}
-
-fn open_folder(path: &Path) {
-    #[cfg(target_os = "windows")]
-    let cmd = "explorer";
-    #[cfg(target_os = "macos")]
-    let cmd = "open";
-    #[cfg(all(unix, not(target_os = "macos")))]
-    let cmd = "xdg-open";
-
-    let _ = std::process::Command::new(cmd).arg(path).spawn();
-}
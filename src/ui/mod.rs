@@ -1,4 +1,6 @@
 mod app;
 mod donors;
+mod util;
 
 pub use app::SvZaApp;
+pub use util::{can_open_folders, open_folder};
@@ -0,0 +1,28 @@
+use std::path::Path;
+
+/// Opens `path` in the platform file manager; failures are ignored.
+pub fn open_folder(path: &Path) {
+    #[cfg(target_os = "windows")]
+    let cmd = "explorer";
+    #[cfg(target_os = "macos")]
+    let cmd = "open";
+    #[cfg(all(unix, not(target_os = "macos")))]
+    let cmd = "xdg-open";
+
+    let _ = std::process::Command::new(cmd).arg(path).spawn();
+}
+
+/// False on CI and on X11/Wayland platforms with no display, where a file manager can't show up
+pub fn can_open_folders() -> bool {
+    if std::env::var_os("CI").is_some() {
+        return false;
+    }
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        std::env::var_os("DISPLAY").is_some() || std::env::var_os("WAYLAND_DISPLAY").is_some()
+    }
+    #[cfg(not(all(unix, not(target_os = "macos"))))]
+    {
+        true
+    }
+}